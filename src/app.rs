@@ -1,18 +1,26 @@
+use crate::cli::Target;
 use crate::components::{
-    app_shell, collapsible_panel_view, collapsible_panel_view_with_actions, file_tree_view,
-    git_status_view, icon, main_layout, tab_bar, tab_button, tab_button_with_menu, terminal_view,
-    FILE, FOLDER, GIT, REFRESH,
+    app_shell, background_tasks_view, collapsible_panel_view, collapsible_panel_view_with_actions, file_tree_view,
+    git_status_view, icon, main_layout, notification_center, save_scrollback, tab_bar, tab_button, tab_button_with_menu,
+    terminal_view, FILE, FOLDER, GIT, REFRESH, TASKS,
 };
 use crate::logging;
-use crate::model::{TerminalPane, WorkspaceTab};
+use crate::model::{BackgroundTask, TerminalPane, TmuxPaneRef, WorkspaceTab};
+use crate::services::config::{launchers_file_path, project_launchers_file_path, settings_file_path};
+use crate::services::config_watch::watch_config_files;
+use crate::services::diagnostics::Diagnostic;
+use crate::services::tasks::{discover_tasks, task_files, with_tasks};
+use crate::services::tmux::{set_tmux_event_handler, TmuxEvent, TmuxWindow};
 use crate::services::{
-    build_tree_entries, git_status_entries, load_launchers, AppState, Launcher,
-    save_state,
+    build_tree_entries, git_status_entries, launcher_changes, load_launchers, load_project_launchers,
+    load_settings, merge_launchers, read_settings, AppState, Launcher, LauncherFile,
+    save_state, take_workspace_scrollback, Settings,
 };
 use crate::theme::UiTheme;
 use floem::event::{Event, EventListener, EventPropagation};
 use floem::ext_event::{register_ext_trigger, ExtSendTrigger};
 use floem::keyboard::{Key, NamedKey};
+use floem::kurbo::Vec2;
 use floem::prelude::*;
 use floem::reactive::{Scope, create_effect, with_scope};
 use floem::style::CursorStyle;
@@ -22,8 +30,11 @@ use floem::views::editor::keypress::default_key_handler;
 use floem::views::editor::text::{SimpleStyling, WrapMethod};
 use floem::views::editor::text_document::TextDocument;
 use floem::views::editor::view::editor_container_view;
+use floem::views::editor::core::cursor::{Cursor, CursorMode};
 use floem::views::editor::Editor;
-use std::path::PathBuf;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
@@ -31,9 +42,30 @@ use std::time::Duration;
 #[cfg(target_os = "macos")]
 use std::process::Command;
 
+#[cfg(target_os = "macos")]
+use crate::components::{
+    run_launcher, set_tab_launch_handler, show_background_task, split_pane, start_pane_session,
+    track_background_tasks, TabLaunch,
+};
+#[cfg(target_os = "macos")]
+use crate::services::process_title::process_cwd;
+#[cfg(target_os = "macos")]
+use crate::services::remote_control::{
+    set_request_handler, start_server, visible_text, PaneAddress, RemoteCommand, Reply, RpcError,
+};
+#[cfg(target_os = "macos")]
+use crate::services::shell_profile::DEFAULT_PROFILE_NAME;
+#[cfg(target_os = "macos")]
+use crate::services::TerminalSession;
+
 static UI_WATCHDOG: OnceLock<()> = OnceLock::new();
 
-pub fn app_view(initial_state: AppState) -> impl IntoView {
+/// `targets` are the paths given on the command line (see `cli`).
+pub fn app_view(
+    initial_state: AppState,
+    state_problems: Vec<(PathBuf, Diagnostic)>,
+    targets: Vec<Target>,
+) -> impl IntoView {
     let theme = UiTheme::new();
     install_ui_watchdog();
     
@@ -42,31 +74,57 @@ pub fn app_view(initial_state: AppState) -> impl IntoView {
     for (i, path) in initial_state.workspaces.iter().enumerate() {
         initial_tabs.push(build_tab(i, path.clone()));
     }
+    let mut next_id_val = initial_tabs.len();
+    let mut active_id = initial_tabs
+        .get(initial_state.active_workspace_index)
+        .or(initial_tabs.first())
+        .map(|t| t.id);
+
+    // Paths from the command line; the last one ends up in front
+    for target in &targets {
+        active_id = Some(open_target(&mut initial_tabs, &mut next_id_val, target));
+    }
     
     // Fallback if empty
     if initial_tabs.is_empty() {
         let root = std::env::var("HOME")
             .map(PathBuf::from)
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-        initial_tabs.push(build_tab(0, root));
+        initial_tabs.push(build_tab(next_id_val, root));
+        next_id_val += 1;
     }
     
-    let next_id_val = initial_tabs.last().map(|t| t.id + 1).unwrap_or(1);
-    
-    let active_id = if initial_state.active_workspace_index < initial_tabs.len() {
-        initial_tabs[initial_state.active_workspace_index].id
-    } else {
-        initial_tabs.first().map(|t| t.id).unwrap_or(0)
-    };
+    let active_id = active_id.unwrap_or(initial_tabs[0].id);
 
-    let launchers = RwSignal::new(load_launchers());
+    let global_launchers = RwSignal::new(load_launchers());
+    let (initial_settings, settings_diagnostics) = load_settings();
+    let settings = RwSignal::new(initial_settings);
+    let settings_problems = RwSignal::new(settings_diagnostics);
     let tabs = RwSignal::new(initial_tabs);
     let active_tab = RwSignal::new(active_id);
     let next_tab_id = RwSignal::new(next_id_val);
+    let workspace_file_changes = install_config_reload(tabs, global_launchers, settings, settings_problems);
+    let (launchers, launcher_problems) =
+        install_workspace_launchers(tabs, active_tab, global_launchers, workspace_file_changes, settings);
 
-    // Effect to auto-save state
+    // Problems in every config file, for the control center's banner
+    let config_problems = RwSignal::new(Vec::new());
     create_effect(move |_| {
-        let current_tabs = tabs.get();
+        let mut problems = state_problems.clone();
+        problems.extend(settings_problems.get());
+        problems.extend(launcher_problems.get());
+        if config_problems.with_untracked(|current| *current != problems) {
+            config_problems.set(problems);
+        }
+    });
+    install_tmux_tabs(tabs, active_tab, next_tab_id);
+    install_remote_control(tabs, active_tab, next_tab_id, global_launchers, settings);
+    install_tab_launches(tabs, active_tab, next_tab_id, settings);
+
+    // Effect to auto-save state (tmux windows are not workspaces of their own)
+    create_effect(move |_| {
+        let current_tabs: Vec<WorkspaceTab> =
+            tabs.get().into_iter().filter(|t| t.tmux_window.is_none()).collect();
         let active_id = active_tab.get();
         
         let paths: Vec<PathBuf> = current_tabs.iter().map(|t| t.root.get()).collect();
@@ -180,13 +238,17 @@ pub fn app_view(initial_state: AppState) -> impl IntoView {
     let tabs_with_add = h_stack((tab_list, new_tab_button))
         .style(|s| s.flex_row().col_gap(6.0).items_center());
 
-    let tabs_bar = tab_bar(tabs_with_add, empty(), theme);
+    let tabs_bar = tab_bar(
+        tabs_with_add,
+        notification_center(tabs, active_tab, settings, theme),
+        theme,
+    );
 
     let content = dyn_container(move || active_tab.get(), move |tab_id| {
         let tabs_vec = tabs.get();
         let tab = tabs_vec.into_iter().find(|tab| tab.id == tab_id);
         match tab {
-            Some(tab) => workspace_view(tab, launchers, theme).into_any(),
+            Some(tab) => workspace_view(tab, launchers, config_problems, settings, theme).into_any(),
             None => label(|| "No workspace").into_any(),
         }
     })
@@ -226,6 +288,643 @@ pub fn app_view(initial_state: AppState) -> impl IntoView {
             }
             EventPropagation::Continue
         })
+        .on_event_cont(EventListener::WindowClosed, move |_| {
+            save_scrollback(&tabs.get_untracked());
+        })
+}
+
+/// Launchers of the active workspace: the global ones with those of the workspace's
+/// `.tide/launchers.json` and its tasks merged in, and the problems found in both files
+/// (with profiles the settings do not have). The project's files are read again on a
+/// background thread when another workspace (or folder) becomes active or one of them
+/// changes; other changes to the tabs or the settings do not read them.
+fn install_workspace_launchers(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    global_launchers: RwSignal<LauncherFile>,
+    workspace_file_changes: RwSignal<u64>,
+    settings: RwSignal<Settings>,
+) -> (RwSignal<Vec<Launcher>>, RwSignal<Vec<(PathBuf, Diagnostic)>>) {
+    let launchers = RwSignal::new(Vec::new());
+    let problems = RwSignal::new(Vec::new());
+
+    // Only set when they change, so the effects below ignore the rest of the tabs and
+    // settings
+    let active_root = RwSignal::new(None::<PathBuf>);
+    create_effect(move |_| {
+        let active_id = active_tab.get();
+        let root = tabs.with(|tabs| tabs.iter().find(|tab| tab.id == active_id).map(|tab| tab.root.get()));
+        if active_root.with_untracked(|current| *current != root) {
+            active_root.set(root);
+        }
+    });
+    let profiles = RwSignal::new(Vec::new());
+    create_effect(move |_| {
+        let current = settings.with(|settings| settings.profiles.clone());
+        if profiles.with_untracked(|profiles| *profiles != current) {
+            profiles.set(current);
+        }
+    });
+
+    // Scans of the active root, numbered so a slow one cannot replace a newer result
+    type Scan = (u64, PathBuf, LauncherFile, Vec<Launcher>);
+    let inbox: Arc<Mutex<Vec<Scan>>> = Arc::new(Mutex::new(Vec::new()));
+    let trigger = ExtSendTrigger::new();
+    let scans = Rc::new(Cell::new(0u64));
+    let posted = Arc::clone(&inbox);
+    create_effect(move |_| {
+        workspace_file_changes.track();
+        let Some(root) = active_root.get() else {
+            return;
+        };
+        scans.set(scans.get() + 1);
+        let scan = scans.get();
+        let posted = Arc::clone(&posted);
+        std::thread::spawn(move || {
+            let file = load_project_launchers(&root);
+            let tasks = discover_tasks(&root);
+            if let Ok(mut inbox) = posted.lock() {
+                inbox.push((scan, root, file, tasks));
+            }
+            register_ext_trigger(trigger);
+        });
+    });
+
+    // The active root's project file and tasks, once read
+    let project = RwSignal::new(None::<(PathBuf, LauncherFile, Vec<Launcher>)>);
+    // Last project files read, whose launchers stay when an edit breaks one
+    let project_files: Rc<RefCell<HashMap<PathBuf, LauncherFile>>> = Rc::new(RefCell::new(HashMap::new()));
+    let applied = Rc::new(Cell::new(0u64));
+    create_effect(move |_| {
+        trigger.track();
+        let results = inbox.lock().map(|mut inbox| std::mem::take(&mut *inbox)).unwrap_or_default();
+        let Some((scan, root, mut file, tasks)) = results.into_iter().max_by_key(|(scan, ..)| *scan) else {
+            return;
+        };
+        if scan <= applied.get() {
+            return;
+        }
+        applied.set(scan);
+        let mut project_files = project_files.borrow_mut();
+        if let Some(previous) = project_files.get(&file.path) {
+            file = file.or_previous(previous);
+            if file != *previous {
+                log_launcher_reload(previous, &file);
+            }
+        }
+        project_files.insert(file.path.clone(), file.clone());
+        project.set(Some((root, file, tasks)));
+    });
+
+    create_effect(move |_| {
+        let root = active_root.get();
+        let mut files = vec![global_launchers.get()];
+        // Until the new root is read, a previous workspace's launchers are not shown
+        let tasks = project.with(|project| match project {
+            Some((scanned, file, tasks)) if Some(scanned) == root.as_ref() => {
+                files.push(file.clone());
+                tasks.clone()
+            }
+            _ => Vec::new(),
+        });
+
+        let found: Vec<(PathBuf, Diagnostic)> = profiles.with(|profiles| {
+            files
+                .iter()
+                .flat_map(|file| {
+                    let unknown = file.unknown_profiles(profiles);
+                    file.diagnostics.iter().cloned().chain(unknown).map(|diagnostic| (file.path.clone(), diagnostic))
+                })
+                .collect()
+        });
+        let mut files = files.into_iter().map(|file| file.config.launchers);
+        let global = files.next().unwrap_or_default();
+        let merged = with_tasks(merge_launchers(global, files.next().unwrap_or_default()), tasks);
+
+        // Unchanged lists keep the control center's buttons as they are
+        if launchers.with_untracked(|current| *current != merged) {
+            launchers.set(merged);
+        }
+        if problems.with_untracked(|current| *current != found) {
+            problems.set(found);
+        }
+    });
+    (launchers, problems)
+}
+
+/// Reload `launchers.json` and `settings.json` when they are edited, and watch every open
+/// workspace's `.tide/launchers.json` and task files. Returns a counter bumped when one
+/// of those changed, for `install_workspace_launchers` to read them again.
+fn install_config_reload(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    global_launchers: RwSignal<LauncherFile>,
+    settings: RwSignal<Settings>,
+    settings_problems: RwSignal<Vec<(PathBuf, Diagnostic)>>,
+) -> RwSignal<u64> {
+    let workspace_file_changes = RwSignal::new(0);
+    let watched: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+    let inbox: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+    let trigger = ExtSendTrigger::new();
+
+    let watch_list = Arc::clone(&watched);
+    create_effect(move |_| {
+        let mut paths = vec![launchers_file_path(), settings_file_path()];
+        tabs.with(|tabs| {
+            for root in tabs.iter().map(|tab| tab.root.get()) {
+                paths.push(project_launchers_file_path(&root));
+                paths.extend(task_files(&root));
+            }
+        });
+        if let Ok(mut watched) = watch_list.lock() {
+            *watched = paths;
+        }
+    });
+
+    let posted = Arc::clone(&inbox);
+    watch_config_files(watched, move |changed| {
+        if let Ok(mut paths) = posted.lock() {
+            paths.extend(changed);
+        }
+        register_ext_trigger(trigger);
+    });
+
+    create_effect(move |_| {
+        trigger.track();
+        let changed = inbox
+            .lock()
+            .map(|mut paths| std::mem::take(&mut *paths))
+            .unwrap_or_default();
+        let mut workspace_changed = false;
+        for path in changed {
+            if path == launchers_file_path() {
+                let previous = global_launchers.get_untracked();
+                let file = load_launchers().or_previous(&previous);
+                log_launcher_reload(&previous, &file);
+                if file != previous {
+                    global_launchers.set(file);
+                }
+            } else if path == settings_file_path() {
+                reload_settings(settings, settings_problems);
+            } else {
+                workspace_changed = true;
+            }
+        }
+        if workspace_changed {
+            workspace_file_changes.update(|count| *count += 1);
+        }
+    });
+    workspace_file_changes
+}
+
+/// Log what a reload of `file` changed since `previous`.
+fn log_launcher_reload(previous: &LauncherFile, file: &LauncherFile) {
+    let path = file.path.display();
+    if file.failed {
+        logging::log_line(
+            "WARN",
+            &format!("{path} failed to parse; keeping the previous {} launchers", file.config.launchers.len()),
+        );
+        return;
+    }
+    let changes = launcher_changes(&previous.config.launchers, &file.config.launchers);
+    let summary = if changes.is_empty() { "no changes".to_string() } else { changes.join(", ") };
+    logging::log_line("INFO", &format!("reloaded {path}: {summary}"));
+}
+
+/// Read `settings.json` again, keeping the current settings (and showing why) when it
+/// does not parse.
+fn reload_settings(settings: RwSignal<Settings>, problems: RwSignal<Vec<(PathBuf, Diagnostic)>>) {
+    match read_settings() {
+        Ok(reloaded) => {
+            if problems.with_untracked(|problems| !problems.is_empty()) {
+                problems.set(Vec::new());
+            }
+            let changed = settings.with_untracked(|current| current.changed_fields(&reloaded));
+            if changed.is_empty() {
+                logging::log_line("INFO", "reloaded settings: no changes");
+                return;
+            }
+            logging::log_line("INFO", &format!("reloaded settings: changed {}", changed.join(", ")));
+            settings.set(reloaded);
+        }
+        Err(diagnostic) => {
+            let path = settings_file_path();
+            logging::log_line("WARN", &format!("{}:{diagnostic}; keeping the previous settings", path.display()));
+            problems.set(vec![(path, diagnostic)]);
+        }
+    }
+}
+
+/// Show the windows of tmux servers running in control mode (`tmux -CC` in a pane) as
+/// workspace tabs, one terminal pane per tmux pane, kept in sync as windows change.
+fn install_tmux_tabs(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    next_tab_id: RwSignal<usize>,
+) {
+    let inbox: Arc<Mutex<Vec<TmuxEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let trigger = ExtSendTrigger::new();
+
+    let posted = Arc::clone(&inbox);
+    set_tmux_event_handler(Arc::new(move |event| {
+        if let Ok(mut events) = posted.lock() {
+            events.push(event);
+        }
+        register_ext_trigger(trigger);
+    }));
+
+    create_effect(move |_| {
+        trigger.track();
+        let events = inbox
+            .lock()
+            .map(|mut events| std::mem::take(&mut *events))
+            .unwrap_or_default();
+        for event in events {
+            apply_tmux_event(event, tabs, next_tab_id);
+        }
+
+        let active_id = active_tab.get_untracked();
+        let remaining = tabs.get_untracked();
+        if !remaining.iter().any(|tab| tab.id == active_id) {
+            if let Some(first) = remaining.first() {
+                active_tab.set(first.id);
+            }
+        }
+    });
+}
+
+fn apply_tmux_event(event: TmuxEvent, tabs: RwSignal<Vec<WorkspaceTab>>, next_tab_id: RwSignal<usize>) {
+    match event {
+        TmuxEvent::Windows { gateway, root, windows } => {
+            let mut current = tabs.get_untracked();
+            current.retain(|tab| match tab.tmux_window {
+                Some((tab_gateway, window)) if tab_gateway == gateway => {
+                    windows.iter().any(|candidate| candidate.id == window)
+                }
+                _ => true,
+            });
+
+            for window in &windows {
+                let name = format!("tmux: {}", window.name);
+                match current.iter().find(|tab| tab.tmux_window == Some((gateway, window.id))) {
+                    Some(tab) => {
+                        if tab.name.get_untracked() != name {
+                            tab.name.set(name);
+                        }
+                        sync_tmux_panes(tab, gateway, window);
+                    }
+                    None => {
+                        let id = next_tab_id.get_untracked();
+                        next_tab_id.set(id + 1);
+                        logging::log_line("INFO", &format!("tmux window @{} opened as tab {id}", window.id));
+                        let panes = window
+                            .panes
+                            .iter()
+                            .enumerate()
+                            .map(|(pane_id, layout)| {
+                                terminal_pane(pane_id, None, Some(TmuxPaneRef { gateway, pane: layout.pane }))
+                            })
+                            .collect();
+                        let tab = workspace_tab(id, root.clone(), panes);
+                        tab.name.set(name);
+                        current.push(WorkspaceTab {
+                            tmux_window: Some((gateway, window.id)),
+                            ..tab
+                        });
+                    }
+                }
+            }
+            tabs.set(current);
+        }
+        TmuxEvent::Exited { gateway, reason } => {
+            logging::log_line(
+                "INFO",
+                &format!("tmux gateway {gateway} ended ({}); closing its tabs", reason.unwrap_or_default()),
+            );
+            tabs.update(|tabs| {
+                tabs.retain(|tab| !matches!(tab.tmux_window, Some((tab_gateway, _)) if tab_gateway == gateway));
+            });
+        }
+    }
+}
+
+/// Match the panes of a tmux window tab to the window's current layout.
+fn sync_tmux_panes(tab: &WorkspaceTab, gateway: usize, window: &TmuxWindow) {
+    let mut panes = tab.terminal_panes.get_untracked();
+    let before: Vec<usize> = panes.iter().map(|pane| pane.id).collect();
+
+    panes.retain(|pane| match pane.tmux {
+        Some(tmux) => window.panes.iter().any(|layout| layout.pane == tmux.pane),
+        None => true,
+    });
+    for layout in &window.panes {
+        if !panes.iter().any(|pane| pane.tmux.is_some_and(|tmux| tmux.pane == layout.pane)) {
+            let id = tab.next_pane_id.get_untracked();
+            tab.next_pane_id.set(id + 1);
+            panes.push(terminal_pane(id, None, Some(TmuxPaneRef { gateway, pane: layout.pane })));
+        }
+    }
+    // Layout order; panes of Tide's own stay at the end
+    panes.sort_by_key(|pane| {
+        pane.tmux
+            .and_then(|tmux| window.panes.iter().position(|layout| layout.pane == tmux.pane))
+            .unwrap_or(usize::MAX)
+    });
+
+    if panes.iter().map(|pane| pane.id).collect::<Vec<_>>() != before {
+        tab.terminal_panes.set(panes);
+    }
+}
+
+/// Run remote control requests (see `services::remote_control`) on the UI thread and
+/// start listening on this instance's socket.
+#[cfg(target_os = "macos")]
+fn install_remote_control(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    next_tab_id: RwSignal<usize>,
+    global_launchers: RwSignal<LauncherFile>,
+    settings: RwSignal<Settings>,
+) {
+    let inbox: Arc<Mutex<Vec<(RemoteCommand, Reply)>>> = Arc::new(Mutex::new(Vec::new()));
+    let trigger = ExtSendTrigger::new();
+
+    let posted = Arc::clone(&inbox);
+    set_request_handler(Arc::new(move |command, reply| {
+        if let Ok(mut requests) = posted.lock() {
+            requests.push((command, reply));
+        }
+        register_ext_trigger(trigger);
+    }));
+
+    create_effect(move |_| {
+        trigger.track();
+        let requests = inbox
+            .lock()
+            .map(|mut requests| std::mem::take(&mut *requests))
+            .unwrap_or_default();
+        for (command, reply) in requests {
+            logging::log_line("INFO", &format!("Remote control: {command:?}"));
+            run_remote_command(command, reply, tabs, active_tab, next_tab_id, global_launchers, settings);
+        }
+    });
+
+    if let Err(err) = start_server() {
+        logging::log_line("ERROR", &format!("Remote control socket failed: {err}"));
+    }
+}
+
+/// Open a workspace tab for each `new_tab` launcher and type its command into the tab's
+/// first pane.
+#[cfg(target_os = "macos")]
+fn install_tab_launches(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    next_tab_id: RwSignal<usize>,
+    settings: RwSignal<Settings>,
+) {
+    let inbox: Arc<Mutex<Vec<TabLaunch>>> = Arc::new(Mutex::new(Vec::new()));
+    let trigger = ExtSendTrigger::new();
+
+    let posted = Arc::clone(&inbox);
+    set_tab_launch_handler(Arc::new(move |launch| {
+        if let Ok(mut launches) = posted.lock() {
+            launches.push(launch);
+        }
+        register_ext_trigger(trigger);
+    }));
+
+    create_effect(move |_| {
+        trigger.track();
+        let launches = inbox
+            .lock()
+            .map(|mut launches| std::mem::take(&mut *launches))
+            .unwrap_or_default();
+        for launch in launches {
+            let id = next_tab_id.get_untracked();
+            next_tab_id.set(id + 1);
+            let mut pane = terminal_pane(0, None, None);
+            pane.profile = launch.profile;
+            pane.should_focus.set(true);
+            let tab = workspace_tab(id, launch.root, vec![pane.clone()]);
+            tabs.update(|tabs| tabs.push(tab.clone()));
+            active_tab.set(id);
+
+            let written = start_pane_session(&tab, &pane, settings)
+                .and_then(|session| session.write(launch.input.as_bytes()));
+            if let Err(err) = written {
+                logging::log_line("ERROR", &format!("Launcher tab {id} failed to start: {err}"));
+            }
+        }
+    });
+}
+
+#[cfg(not(target_os = "macos"))]
+fn install_tab_launches(
+    _tabs: RwSignal<Vec<WorkspaceTab>>,
+    _active_tab: RwSignal<usize>,
+    _next_tab_id: RwSignal<usize>,
+    _settings: RwSignal<Settings>,
+) {
+}
+
+#[cfg(not(target_os = "macos"))]
+fn install_remote_control(
+    _tabs: RwSignal<Vec<WorkspaceTab>>,
+    _active_tab: RwSignal<usize>,
+    _next_tab_id: RwSignal<usize>,
+    _global_launchers: RwSignal<LauncherFile>,
+    _settings: RwSignal<Settings>,
+) {
+}
+
+/// The workspace tab and pane a request is about; without an address, the focused (or
+/// first) pane of the active tab.
+#[cfg(target_os = "macos")]
+fn resolve_pane(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    address: Option<PaneAddress>,
+) -> Result<(WorkspaceTab, TerminalPane), RpcError> {
+    let workspace_id = address.map_or_else(|| active_tab.get_untracked(), |address| address.workspace);
+    let tab = tabs
+        .get_untracked()
+        .into_iter()
+        .find(|tab| tab.id == workspace_id)
+        .ok_or_else(|| RpcError::invalid_params(format!("no workspace {workspace_id}")))?;
+    let panes = tab.terminal_panes.get_untracked();
+    let pane = match address {
+        Some(address) => panes.iter().find(|pane| pane.id == address.pane),
+        None => tab
+            .focused_pane_id
+            .get_untracked()
+            .and_then(|id| panes.iter().find(|pane| pane.id == id))
+            .or_else(|| panes.first()),
+    };
+    let pane = pane.cloned().ok_or_else(|| match address {
+        Some(address) => RpcError::invalid_params(format!("no pane {address}")),
+        None => RpcError::invalid_params(format!("workspace {workspace_id} has no panes")),
+    })?;
+    Ok((tab, pane))
+}
+
+/// `open_target` on the tab signals; the opened tab is brought to the front.
+#[cfg(target_os = "macos")]
+fn open_in_tabs(
+    target: &Target,
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    next_tab_id: RwSignal<usize>,
+) -> usize {
+    let mut current = tabs.get_untracked();
+    let mut next_id = next_tab_id.get_untracked();
+    let id = open_target(&mut current, &mut next_id, target);
+    if next_id != next_tab_id.get_untracked() {
+        next_tab_id.set(next_id);
+        tabs.set(current);
+    }
+    active_tab.set(id);
+    id
+}
+
+/// Bring the app, unhidden, in front of the other apps (`window.focus`).
+#[cfg(target_os = "macos")]
+fn activate_app() -> Result<(), RpcError> {
+    use objc2::MainThreadMarker;
+    use objc2_app_kit::NSApplication;
+
+    let mtm = MainThreadMarker::new().ok_or_else(|| RpcError::internal("not on the main thread"))?;
+    let app = NSApplication::sharedApplication(mtm);
+    app.unhide(None);
+    // `activate` only takes the focus when the app in front yields it, which a
+    // terminal running `tide` does not do
+    #[allow(deprecated)]
+    app.activateIgnoringOtherApps(true);
+    Ok(())
+}
+
+/// Session of a pane, started if the pane has not been shown yet.
+#[cfg(target_os = "macos")]
+fn pane_session(
+    tab: &WorkspaceTab,
+    pane: &TerminalPane,
+    settings: RwSignal<Settings>,
+) -> Result<Arc<TerminalSession>, RpcError> {
+    match pane.session.get_untracked() {
+        Some(session) => Ok(session),
+        None => start_pane_session(tab, pane, settings).map_err(|err| RpcError::internal(err.to_string())),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn run_remote_command(
+    command: RemoteCommand,
+    reply: Reply,
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    next_tab_id: RwSignal<usize>,
+    global_launchers: RwSignal<LauncherFile>,
+    settings: RwSignal<Settings>,
+) {
+    let address = |tab: &WorkspaceTab, pane: &TerminalPane| PaneAddress::new(tab.id, pane.id).to_string();
+    let result = match command {
+        RemoteCommand::OpenWorkspace { path } => match path.canonicalize() {
+            Ok(root) if root.is_dir() => {
+                let id = open_in_tabs(&Target::Workspace(root), tabs, active_tab, next_tab_id);
+                Ok(serde_json::json!({ "workspace": id }))
+            }
+            Ok(root) => Err(RpcError::invalid_params(format!("{} is not a directory", root.display()))),
+            Err(err) => Err(RpcError::invalid_params(format!("{}: {err}", path.display()))),
+        },
+        RemoteCommand::OpenFile { path, line, column } => match path.canonicalize() {
+            Ok(path) if path.is_file() => {
+                let id = open_in_tabs(&Target::File { path, line, column }, tabs, active_tab, next_tab_id);
+                Ok(serde_json::json!({ "workspace": id }))
+            }
+            Ok(path) => Err(RpcError::invalid_params(format!("{} is not a file", path.display()))),
+            Err(err) => Err(RpcError::invalid_params(format!("{}: {err}", path.display()))),
+        },
+        RemoteCommand::FocusWindow => activate_app().map(|()| serde_json::Value::Null),
+        RemoteCommand::ListPanes => {
+            // Working directories are looked up off the UI thread (`lsof` on macOS)
+            let active_id = active_tab.get_untracked();
+            let mut panes = Vec::new();
+            for tab in tabs.get_untracked() {
+                let focused = tab.focused_pane_id.get_untracked();
+                for pane in tab.terminal_panes.get_untracked() {
+                    let pid = pane.session.get_untracked().and_then(|session| session.foreground_pid());
+                    let entry = serde_json::json!({
+                        "pane": address(&tab, &pane),
+                        "workspace": tab.id,
+                        "workspace_name": tab.name.get_untracked(),
+                        "root": tab.root.get_untracked(),
+                        "title": pane.title.get_untracked(),
+                        "active": tab.id == active_id,
+                        "focused": focused == Some(pane.id),
+                    });
+                    panes.push((entry, pid));
+                }
+            }
+            std::thread::spawn(move || {
+                let panes: Vec<serde_json::Value> = panes
+                    .into_iter()
+                    .map(|(mut entry, pid)| {
+                        entry["cwd"] = serde_json::json!(pid.and_then(process_cwd));
+                        entry
+                    })
+                    .collect();
+                let _ = reply.send(Ok(serde_json::Value::Array(panes)));
+            });
+            return;
+        }
+        RemoteCommand::SplitPane { pane, profile } => resolve_pane(tabs, active_tab, pane).and_then(|(tab, pane)| {
+            if let Some(name) = &profile {
+                let known = settings.with_untracked(|settings| settings.profiles.iter().any(|p| &p.name == name));
+                if !known && name != DEFAULT_PROFILE_NAME {
+                    return Err(RpcError::invalid_params(format!("no shell profile {name:?}")));
+                }
+            }
+            split_pane(&tab, Some(pane.id), profile, settings)
+                .map(|new_pane| serde_json::json!({ "pane": address(&tab, &new_pane) }))
+                .map_err(|err| RpcError::internal(err.to_string()))
+        }),
+        RemoteCommand::SendText { pane, text } => resolve_pane(tabs, active_tab, pane).and_then(|(tab, pane)| {
+            pane_session(&tab, &pane, settings)?
+                .write(text.as_bytes())
+                .map(|_| serde_json::Value::Null)
+                .map_err(|err| RpcError::internal(err.to_string()))
+        }),
+        RemoteCommand::ReadText { pane } => resolve_pane(tabs, active_tab, pane).and_then(|(tab, pane)| {
+            let text = pane_session(&tab, &pane, settings)?.with_term(visible_text);
+            Ok(serde_json::json!({ "text": text }))
+        }),
+        RemoteCommand::LastOutput { pane } => resolve_pane(tabs, active_tab, pane).and_then(|(tab, pane)| {
+            pane_session(&tab, &pane, settings)?
+                .last_command_output()
+                .map(|text| serde_json::json!({ "text": text }))
+                .ok_or_else(|| RpcError::internal("no command has been entered in this pane"))
+        }),
+        RemoteCommand::RunLauncher { name, pane, inputs } => {
+            resolve_pane(tabs, active_tab, pane).and_then(|(tab, pane)| {
+                // Launchers of the pane's workspace, which need not be the active one
+                let root = tab.root.get_untracked();
+                let project = load_project_launchers(&root).config.launchers;
+                let global = global_launchers.with_untracked(|file| file.config.launchers.clone());
+                let launcher = with_tasks(merge_launchers(global, project), discover_tasks(&root))
+                    .into_iter()
+                    .find(|launcher| launcher.name == name)
+                    .ok_or_else(|| RpcError::invalid_params(format!("no launcher named {name:?}")))?;
+                run_launcher(&launcher, &tab, Some(pane.id), &inputs, settings)
+                    .map(|pane| serde_json::json!({ "pane": pane.map(|pane| address(&tab, &pane)) }))
+                    .map_err(|err| match err.kind() {
+                        // Bad or unanswered placeholders
+                        std::io::ErrorKind::InvalidInput => RpcError::invalid_params(err.to_string()),
+                        _ => RpcError::internal(err.to_string()),
+                    })
+            })
+        }
+    };
+    let _ = reply.send(result);
 }
 
 fn install_ui_watchdog() {
@@ -255,6 +954,8 @@ fn install_ui_watchdog() {
 fn workspace_view(
     tab: WorkspaceTab,
     launchers: RwSignal<Vec<Launcher>>,
+    config_problems: RwSignal<Vec<(PathBuf, Diagnostic)>>,
+    settings: RwSignal<Settings>,
     theme: UiTheme,
 ) -> impl IntoView {
     let workspace_name = tab.name;
@@ -294,6 +995,7 @@ fn workspace_view(
     // Collapse state signals - all expanded by default
     let files_expanded = RwSignal::new(true);
     let changes_expanded = RwSignal::new(true);
+    let tasks_expanded = RwSignal::new(true);
     let history_expanded = RwSignal::new(true);
 
     let refresh_changes = move || {
@@ -303,57 +1005,11 @@ fn workspace_view(
         logging::breadcrumb("manual git status refresh");
     };
 
-    let on_file_click = move |path: PathBuf, is_double_click: bool| {
-        logging::breadcrumb(format!("file clicked: {} dbl={}", path.display(), is_double_click));
-        
-        // 1. Check if already open
-        let mut tabs = editor_tabs.get_untracked();
-        if let Some(existing_idx) = tabs.iter().position(|t| t.path == path) {
-            let id = tabs[existing_idx].id;
-            if is_double_click {
-                tabs[existing_idx].is_pinned.set(true);
-            }
-            active_editor_tab_id.set(Some(id));
-            return;
-        }
-
-        // 2. Read file content
-        match crate::services::read_file_preview(&path) {
-            Ok(content) => {
-                let name = path.file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
-                
-                let new_id = tab.next_editor_tab_id.get_untracked();
-                tab.next_editor_tab_id.set(new_id + 1);
-
-                let new_tab = crate::model::EditorTab {
-                    id: new_id,
-                    path,
-                    name,
-                    is_pinned: RwSignal::new(is_double_click),
-                    content,
-                };
-
-                // 3. Find temporary tab to replace (only if single click and not forcing pin)
-                if !is_double_click {
-                    if let Some(temp_idx) = tabs.iter().position(|t| !t.is_pinned.get_untracked()) {
-                        // Replace temp tab
-                        tabs[temp_idx] = new_tab;
-                        editor_tabs.set(tabs);
-                        active_editor_tab_id.set(Some(new_id));
-                        return;
-                    }
-                }
-                
-                // Otherwise append (pinned or no temp tab found)
-                tabs.push(new_tab);
-                editor_tabs.set(tabs);
-                active_editor_tab_id.set(Some(new_id));
-            }
-            Err(e) => {
-                logging::log_line("ERROR", &format!("Failed to read file {}: {}", path.display(), e));
-            }
+    let on_file_click = {
+        let tab = tab.clone();
+        move |path: PathBuf, is_double_click: bool| {
+            logging::breadcrumb(format!("file clicked: {} dbl={}", path.display(), is_double_click));
+            tab.open_editor_file(path, is_double_click, None);
         }
     };
 
@@ -423,6 +1079,37 @@ fn workspace_view(
             }),
         theme,
     );
+    let background_tasks = tab.background_tasks;
+    #[cfg(target_os = "macos")]
+    track_background_tasks(background_tasks);
+    let on_open_task = {
+        let tab = tab.clone();
+        move |task: BackgroundTask| {
+            logging::breadcrumb(format!("background task clicked: {}", task.name));
+            #[cfg(target_os = "macos")]
+            show_background_task(&tab, &task);
+        }
+    };
+    let tasks_panel = collapsible_panel_view_with_actions(
+        "Tasks",
+        TASKS,
+        background_tasks_view(background_tasks, on_open_task, theme).style(|s| s.width_full()),
+        tasks_expanded,
+        label(|| "Clear")
+            .on_click_stop(move |_| {
+                // Finished runs go; their output stays in panes already showing it
+                background_tasks.update(|tasks| tasks.retain(|task| task.status.get_untracked().is_running()));
+            })
+            .style(move |s| {
+                s.font_size(11.0)
+                    .color(theme.text_muted)
+                    .padding_horiz(4.0)
+                    .border_radius(4.0)
+                    .hover(|s| s.background(theme.surface))
+                    .cursor(floem::style::CursorStyle::Pointer)
+            }),
+        theme,
+    );
     let history_panel = collapsible_panel_view(
         "History",
         FILE,
@@ -438,6 +1125,7 @@ fn workspace_view(
         project_header,
         files_panel,
         changes_panel,
+        tasks_panel,
         history_panel,
     ))
     .style(move |s| {
@@ -452,7 +1140,7 @@ fn workspace_view(
             .set(OverflowY, floem::taffy::Overflow::Hidden)
     });
 
-    let center_column = terminal_view(theme, tab, launchers);
+    let center_column = terminal_view(theme, tab, launchers, config_problems, settings);
     let right_column = editor_workspace_view(editor_tabs, active_editor_tab_id, theme);
 
     main_layout(left_column, center_column, right_column, theme)
@@ -669,7 +1357,34 @@ fn menu_item_view(label_text: &str, theme: UiTheme) -> impl IntoView {
     })
 }
 
-fn read_only_code_viewer(content: String, theme: UiTheme) -> impl IntoView {
+/// Font size and line height factor of the code viewer.
+const EDITOR_FONT_SIZE: usize = 12;
+const EDITOR_LINE_HEIGHT: f32 = 1.25;
+
+/// Lines kept visible above a line the viewer jumps to.
+const JUMP_CONTEXT_LINES: usize = 5;
+
+/// Byte offset of a 1-based line and column (in characters) of `text`, clamped to it.
+fn text_offset(text: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line_text = text[line_start..].split('\n').next().unwrap_or_default();
+    let column_bytes: usize = line_text
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(char::len_utf8)
+        .sum();
+    line_start + column_bytes
+}
+
+fn read_only_code_viewer(
+    content: String,
+    position: RwSignal<Option<(usize, usize)>>,
+    theme: UiTheme,
+) -> impl IntoView {
     // `floem::views::text_editor::text_editor` hard-codes `is_active = |_| true`, which means it
     // continuously calls `set_ime_cursor_area` even when it isn't focused. That breaks terminal
     // IME: the OS preedit UI appears anchored to the editor instead of the focused terminal.
@@ -679,19 +1394,32 @@ fn read_only_code_viewer(content: String, theme: UiTheme) -> impl IntoView {
 
     let mut style_builder = SimpleStyling::builder();
     style_builder
-        .font_size(12)
-        .line_height(1.25)
+        .font_size(EDITOR_FONT_SIZE)
+        .line_height(EDITOR_LINE_HEIGHT)
         .font_family(
             FamilyOwned::parse_list("Menlo, Monaco, 'Courier New', monospace").collect(),
         );
     let styling = Rc::new(style_builder.build());
 
-    let doc = Rc::new(TextDocument::new(scope, content));
+    let doc = Rc::new(TextDocument::new(scope, content.clone()));
     let editor = Editor::new(scope, doc, styling, false);
     editor.read_only.set(true);
     let editor_sig = scope.create_rw_signal(editor);
 
     with_scope(scope, move || {
+        // Jump to the position the file was opened at
+        create_effect(move |_| {
+            let Some((line, column)) = position.get() else { return };
+            let offset = text_offset(&content, line, column);
+            let top = line.saturating_sub(JUMP_CONTEXT_LINES + 1) as f64
+                * EDITOR_FONT_SIZE as f64
+                * EDITOR_LINE_HEIGHT as f64;
+            editor_sig.with_untracked(|editor| {
+                editor.cursor.set(Cursor::new(CursorMode::Normal(offset), None, None));
+                editor.scroll_to.set(Some(Vec2::new(0.0, top)));
+            });
+        });
+
         // Keep this view non-active: it must not drive global IME state or cursor area, since it's
         // a read-only viewer and the terminal should own IME when focused.
         editor_container_view(editor_sig, |_| false, default_key_handler(editor_sig))
@@ -765,7 +1493,7 @@ fn editor_workspace_view(
             if let Some(id) = id_opt {
                 if let Some(tab) = tabs.iter().find(|t| t.id == id) {
                     let content = tab.content.clone();
-                    return read_only_code_viewer(content, theme).into_any();
+                    return read_only_code_viewer(content, tab.position, theme).into_any();
                 }
             }
             
@@ -862,25 +1590,74 @@ where F: Fn() -> bool + 'static + Clone
     })
 }
 
+/// Open a path from the command line or the remote control socket in `tabs`, returning
+/// the tab to bring to the front. A folder opens as a workspace (an open one is reused);
+/// a file opens in the editor of the innermost workspace containing it, or of a new
+/// workspace for its folder.
+fn open_target(tabs: &mut Vec<WorkspaceTab>, next_tab_id: &mut usize, target: &Target) -> usize {
+    match target {
+        Target::Workspace(root) => workspace_for_root(tabs, next_tab_id, root),
+        Target::File { path, line, column } => {
+            let containing = tabs
+                .iter()
+                .filter(|tab| tab.tmux_window.is_none())
+                .map(|tab| (tab.id, tab.root.get_untracked()))
+                .filter(|(_, root)| path.starts_with(root))
+                .max_by_key(|(_, root)| root.components().count())
+                .map(|(id, _)| id);
+            let id = containing.unwrap_or_else(|| {
+                workspace_for_root(tabs, next_tab_id, path.parent().unwrap_or(path))
+            });
+            if let Some(tab) = tabs.iter().find(|tab| tab.id == id) {
+                let position = line.map(|line| (line, column.unwrap_or(1)));
+                tab.open_editor_file(path.clone(), true, position);
+            }
+            id
+        }
+    }
+}
+
+/// The workspace tab showing `root`, opened if there is none.
+fn workspace_for_root(tabs: &mut Vec<WorkspaceTab>, next_tab_id: &mut usize, root: &Path) -> usize {
+    let existing = tabs.iter().find(|tab| {
+        let tab_root = tab.root.get_untracked();
+        tab.tmux_window.is_none() && (tab_root == root || tab_root.canonicalize().is_ok_and(|path| path == root))
+    });
+    if let Some(tab) = existing {
+        return tab.id;
+    }
+    let id = *next_tab_id;
+    *next_tab_id += 1;
+    logging::log_line("INFO", &format!("open workspace: id={id} root={}", root.display()));
+    tabs.push(build_tab(id, root.to_path_buf()));
+    id
+}
+
 fn build_tab(id: usize, root: PathBuf) -> WorkspaceTab {
+    // Create the terminal panes, one per pane saved at the last shutdown
+    let saved = take_workspace_scrollback(&root);
+    let pane_count = saved.len().max(1);
+    let mut saved = saved.into_iter();
+    let initial_panes: Vec<TerminalPane> = (0..pane_count)
+        .map(|pane_id| {
+            let replay = saved
+                .next()
+                .map(|pane| pane.replay_bytes())
+                .filter(|bytes| !bytes.is_empty());
+            terminal_pane(pane_id, replay, None)
+        })
+        .collect();
+
+    workspace_tab(id, root, initial_panes)
+}
+
+fn workspace_tab(id: usize, root: PathBuf, panes: Vec<TerminalPane>) -> WorkspaceTab {
     let name = root
         .file_name()
         .and_then(|name| name.to_str())
         .unwrap_or("workspace")
         .to_string();
-    let file_tree = build_tree_entries(&root, 3);
-    let git_status = git_status_entries(&root);
-
-    // Create initial terminal pane
-    let initial_pane = TerminalPane {
-        id: 0,
-        session: RwSignal::new(None),
-        trigger: ExtSendTrigger::new(),
-        flex_ratio: RwSignal::new(1.0),
-        title: RwSignal::new("Terminal".to_string()),
-        should_focus: RwSignal::new(false),
-        title_buffer: Arc::new(Mutex::new(None)),
-    };
+    let pane_count = panes.len();
 
     WorkspaceTab {
         id,
@@ -892,8 +1669,28 @@ fn build_tab(id: usize, root: PathBuf) -> WorkspaceTab {
         editor_tabs: RwSignal::new(Vec::new()),
         active_editor_tab: RwSignal::new(None),
         focused_pane_id: RwSignal::new(None),
-        terminal_panes: RwSignal::new(vec![initial_pane]),
-        next_pane_id: RwSignal::new(1),
+        terminal_panes: RwSignal::new(panes),
+        next_pane_id: RwSignal::new(pane_count),
         next_editor_tab_id: RwSignal::new(0),
+        background_tasks: RwSignal::new(Vec::new()),
+        next_task_id: RwSignal::new(0),
+        tmux_window: None,
+    }
+}
+
+fn terminal_pane(id: usize, replay: Option<Vec<u8>>, tmux: Option<TmuxPaneRef>) -> TerminalPane {
+    TerminalPane {
+        id,
+        session: RwSignal::new(None),
+        trigger: ExtSendTrigger::new(),
+        flex_ratio: RwSignal::new(1.0),
+        title: RwSignal::new("Terminal".to_string()),
+        should_focus: RwSignal::new(false),
+        title_buffer: Arc::new(Mutex::new(None)),
+        process_title_buffer: Arc::new(Mutex::new(None)),
+        pinned_title: RwSignal::new(None),
+        profile: None,
+        restored_output: Arc::new(Mutex::new(replay)),
+        tmux,
     }
 }
@@ -0,0 +1,281 @@
+//! Command line of the `tide` binary.
+//!
+//! `tide [OPTIONS] [PATH[:LINE[:COLUMN]]...]` opens folders as workspace tabs and files in
+//! the editor. When an instance is already running, the paths are sent to it over its
+//! remote control socket (see `services::remote_control`) and its window is brought to
+//! the front instead of opening a second one.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub const USAGE: &str = "\
+Usage: tide [OPTIONS] [PATH[:LINE[:COLUMN]]...]
+
+Opens each folder as a workspace tab (an open one is brought to the front) and each
+file in the editor, at LINE and COLUMN when given. If Tide is already running, the
+paths are opened there and its window is brought to the front.
+
+Options:
+      --new-window    Start another instance instead of using the running one
+      --no-restore    Do not reopen the workspaces of the last session, and keep them
+                      saved for the next one
+      --state <FILE>  Keep the list of open workspaces in FILE (starts a separate instance)
+  -h, --help          Print this help
+  -V, --version       Print the version";
+
+/// Something to open, with relative paths resolved against the invoking directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Workspace(PathBuf),
+    /// A file; `line` and `column` are 1-based
+    File {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+}
+
+/// Parsed command line for a normal run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CliArgs {
+    pub targets: Vec<Target>,
+    pub new_window: bool,
+    pub no_restore: bool,
+    pub state_file: Option<PathBuf>,
+}
+
+impl CliArgs {
+    /// Whether the paths may go to an instance that is already running.
+    pub fn forwardable(&self) -> bool {
+        !self.new_window && self.state_file.is_none()
+    }
+}
+
+/// What the command line asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliAction {
+    Run(CliArgs),
+    Help,
+    Version,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    /// A path that names neither a folder nor a file
+    NotFound(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => write!(f, "unknown option '{option}'"),
+            CliError::MissingValue(option) => write!(f, "'{option}' needs a value"),
+            CliError::NotFound(path) => write!(f, "'{path}': no such file or directory"),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parse the arguments after the program name; relative paths are resolved against `cwd`.
+pub fn parse_args<I>(args: I, cwd: &Path) -> Result<CliAction, CliError>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter().map(Into::into);
+    let mut options_done = false;
+
+    while let Some(arg) = args.next() {
+        if options_done || !arg.starts_with('-') {
+            parsed.targets.push(resolve_target(&arg, cwd)?);
+            continue;
+        }
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => (option.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        match option.as_str() {
+            "--" => options_done = true,
+            // Process serial number macOS may pass to apps started from Finder
+            _ if option.starts_with("-psn_") => {}
+            "-h" | "--help" => return Ok(CliAction::Help),
+            "-V" | "--version" => return Ok(CliAction::Version),
+            "--new-window" => parsed.new_window = true,
+            "--no-restore" => parsed.no_restore = true,
+            "--state" => {
+                let value = inline_value
+                    .or_else(|| args.next())
+                    .filter(|value| !value.is_empty())
+                    .ok_or_else(|| CliError::MissingValue(option.clone()))?;
+                parsed.state_file = Some(cwd.join(expand_home(&value)));
+            }
+            _ => return Err(CliError::UnknownOption(arg)),
+        }
+    }
+    Ok(CliAction::Run(parsed))
+}
+
+/// Split a trailing `:LINE` or `:LINE:COLUMN` off a path argument.
+pub fn split_position(arg: &str) -> (&str, Option<usize>, Option<usize>) {
+    // Digits only: `parse` would also take a leading `+`
+    let number = |text: &str| {
+        if text.bytes().all(|byte| byte.is_ascii_digit()) {
+            text.parse::<usize>().ok()
+        } else {
+            None
+        }
+    };
+    let Some((head, last)) = arg.rsplit_once(':') else {
+        return (arg, None, None);
+    };
+    let Some(last) = number(last) else {
+        return (arg, None, None);
+    };
+    match head.rsplit_once(':') {
+        Some((path, line)) if number(line).is_some() && !path.is_empty() => (path, number(line), Some(last)),
+        _ if !head.is_empty() => (head, Some(last), None),
+        _ => (arg, None, None),
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Folder or file named by a path argument. A path that exists as written wins over a
+/// `:LINE` reading, so files with colons in their name still open.
+fn resolve_target(arg: &str, cwd: &Path) -> Result<Target, CliError> {
+    let literal = cwd.join(expand_home(arg));
+    let (path, line, column) = if literal.exists() {
+        (literal, None, None)
+    } else {
+        let (path, line, column) = split_position(arg);
+        (cwd.join(expand_home(path)), line, column)
+    };
+
+    let path = path.canonicalize().map_err(|_| CliError::NotFound(arg.to_string()))?;
+    if path.is_dir() {
+        Ok(Target::Workspace(path))
+    } else {
+        Ok(Target::File { path, line, column })
+    }
+}
+
+/// Send the targets to a running instance and bring its window to the front. Returns
+/// `false` when there is none (or it did not answer), in which case this process opens
+/// its own window.
+#[cfg(unix)]
+pub fn forward_to_running_instance(args: &CliArgs) -> bool {
+    use crate::services::remote_control::{call, running_instances};
+    use serde_json::{json, Value};
+
+    let Some(socket) = running_instances().into_iter().next() else {
+        return false;
+    };
+
+    let requests = args.targets.iter().map(|target| match target {
+        Target::Workspace(path) => ("workspace.open", json!({ "path": path })),
+        Target::File { path, line, column } => {
+            ("editor.open", json!({ "path": path, "line": line, "column": column }))
+        }
+    });
+    for (method, params) in requests.chain([("window.focus", Value::Null)]) {
+        match call(&socket, method, params) {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => eprintln!("tide: {method} failed: {}", err.message),
+            Err(err) => {
+                crate::logging::log_line("WARN", &format!("Forwarding to {} failed: {err}", socket.display()));
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(not(unix))]
+pub fn forward_to_running_instance(_args: &CliArgs) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn run(args: &[&str], cwd: &Path) -> CliArgs {
+        match parse_args(args.iter().copied(), cwd).unwrap() {
+            CliAction::Run(args) => args,
+            other => panic!("expected a run, got {other:?}"),
+        }
+    }
+
+    /// A folder with `src/main.rs` and `notes:v2.txt` in it.
+    fn fixture(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("tide-cli-{name}-{}", std::process::id()));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("notes:v2.txt"), "").unwrap();
+        root.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn positions_are_split_off_paths() {
+        assert_eq!(split_position("src/main.rs:12"), ("src/main.rs", Some(12), None));
+        assert_eq!(split_position("src/main.rs:12:5"), ("src/main.rs", Some(12), Some(5)));
+        assert_eq!(split_position("src/main.rs"), ("src/main.rs", None, None));
+        assert_eq!(split_position("notes:v2.txt"), ("notes:v2.txt", None, None));
+        assert_eq!(split_position("src/main.rs:"), ("src/main.rs:", None, None));
+        assert_eq!(split_position(":12"), (":12", None, None));
+    }
+
+    #[test]
+    fn paths_become_workspaces_and_files() {
+        let root = fixture("targets");
+        let args = run(&[".", "src", "src/main.rs:3:7", "notes:v2.txt"], &root);
+        assert_eq!(
+            args.targets,
+            vec![
+                Target::Workspace(root.clone()),
+                Target::Workspace(root.join("src")),
+                Target::File { path: root.join("src/main.rs"), line: Some(3), column: Some(7) },
+                Target::File { path: root.join("notes:v2.txt"), line: None, column: None },
+            ]
+        );
+        assert!(args.forwardable());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let root = fixture("options");
+        let args = run(&["--new-window", "--no-restore", "--state", "s.json", "src"], &root);
+        assert!(args.new_window && args.no_restore);
+        assert_eq!(args.state_file, Some(root.join("s.json")));
+        assert_eq!(args.targets, vec![Target::Workspace(root.join("src"))]);
+        assert!(!args.forwardable());
+
+        assert_eq!(run(&["--state=other.json"], &root).state_file, Some(root.join("other.json")));
+        assert_eq!(run(&["-psn_0_12345"], &root), CliArgs::default());
+        assert_eq!(parse_args(["-h", "--bogus"], &root), Ok(CliAction::Help));
+        assert_eq!(parse_args(["--version"], &root), Ok(CliAction::Version));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn bad_arguments_are_reported() {
+        let root = fixture("errors");
+        assert_eq!(parse_args(["--bogus"], &root), Err(CliError::UnknownOption("--bogus".to_string())));
+        assert_eq!(parse_args(["--state"], &root), Err(CliError::MissingValue("--state".to_string())));
+        assert_eq!(parse_args(["missing.rs:3"], &root), Err(CliError::NotFound("missing.rs:3".to_string())));
+        // After `--` everything is a path
+        assert_eq!(parse_args(["--", "--new-window"], &root), Err(CliError::NotFound("--new-window".to_string())));
+        let _ = fs::remove_dir_all(root);
+    }
+}
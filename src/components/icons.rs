@@ -4,3 +4,4 @@ pub const FILE: &str = r#"<svg width="16" height="16" viewBox="0 0 16 16" fill="
 pub const FOLDER: &str = r#"<svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor" xmlns="http://www.w3.org/2000/svg"><path d="M2 4.5A1.5 1.5 0 0 1 3.5 3h3l1.2 1.2h4.8A1.5 1.5 0 0 1 14 5.7v6.8A1.5 1.5 0 0 1 12.5 14h-9A1.5 1.5 0 0 1 2 12.5v-8z"/></svg>"#;
 pub const GIT: &str = r#"<svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor" xmlns="http://www.w3.org/2000/svg"><path d="M5 3a2 2 0 1 0 1.9 2.5l2.2 2.2a2 2 0 1 0 1.2-.4V6.5H11V5h-1.7c-.4 0-.8.2-1.1.5L6 3.3A2 2 0 0 0 5 3zm0 1.5a.5.5 0 1 1 0 1 .5.5 0 0 1 0-1zM10 9a.5.5 0 1 1 0 1 .5.5 0 0 1 0-1z"/></svg>"#;
 pub const REFRESH: &str = r#"<svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor" xmlns="http://www.w3.org/2000/svg"><path d="M8 3a5 5 0 1 0 4.546 2.914.5.5 0 0 1 .908-.417A6 6 0 1 1 8 2v1z"/><path d="M8 4.466V.534a.25.25 0 0 1 .41-.192l2.36 1.966a.25.25 0 0 1 0 .384l-2.36 1.966a.25.25 0 0 1-.41-.192z"/></svg>"#;
+pub const TASKS: &str = r#"<svg width="16" height="16" viewBox="0 0 16 16" fill="currentColor" xmlns="http://www.w3.org/2000/svg"><path d="M8 1.5a6.5 6.5 0 1 0 0 13 6.5 6.5 0 0 0 0-13zM3 8a5 5 0 1 1 10 0A5 5 0 0 1 3 8z"/><path d="M6.5 5.2v5.6L11 8 6.5 5.2z"/></svg>"#;
@@ -0,0 +1,520 @@
+//! Editor for `~/.config/tide/launchers.json`, opened by the control center's Settings
+//! button.
+//!
+//! The launchers are edited as a list: select one to change its fields, or add, remove
+//! and move them. Problems are shown while typing and block saving. Saving writes the
+//! file with `save_launchers`, asking first when the file's version has to go up; the
+//! config watcher then reloads the buttons from it.
+
+use crate::logging;
+use crate::model::WorkspaceTab;
+use crate::services::config::launchers_file_path;
+use crate::services::{launchers_upgrade, load_launchers, save_launchers, Launcher, LauncherConfig, LauncherRunIn};
+use crate::theme::UiTheme;
+use floem::prelude::*;
+use floem::reactive::create_effect;
+use floem::style::{CursorStyle, Display, Style};
+use std::collections::BTreeMap;
+
+const RUN_IN_CHOICES: [(LauncherRunIn, &str); 4] = [
+    (LauncherRunIn::Current, "Current pane"),
+    (LauncherRunIn::NewSplit, "New split"),
+    (LauncherRunIn::NewTab, "New tab"),
+    (LauncherRunIn::Background, "Background"),
+];
+
+fn error_color() -> Color {
+    Color::from_rgb8(235, 87, 87)
+}
+
+/// Problem messages of the launcher at `index`, checked against the ones before it.
+fn problems_at(launchers: &[Launcher], index: usize) -> Vec<(&'static str, String)> {
+    launchers
+        .get(index)
+        .map(|launcher| launcher.problems(&launchers[..index]))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|problem| (problem.field, problem.message))
+        .collect()
+}
+
+fn problem_count(launchers: &[Launcher]) -> usize {
+    (0..launchers.len()).map(|index| problems_at(launchers, index).len()).sum()
+}
+
+/// Asked before a save that raises the file's version from `from` to `to`.
+fn upgrade_question(from: u32, to: u32) -> String {
+    format!("Older Tide versions cannot read version {to}: Save again to upgrade launchers.json from version {from}")
+}
+
+/// "New launcher", or "New launcher 2", ... when that name is taken.
+fn unused_name(launchers: &[Launcher]) -> String {
+    (1..)
+        .map(|n| if n == 1 { "New launcher".to_string() } else { format!("New launcher {n}") })
+        .find(|name| !launchers.iter().any(|launcher| launcher.name == *name))
+        .unwrap_or_default()
+}
+
+pub fn launcher_editor(open: RwSignal<bool>, workspace: WorkspaceTab, theme: UiTheme) -> impl IntoView {
+    let draft = RwSignal::new(Vec::<Launcher>::new());
+    // What the file holds, to tell unsaved changes
+    let saved = RwSignal::new(Vec::<Launcher>::new());
+    let selected = RwSignal::new(None::<usize>);
+    // Bumped when launchers are added, removed or moved, so the form is built again
+    let layout_changes = RwSignal::new(0u64);
+    let load_error = RwSignal::new(None::<String>);
+    // A message about the list as it was when the message was set
+    let status = RwSignal::new(None::<(String, Vec<Launcher>)>);
+
+    // Read the file each time the editor opens, so edits made elsewhere are kept
+    create_effect(move |_| {
+        if !open.get() {
+            return;
+        }
+        let file = load_launchers();
+        let launchers = if file.failed { Vec::new() } else { file.config.launchers };
+        load_error.set(file.failed.then(|| {
+            let reason = file.diagnostics.first().map(ToString::to_string).unwrap_or_default();
+            format!("{} does not parse ({reason}); fix it with Edit JSON", file.path.display())
+        }));
+        status.set((!file.failed && !file.diagnostics.is_empty()).then(|| {
+            let count = file.diagnostics.len();
+            let message =
+                format!("{count} problem(s) in the file: skipped launchers are dropped on save");
+            (message, launchers.clone())
+        }));
+        selected.set((!launchers.is_empty()).then_some(0));
+        saved.set(launchers.clone());
+        draft.set(launchers);
+        layout_changes.update(|count| *count += 1);
+    });
+
+    let can_save = move || {
+        load_error.with(Option::is_none)
+            && draft.with(|draft| problem_count(draft) == 0 && saved.with(|saved| saved != draft))
+    };
+    let save = move || {
+        if !can_save() {
+            return;
+        }
+        let launchers = draft.get_untracked();
+        let path = launchers_file_path();
+        // The first Save of fields a newer version added only asks
+        if let Some((from, to)) = launchers_upgrade(&path, &launchers) {
+            let question = (upgrade_question(from, to), launchers.clone());
+            if status.with_untracked(|status| status.as_ref() != Some(&question)) {
+                status.set(Some(question));
+                return;
+            }
+        }
+        match save_launchers(&path, &launchers) {
+            Ok(()) => {
+                logging::log_line("INFO", &format!("saved {} launchers to {}", launchers.len(), path.display()));
+                status.set(Some(("Saved".to_string(), launchers.clone())));
+                saved.set(launchers);
+            }
+            Err(err) => {
+                logging::log_line("ERROR", &format!("cannot save {}: {err}", path.display()));
+                status.set(Some((format!("Cannot save: {err}"), launchers)));
+            }
+        }
+    };
+
+    let button_style = move |s: Style| {
+        s.font_size(11.0)
+            .padding_horiz(8.0)
+            .padding_vert(3.0)
+            .border(1.0)
+            .border_color(theme.border_subtle)
+            .border_radius(4.0)
+            .color(theme.text)
+            .background(theme.element_bg)
+            .hover(|s| s.background(theme.accent.with_alpha(0.2)))
+            .cursor(CursorStyle::Pointer)
+    };
+
+    let status_label = label(move || {
+        if let Some(err) = load_error.get() {
+            return err;
+        }
+        let problems = draft.with(|draft| problem_count(draft));
+        if problems > 0 {
+            return format!("{problems} problem(s) to fix before saving");
+        }
+        match status.get() {
+            Some((message, at)) if draft.with(|draft| *draft == at) => message,
+            _ if draft.with(|draft| saved.with(|saved| saved != draft)) => "Unsaved changes".to_string(),
+            _ => String::new(),
+        }
+    })
+    .style(move |s| {
+        let failing = load_error.with(Option::is_some) || draft.with(|draft| problem_count(draft) > 0);
+        s.font_size(11.0)
+            .color(if failing { error_color() } else { theme.text_muted })
+            .text_ellipsis()
+            .min_width(0.0)
+            .flex_grow(1.0)
+    });
+
+    let add_button = label(|| "+ Add").style(button_style).on_click_stop(move |_| {
+        if load_error.with_untracked(Option::is_some) {
+            return;
+        }
+        let mut added = 0;
+        draft.update(|launchers| {
+            let name = unused_name(launchers);
+            launchers.push(Launcher::new(&name, "", LauncherRunIn::Current));
+            added = launchers.len() - 1;
+        });
+        selected.set(Some(added));
+        layout_changes.update(|count| *count += 1);
+    });
+    let json_button = label(|| "Edit JSON").style(button_style).on_click_stop(move |_| {
+        let path = launchers_file_path();
+        if !path.exists() {
+            // Start from the built-in launchers so the file shows the format
+            if let Err(err) = save_launchers(&path, &LauncherConfig::default().launchers) {
+                logging::log_line("ERROR", &format!("cannot create {}: {err}", path.display()));
+            }
+        }
+        workspace.open_editor_file(path, true, None);
+    });
+    let save_button = label(|| "Save")
+        .style(move |s| {
+            let enabled = can_save();
+            button_style(s)
+                .color(if enabled { theme.text } else { theme.text_muted })
+                .cursor(if enabled { CursorStyle::Pointer } else { CursorStyle::Default })
+        })
+        .on_click_stop(move |_| save());
+    let close_button = label(|| "Close").style(button_style).on_click_stop(move |_| open.set(false));
+
+    let header = h_stack((
+        label(|| "Launchers").style(move |s| s.font_size(12.0).font_bold().color(theme.text)),
+        status_label,
+        add_button,
+        json_button,
+        save_button,
+        close_button,
+    ))
+    .style(|s| s.width_full().items_center().col_gap(8.0).min_width(0.0));
+
+    let list = dyn_stack(
+        move || draft.get().into_iter().enumerate().collect::<Vec<_>>(),
+        |(index, launcher)| format!("{index}{launcher:?}"),
+        move |(index, launcher)| {
+            let text = launcher.label();
+            let move_to = move |to: usize| {
+                if to >= draft.with_untracked(Vec::len) {
+                    return;
+                }
+                draft.update(|launchers| launchers.swap(index, to));
+                selected.set(Some(to));
+                layout_changes.update(|count| *count += 1);
+            };
+            let small_button = move |s: Style| {
+                s.font_size(11.0)
+                    .padding_horiz(3.0)
+                    .color(theme.text_muted)
+                    .cursor(CursorStyle::Pointer)
+                    .hover(|s| s.color(theme.text))
+            };
+            h_stack((
+                label(move || {
+                    let broken = draft.with(|launchers| !problems_at(launchers, index).is_empty());
+                    if broken { format!("⚠ {text}") } else { text.clone() }
+                })
+                .style(move |s| s.font_size(11.0).color(theme.text).text_ellipsis().min_width(0.0).flex_grow(1.0)),
+                label(|| "↑").style(small_button).on_click_stop(move |_| {
+                    if index > 0 {
+                        move_to(index - 1);
+                    }
+                }),
+                label(|| "↓").style(small_button).on_click_stop(move |_| move_to(index + 1)),
+                label(|| "✕").style(small_button).on_click_stop(move |_| {
+                    draft.update(|launchers| {
+                        launchers.remove(index);
+                    });
+                    let remaining = draft.with_untracked(Vec::len);
+                    selected.update(|selected| {
+                        *selected = match *selected {
+                            _ if remaining == 0 => None,
+                            Some(at) if at > index => Some(at - 1),
+                            Some(at) => Some(at.min(remaining - 1)),
+                            None => None,
+                        }
+                    });
+                    layout_changes.update(|count| *count += 1);
+                }),
+            ))
+            .style(move |s| {
+                let current = selected.get() == Some(index);
+                s.width_full()
+                    .items_center()
+                    .padding_horiz(6.0)
+                    .padding_vert(2.0)
+                    .border_radius(3.0)
+                    .background(if current { theme.accent.with_alpha(0.25) } else { Color::TRANSPARENT })
+                    .cursor(CursorStyle::Pointer)
+            })
+            .on_click_stop(move |_| {
+                if selected.get_untracked() != Some(index) {
+                    selected.set(Some(index));
+                    layout_changes.update(|count| *count += 1);
+                }
+            })
+        },
+    )
+    .style(|s| s.flex_col().width_full())
+    .scroll()
+    .style(move |s| {
+        s.width(220.0)
+            .height_full()
+            .border_right(1.0)
+            .border_color(theme.border_subtle)
+            .padding_right(6.0)
+    });
+
+    let form = dyn_container(
+        move || {
+            layout_changes.track();
+            selected.get_untracked()
+        },
+        move |index| match index {
+            Some(index) => launcher_form(draft, index, theme).into_any(),
+            None => label(|| "Add a launcher to get started")
+                .style(move |s| s.font_size(11.0).color(theme.text_muted))
+                .into_any(),
+        },
+    )
+    .style(|s| s.flex_grow(1.0).min_width(0.0).height_full().padding_left(10.0));
+
+    v_stack((header, h_stack((list, form)).style(|s| s.width_full().height(240.0).min_width(0.0))))
+        .style(move |s| {
+            s.display(if open.get() { Display::Flex } else { Display::None })
+                .width_full()
+                .row_gap(6.0)
+                .padding(8.0)
+                .min_width(0.0)
+                .border_top(1.0)
+                .border_color(theme.border_subtle)
+                .background(theme.panel_bg)
+        })
+}
+
+/// Fields of the launcher at `index` in `draft`; every edit is written back at once.
+fn launcher_form(draft: RwSignal<Vec<Launcher>>, index: usize, theme: UiTheme) -> impl IntoView {
+    let Some(launcher) = draft.with_untracked(|launchers| launchers.get(index).cloned()) else {
+        return empty().into_any();
+    };
+    let optional_text = |value: &Option<String>| RwSignal::new(value.clone().unwrap_or_default());
+    let name = RwSignal::new(launcher.name.clone());
+    let command = RwSignal::new(launcher.command.clone());
+    let cwd = optional_text(&launcher.cwd);
+    let profile = optional_text(&launcher.profile);
+    let icon = optional_text(&launcher.icon);
+    let group = optional_text(&launcher.group);
+    // (id, name, value) of each environment variable row
+    let env_rows = RwSignal::new(
+        launcher
+            .env
+            .iter()
+            .enumerate()
+            .map(|(id, (key, value))| (id, RwSignal::new(key.clone()), RwSignal::new(value.clone())))
+            .collect::<Vec<_>>(),
+    );
+    let next_env_id = RwSignal::new(launcher.env.len());
+
+    create_effect(move |_| {
+        let optional = |value: RwSignal<String>| Some(value.get()).filter(|value| !value.trim().is_empty());
+        let env: BTreeMap<String, String> = env_rows
+            .get()
+            .into_iter()
+            .map(|(_, key, value)| (key.get(), value.get()))
+            // Rows added but not filled in yet
+            .filter(|(key, value)| !key.is_empty() || !value.is_empty())
+            .collect();
+        let edited = (name.get(), command.get(), optional(cwd), optional(profile), optional(icon), optional(group));
+        draft.update(|launchers| {
+            if let Some(launcher) = launchers.get_mut(index) {
+                (launcher.name, launcher.command, launcher.cwd, launcher.profile, launcher.icon, launcher.group) =
+                    edited;
+                launcher.env = env;
+            }
+        });
+    });
+
+    let has_problem = move |field: &str| {
+        draft.with(|launchers| problems_at(launchers, index).iter().any(|(problem, _)| *problem == field))
+    };
+    let input_style = move |s: Style, field: &'static str, width: f64| {
+        s.font_size(11.0)
+            .width(width)
+            .padding_horiz(4.0)
+            .border(1.0)
+            .border_radius(3.0)
+            .border_color(if has_problem(field) { error_color() } else { theme.border_subtle })
+            .color(theme.text)
+            .background(theme.element_bg)
+    };
+    let caption = move |text: &'static str| {
+        label(move || text).style(move |s| s.font_size(11.0).width(70.0).color(theme.text_soft))
+    };
+    let text_field = move |title: &'static str, field: &'static str, value: RwSignal<String>, width: f64| {
+        h_stack((caption(title), text_input(value).style(move |s| input_style(s, field, width))))
+            .style(|s| s.items_center())
+    };
+    let chip_style = move |s: Style, on: bool| {
+        s.font_size(11.0)
+            .padding_horiz(6.0)
+            .padding_vert(2.0)
+            .border(1.0)
+            .border_radius(3.0)
+            .border_color(theme.border_subtle)
+            .color(if on { theme.text } else { theme.text_soft })
+            .background(if on { theme.accent.with_alpha(0.25) } else { theme.element_bg })
+            .cursor(CursorStyle::Pointer)
+    };
+    let is_set = move |get: fn(&Launcher) -> bool| {
+        draft.with(|launchers| launchers.get(index).is_some_and(get))
+    };
+    let toggle = move |set: fn(&mut Launcher)| {
+        draft.update(|launchers| {
+            if let Some(launcher) = launchers.get_mut(index) {
+                set(launcher);
+            }
+        })
+    };
+
+    let run_in = h_stack((
+        caption("Run in"),
+        h_stack_from_iter(RUN_IN_CHOICES.into_iter().map(move |(choice, text)| {
+            let chosen = choice.clone();
+            label(move || text)
+                .style(move |s| {
+                    let on = draft.with(|launchers| launchers.get(index).is_some_and(|l| l.run_in == choice));
+                    chip_style(s, on)
+                })
+                .on_click_stop(move |_| {
+                    let chosen = chosen.clone();
+                    draft.update(|launchers| {
+                        if let Some(launcher) = launchers.get_mut(index) {
+                            launcher.run_in = chosen;
+                        }
+                    })
+                })
+        }))
+        .style(|s| s.col_gap(4.0)),
+    ))
+    .style(|s| s.items_center());
+
+    let options = h_stack((
+        caption("Options"),
+        label(move || if is_set(|l| l.execute) { "☑ Press Enter" } else { "☐ Press Enter" })
+            .style(move |s| chip_style(s, is_set(|l| l.execute)))
+            .on_click_stop(move |_| toggle(|l| l.execute = !l.execute)),
+        label(move || if is_set(|l| l.confirm) { "☑ Ask first" } else { "☐ Ask first" })
+            .style(move |s| chip_style(s, is_set(|l| l.confirm)))
+            .on_click_stop(move |_| toggle(|l| l.confirm = !l.confirm)),
+    ))
+    .style(|s| s.items_center().col_gap(4.0));
+
+    let env_list = dyn_stack(
+        move || env_rows.get(),
+        |(id, _, _)| *id,
+        move |(id, key, value)| {
+            h_stack((
+                text_input(key).style(move |s| input_style(s, "env", 140.0)),
+                label(|| "=").style(move |s| s.font_size(11.0).color(theme.text_muted)),
+                text_input(value).style(move |s| input_style(s, "", 220.0)),
+                label(|| "✕")
+                    .style(move |s| s.font_size(11.0).color(theme.text_muted).cursor(CursorStyle::Pointer))
+                    .on_click_stop(move |_| env_rows.update(|rows| rows.retain(|(row, _, _)| *row != id))),
+            ))
+            .style(|s| s.items_center().col_gap(4.0))
+        },
+    )
+    .style(|s| s.flex_col().row_gap(2.0));
+    let env = h_stack((
+        caption("Env"),
+        v_stack((
+            env_list,
+            label(|| "+ Variable")
+                .style(move |s| s.font_size(11.0).color(theme.text_soft).cursor(CursorStyle::Pointer))
+                .on_click_stop(move |_| {
+                    let id = next_env_id.get_untracked();
+                    next_env_id.set(id + 1);
+                    env_rows.update(|rows| rows.push((id, RwSignal::new(String::new()), RwSignal::new(String::new()))));
+                }),
+        ))
+        .style(|s| s.row_gap(2.0)),
+    ));
+
+    let problems = label(move || {
+        draft.with(|launchers| {
+            problems_at(launchers, index)
+                .into_iter()
+                .map(|(field, message)| format!("{field}: {message}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    })
+    .style(move |s| s.font_size(11.0).color(error_color()));
+
+    let placeholders = label(|| {
+        "Placeholders: ${workspaceRoot} ${paneCwd} ${file} ${relativeFile} ${selectedText} ${gitBranch} ${env:NAME} ${input:Prompt}"
+    })
+    .style(move |s| s.font_size(10.0).color(theme.text_muted).text_ellipsis().min_width(0.0));
+
+    v_stack((
+        text_field("Name", "name", name, 220.0),
+        text_field("Command", "command", command, 420.0),
+        placeholders,
+        run_in,
+        options,
+        text_field("Folder", "cwd", cwd, 220.0),
+        text_field("Profile", "profile", profile, 220.0),
+        h_stack((text_field("Icon", "icon", icon, 60.0), text_field("Group", "group", group, 140.0)))
+            .style(|s| s.col_gap(12.0)),
+        env,
+        problems,
+    ))
+    .style(|s| s.row_gap(4.0).min_width(0.0))
+    .scroll()
+    .style(|s| s.width_full().height_full())
+    .into_any()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problems_are_checked_against_earlier_launchers() {
+        let mut launchers = LauncherConfig::default().launchers;
+        assert_eq!(problem_count(&launchers), 0);
+
+        launchers.push(Launcher::new("Claude", "claude ${nope}", LauncherRunIn::Current));
+        let fields: Vec<_> = problems_at(&launchers, 2).into_iter().map(|(field, _)| field).collect();
+        assert_eq!(fields, ["command", "name"]);
+        // The first of two launchers with one name is fine
+        assert!(problems_at(&launchers, 0).is_empty());
+        assert!(problems_at(&launchers, 5).is_empty());
+        assert_eq!(problem_count(&launchers), 2);
+    }
+
+    #[test]
+    fn added_launchers_get_unused_names() {
+        let mut launchers = Vec::new();
+        assert_eq!(unused_name(&launchers), "New launcher");
+        launchers.push(Launcher::new("New launcher", "", LauncherRunIn::Current));
+        launchers.push(Launcher::new("New launcher 2", "", LauncherRunIn::Current));
+        assert_eq!(unused_name(&launchers), "New launcher 3");
+    }
+
+    #[test]
+    fn upgrades_are_asked_for() {
+        let question = upgrade_question(1, 2);
+        assert!(question.contains("version 2") && question.ends_with("from version 1"), "{question}");
+    }
+}
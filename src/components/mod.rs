@@ -1,16 +1,27 @@
 mod atoms;
 mod icons;
 mod layout;
+#[cfg(target_os = "macos")]
+mod launcher_editor;
+mod notifications;
 mod panels;
 mod terminal;
 
 pub use atoms::{
     collapsible_panel_header, icon, tab_button, tab_button_with_menu,
 };
-pub use icons::{FILE, FOLDER, GIT, REFRESH};
+pub use icons::{FILE, FOLDER, GIT, REFRESH, TASKS};
 pub use layout::{app_shell, get_last_window_size, main_layout, tab_bar};
+#[cfg(target_os = "macos")]
+pub use launcher_editor::launcher_editor;
+pub use notifications::{notification_center, post_notification};
 pub use panels::{
-    collapsible_panel_view, collapsible_panel_view_with_actions, file_tree_view, git_status_view,
-    panel_view,
+    background_tasks_view, collapsible_panel_view, collapsible_panel_view_with_actions, file_tree_view,
+    git_status_view, panel_view,
+};
+pub use terminal::{force_terminal_repaint, save_scrollback, terminal_view};
+#[cfg(target_os = "macos")]
+pub use terminal::{
+    run_launcher, set_tab_launch_handler, show_background_task, split_pane, start_pane_session,
+    track_background_tasks, TabLaunch,
 };
-pub use terminal::{force_terminal_repaint, terminal_view};
@@ -0,0 +1,207 @@
+//! Notification center for notifications posted by terminal programs (OSC 9 / OSC 777).
+//!
+//! Terminal IO threads post into a global inbox; the center drains it on the UI thread,
+//! applies the workspace setting and the per-pane rate limit, and lists the result in a
+//! menu on the tab bar. Choosing an entry switches to its workspace and focuses the pane.
+
+use crate::logging;
+use crate::model::WorkspaceTab;
+use crate::services::terminal_notification::NotificationRateLimiter;
+use crate::services::{Settings, TerminalNotification};
+use crate::theme::UiTheme;
+use floem::ext_event::{register_ext_trigger, ExtSendTrigger};
+use floem::menu::{Menu, MenuItem};
+use floem::prelude::*;
+use floem::reactive::create_effect;
+use floem::style::CursorStyle;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Most notifications kept in the center (oldest are dropped).
+const MAX_NOTIFICATIONS: usize = 50;
+
+/// Window for `NotificationSettings::max_per_minute`.
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// A notification waiting for the UI thread.
+struct PostedNotification {
+    workspace_id: usize,
+    pane_id: usize,
+    notification: TerminalNotification,
+}
+
+/// Notifications posted from IO threads, with the trigger of the center draining them.
+static INBOX: OnceLock<Mutex<(Vec<PostedNotification>, Option<ExtSendTrigger>)>> = OnceLock::new();
+
+fn inbox() -> &'static Mutex<(Vec<PostedNotification>, Option<ExtSendTrigger>)> {
+    INBOX.get_or_init(|| Mutex::new((Vec::new(), None)))
+}
+
+/// Post a notification from the pane `pane_id` of workspace tab `workspace_id`.
+/// Safe to call from any thread.
+pub fn post_notification(workspace_id: usize, pane_id: usize, notification: TerminalNotification) {
+    let trigger = match inbox().lock() {
+        Ok(mut guard) => {
+            guard.0.push(PostedNotification {
+                workspace_id,
+                pane_id,
+                notification,
+            });
+            guard.1
+        }
+        Err(_) => return,
+    };
+    if let Some(trigger) = trigger {
+        register_ext_trigger(trigger);
+    }
+}
+
+#[derive(Clone)]
+struct CenterEntry {
+    workspace_id: usize,
+    pane_id: usize,
+    /// "workspace · pane title"
+    source: String,
+    title: Option<String>,
+    body: String,
+}
+
+/// Bell button with an unread count; clicking lists the notifications.
+pub fn notification_center(
+    tabs: RwSignal<Vec<WorkspaceTab>>,
+    active_tab: RwSignal<usize>,
+    settings: RwSignal<Settings>,
+    theme: UiTheme,
+) -> impl IntoView {
+    let entries: RwSignal<Vec<CenterEntry>> = RwSignal::new(Vec::new());
+    let unread = RwSignal::new(0usize);
+    let limiters: Rc<RefCell<HashMap<(usize, usize), NotificationRateLimiter>>> =
+        Rc::new(RefCell::new(HashMap::new()));
+
+    let trigger = ExtSendTrigger::new();
+    if let Ok(mut guard) = inbox().lock() {
+        guard.1 = Some(trigger);
+    }
+
+    create_effect(move |_| {
+        trigger.track();
+        let posted = inbox()
+            .lock()
+            .map(|mut guard| std::mem::take(&mut guard.0))
+            .unwrap_or_default();
+        if posted.is_empty() {
+            return;
+        }
+
+        let max_per_minute =
+            settings.with_untracked(|settings| settings.notifications.max_per_minute) as usize;
+        let now = Instant::now();
+        let tabs_now = tabs.get_untracked();
+        for PostedNotification { workspace_id, pane_id, notification } in posted {
+            let Some(tab) = tabs_now.iter().find(|tab| tab.id == workspace_id) else {
+                continue;
+            };
+            let root = tab.root.get_untracked();
+            if !settings.with_untracked(|settings| settings.notifications_enabled(&root)) {
+                continue;
+            }
+
+            let allowed = limiters
+                .borrow_mut()
+                .entry((workspace_id, pane_id))
+                .or_insert_with(|| NotificationRateLimiter::new(max_per_minute, RATE_LIMIT_WINDOW))
+                .allow(now);
+            if !allowed {
+                logging::log_line(
+                    "WARN",
+                    &format!("Notification from pane {pane_id} dropped (rate limit): {}", notification.body),
+                );
+                continue;
+            }
+
+            let pane_title = tab.terminal_panes.with_untracked(|panes| {
+                panes
+                    .iter()
+                    .find(|pane| pane.id == pane_id)
+                    .map(|pane| pane.title.get_untracked())
+            });
+            let Some(pane_title) = pane_title else {
+                continue;
+            };
+            logging::log_line(
+                "INFO",
+                &format!("Notification from {} pane {pane_id}: {:?}", root.display(), notification),
+            );
+            let entry = CenterEntry {
+                workspace_id,
+                pane_id,
+                source: format!("{} · {}", tab.name.get_untracked(), pane_title),
+                title: notification.title,
+                body: notification.body,
+            };
+            entries.update(|entries| {
+                entries.insert(0, entry);
+                entries.truncate(MAX_NOTIFICATIONS);
+            });
+            unread.update(|count| *count += 1);
+        }
+    });
+
+    let focus_pane = move |workspace_id: usize, pane_id: usize| {
+        logging::log_line("INFO", &format!("Notification: focus workspace {workspace_id} pane {pane_id}"));
+        active_tab.set(workspace_id);
+        let pane = tabs.with_untracked(|tabs| {
+            tabs.iter()
+                .find(|tab| tab.id == workspace_id)
+                .and_then(|tab| {
+                    tab.terminal_panes
+                        .with_untracked(|panes| panes.iter().find(|pane| pane.id == pane_id).cloned())
+                })
+        });
+        if let Some(pane) = pane {
+            pane.should_focus.set(true);
+        }
+    };
+
+    label(move || match unread.get() {
+        0 => "🔔".to_string(),
+        count => format!("🔔 {count}"),
+    })
+    .style(move |s| {
+        let has_unread = unread.get() > 0;
+        s.font_size(12.0)
+            .padding_horiz(8.0)
+            .padding_vert(3.0)
+            .border_radius(6.0)
+            .cursor(CursorStyle::Pointer)
+            .color(if has_unread { theme.accent } else { theme.text_muted })
+            .hover(|s| s.background(theme.element_bg))
+    })
+    .popout_menu(move || {
+        unread.set(0);
+        let current = entries.get_untracked();
+        if current.is_empty() {
+            return Menu::new("").entry(MenuItem::new("No notifications").enabled(false));
+        }
+
+        let mut menu = Menu::new("");
+        for entry in current {
+            let text = match &entry.title {
+                Some(title) if entry.body.is_empty() => title.clone(),
+                Some(title) => format!("{title}: {}", entry.body),
+                None => entry.body.clone(),
+            };
+            let (workspace_id, pane_id) = (entry.workspace_id, entry.pane_id);
+            menu = menu.entry(
+                MenuItem::new(format!("{text} — {}", entry.source))
+                    .action(move || focus_pane(workspace_id, pane_id)),
+            );
+        }
+        menu.separator().entry(MenuItem::new("Clear Notifications").action(move || {
+            entries.set(Vec::new());
+        }))
+    })
+}
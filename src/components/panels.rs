@@ -4,8 +4,8 @@ use crate::components::atoms::{
 };
 use crate::components::icons::{CHEVRON_DOWN, CHEVRON_RIGHT, FILE, FOLDER, GIT};
 use crate::logging;
-use crate::model::TreeEntry;
-use crate::services::list_dir_entries;
+use crate::model::{BackgroundTask, TreeEntry};
+use crate::services::{format_duration, list_dir_entries, TaskStatus};
 use crate::theme::{TREE_INDENT, UiTheme};
 use floem::event::{EventListener, EventPropagation};
 use floem::ext_event::{register_ext_trigger, ExtSendTrigger};
@@ -334,6 +334,65 @@ where
     .style(|s| s.flex_col().width_full())
 }
 
+/// Runs of `background` launchers: status, name, running time and exit code. Clicking a
+/// run calls `on_open`, which shows its output in a pane.
+pub fn background_tasks_view<O>(
+    tasks: RwSignal<Vec<BackgroundTask>>,
+    on_open: O,
+    theme: UiTheme,
+) -> impl IntoView
+where
+    O: Fn(BackgroundTask) + Clone + 'static,
+{
+    let rows = dyn_stack(
+        move || tasks.get(),
+        |task| task.id,
+        move |task| {
+            let on_open = on_open.clone();
+            let (status, duration) = (task.status, task.duration);
+            let command = task.command.clone();
+
+            let row = h_stack((
+                label(move || status.get().symbol().to_string()).style(move |s| {
+                    let color = match status.get() {
+                        TaskStatus::Running => theme.accent,
+                        TaskStatus::Succeeded => theme.text_muted,
+                        TaskStatus::Failed(_) => Color::from_rgb8(235, 87, 87),
+                    };
+                    s.font_size(11.0).color(color)
+                }),
+                list_label(task.name.clone(), theme, true)
+                    .style(|s| s.flex_grow(1.0).flex_shrink(1.0).min_width(0.0).text_ellipsis()),
+                label(move || format!("{} · {}", format_duration(duration.get()), status.get().label()))
+                    .style(move |s| s.font_size(11.0).color(theme.text_soft).flex_shrink(0.0)),
+            ))
+            .style(|s| s.width_full().items_center().col_gap(6.0));
+
+            list_item(row, TREE_INDENT, theme)
+                .on_click_stop(move |_| on_open(task.clone()))
+                .style(|s| s.cursor(floem::style::CursorStyle::Pointer))
+                .context_menu(move || {
+                    let command = command.clone();
+                    floem::menu::Menu::new("").entry(
+                        floem::menu::MenuItem::new("Copy Command")
+                            .action(move || crate::services::set_clipboard_string(&command)),
+                    )
+                })
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    let placeholder = label(|| "Background launchers show up here").style(move |s| {
+        s.font_size(12.0)
+            .color(theme.text_soft)
+            .padding_horiz(10.0)
+            .padding_vert(4.0)
+            .display(if tasks.with(Vec::is_empty) { Display::Flex } else { Display::None })
+    });
+
+    v_stack((rows, placeholder)).style(|s| s.width_full())
+}
+
 fn toggle_dir(
     entries: RwSignal<Vec<Arc<TreeEntry>>>,
     entry_id: String,
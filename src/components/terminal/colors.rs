@@ -16,13 +16,43 @@ use floem::peniko::{Brush, Color};
 use std::ops::{Index, IndexMut};
 
 #[cfg(target_os = "macos")]
-pub fn background_brush(theme: UiTheme) -> Brush {
-    Brush::from(theme.panel_bg)
+pub fn background_brush(palette: &TerminalPalette) -> Brush {
+    Brush::from(ansi_rgb_to_color(palette.primary_background))
 }
 
+/// Cursor fill: the scheme's cursor color when set, otherwise a translucent UI accent.
 #[cfg(target_os = "macos")]
-pub fn cursor_brush(theme: UiTheme) -> Brush {
-    Brush::from(theme.accent.with_alpha(0.7))
+pub fn cursor_brush(theme: UiTheme, palette: &TerminalPalette) -> Brush {
+    match palette.cursor {
+        Some(rgb) => Brush::from(ansi_rgb_to_color(rgb)),
+        None => Brush::from(theme.accent.with_alpha(0.7)),
+    }
+}
+
+/// Selection (foreground, background): the scheme's colors when set, otherwise
+/// dark text on a white background.
+#[cfg(target_os = "macos")]
+pub fn selection_colors(palette: &TerminalPalette) -> (Color, Color) {
+    let fg = palette
+        .selection_foreground
+        .map(ansi_rgb_to_color)
+        .unwrap_or(Color::from_rgb8(30, 30, 30));
+    let bg = palette
+        .selection_background
+        .map(ansi_rgb_to_color)
+        .unwrap_or(Color::from_rgb8(255, 255, 255));
+    (fg, bg)
+}
+
+/// Copy mode cursor uses a distinct color so it is never confused with the shell cursor.
+#[cfg(target_os = "macos")]
+pub fn copy_mode_cursor_brush() -> Brush {
+    Brush::from(Color::from_rgba8(255, 184, 108, 170))
+}
+
+#[cfg(target_os = "macos")]
+pub fn search_match_brush(theme: UiTheme) -> Brush {
+    Brush::from(theme.accent.with_alpha(0.35))
 }
 
 #[cfg(target_os = "macos")]
@@ -72,6 +102,7 @@ impl TerminalColorList {
         self[NamedColor::Foreground] = palette.primary_foreground;
         self[NamedColor::Background] = palette.primary_background;
         self[NamedColor::BrightForeground] = palette.bright[7];
+        self[NamedColor::Cursor] = palette.cursor.unwrap_or(palette.primary_foreground);
 
         // Dimmed foreground and ANSI colors.
         let dim_fg = {
@@ -135,6 +166,11 @@ impl TerminalColorList {
         debug_assert!(index == 256);
     }
 
+    /// Copy of the full color table, used by the session to answer color queries.
+    pub fn colors(&self) -> [alacritty_terminal::vte::ansi::Rgb; alacritty_terminal::term::color::COUNT] {
+        self.0
+    }
+
     pub fn color_for_index(&self, index: usize, overrides: &TermColors) -> alacritty_terminal::vte::ansi::Rgb {
         let clamped = index.min(self.0.len().saturating_sub(1));
         overrides[clamped].unwrap_or(self.0[clamped])
@@ -217,12 +253,21 @@ pub fn resolve_fg_color(
                 (true, Flags::BOLD, 0..=7) => {
                     palette_index = idx as usize + 8;
                 }
-                (false, Flags::DIM, 8..=15) => {
-                    palette_index = idx as usize - 8;
+                (_, Flags::DIM, 8..=15) => {
+                    palette_index = NamedColor::DimBlack as usize + idx as usize - 8;
                 }
-                (false, Flags::DIM, 0..=7) => {
+                (_, Flags::DIM, 0..=7) => {
                     palette_index = NamedColor::DimBlack as usize + idx as usize;
                 }
+                (_, Flags::DIM, _) => {
+                    // Cube and gray ramp colors have no dim variant; scale them instead
+                    let rgb = palette.color_for_index(palette_index, overrides);
+                    return ansi_rgb_to_color(alacritty_terminal::vte::ansi::Rgb {
+                        r: (rgb.r as f32 * DIM_FACTOR) as u8,
+                        g: (rgb.g as f32 * DIM_FACTOR) as u8,
+                        b: (rgb.b as f32 * DIM_FACTOR) as u8,
+                    });
+                }
                 _ => {}
             }
 
@@ -1,11 +1,22 @@
 //! Terminal component constants.
 
+#[cfg(target_os = "macos")]
+use crate::services::FontSettings;
+
 #[cfg(target_os = "macos")]
 use floem::text::FamilyOwned;
 
-/// Base font size for terminal cells.
+/// Smallest font size reachable by zooming out.
+#[cfg(target_os = "macos")]
+pub const MIN_TERMINAL_FONT_SIZE: f32 = 6.0;
+
+/// Largest font size reachable by zooming in.
+#[cfg(target_os = "macos")]
+pub const MAX_TERMINAL_FONT_SIZE: f32 = 48.0;
+
+/// Font size change per zoom step (Cmd/Ctrl +/-).
 #[cfg(target_os = "macos")]
-pub const TERMINAL_FONT_SIZE: f32 = 13.0;
+pub const FONT_ZOOM_STEP: f32 = 1.0;
 
 /// Width of the splitter handle between terminal panes.
 #[cfg(target_os = "macos")]
@@ -35,8 +46,22 @@ pub const SPLIT_TRIGGER_DELAY_MS: u64 = 100;
 #[cfg(target_os = "macos")]
 pub const SPLIT_SECOND_WAVE_MS: u64 = 150;
 
-/// Terminal font families in preference order.
+/// Interval between cursor blink phases (ms).
+#[cfg(target_os = "macos")]
+pub const CURSOR_BLINK_INTERVAL_MS: u64 = 750;
+
+/// Blinking stops (cursor stays visible) after this long without input or output (s).
+#[cfg(target_os = "macos")]
+pub const CURSOR_BLINK_TIMEOUT_SECS: u64 = 5;
+
+/// Terminal font families in preference order: the configured family, its fallbacks,
+/// then the system monospace font (Menlo does not exist outside macOS).
 #[cfg(target_os = "macos")]
-pub fn terminal_font_families() -> [FamilyOwned; 1] {
-    [FamilyOwned::Name("Menlo".into())]
+pub fn terminal_font_families(font: &FontSettings) -> Vec<FamilyOwned> {
+    std::iter::once(&font.family)
+        .chain(font.fallback.iter())
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| FamilyOwned::Name(name.as_str().into()))
+        .chain(std::iter::once(FamilyOwned::Monospace))
+        .collect()
 }
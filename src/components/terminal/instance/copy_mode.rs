@@ -0,0 +1,581 @@
+//! Terminal instance copy mode - keyboard-driven scrollback navigation and copying.
+//!
+//! Copy mode is built on alacritty's vi mode: the vi cursor moves independently of the
+//! shell cursor, motions are resolved by `Term::vi_motion`, and visual selections reuse
+//! `Term::selection` so the regular selection rendering applies unchanged.
+
+#[cfg(target_os = "macos")]
+use alacritty_terminal::{
+    event::EventListener,
+    grid::{Dimensions, Scroll},
+    index::{Boundary, Column, Direction, Point, Side},
+    selection::{Selection, SelectionType},
+    term::{
+        search::{Match, RegexSearch},
+        Term, TermMode,
+    },
+    vi_mode::ViMotion,
+};
+
+#[cfg(target_os = "macos")]
+use floem::keyboard::{Key, Modifiers, NamedKey};
+
+/// Per-pane copy mode state that is not stored in the `Term` itself.
+#[cfg(target_os = "macos")]
+#[derive(Clone, Default)]
+pub struct CopyModeState {
+    /// `g` was pressed and we are waiting for the second key of `gg`.
+    pub pending_g: bool,
+    /// Search prompt currently being typed (`/` or `?`).
+    pub search_input: Option<SearchInput>,
+    /// Last executed search, repeated by `n` / `N`.
+    pub last_search: Option<(String, Direction)>,
+    /// Range of the last search match, highlighted by the renderer.
+    pub last_match: Option<Match>,
+    /// Transient status message (e.g. "Pattern not found").
+    pub status: Option<String>,
+}
+
+/// Search prompt being edited in copy mode.
+#[cfg(target_os = "macos")]
+#[derive(Clone)]
+pub struct SearchInput {
+    pub query: String,
+    pub direction: Direction,
+}
+
+#[cfg(target_os = "macos")]
+impl CopyModeState {
+    /// Text for the status line drawn at the bottom of the pane.
+    pub fn status_line(&self) -> String {
+        if let Some(input) = &self.search_input {
+            let prefix = if input.direction == Direction::Right {
+                '/'
+            } else {
+                '?'
+            };
+            return format!("{prefix}{}", input.query);
+        }
+        match &self.status {
+            Some(status) => format!("-- COPY -- {status}"),
+            None => "-- COPY --".to_string(),
+        }
+    }
+}
+
+/// Result of feeding a key press to copy mode.
+#[cfg(target_os = "macos")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum CopyModeOutcome {
+    /// Key was consumed; stay in copy mode.
+    Handled,
+    /// Key has no copy mode binding.
+    Ignored,
+    /// Leave copy mode without copying.
+    Exit,
+    /// Copy the text to the clipboard and leave copy mode.
+    Yank(String),
+}
+
+/// Shortcut that enters copy mode (Ctrl+Shift+Space, same as Alacritty).
+#[cfg(target_os = "macos")]
+pub fn is_copy_mode_toggle(key: &Key, modifiers: &Modifiers) -> bool {
+    if !(modifiers.control() && modifiers.shift()) {
+        return false;
+    }
+    match key {
+        Key::Named(NamedKey::Space) => true,
+        Key::Character(text) => text == " ",
+        _ => false,
+    }
+}
+
+/// Switch the terminal into vi mode, placing the vi cursor on the shell cursor.
+#[cfg(target_os = "macos")]
+pub fn enter_copy_mode<T: EventListener>(term: &mut Term<T>) {
+    if !term.mode().contains(TermMode::VI) {
+        term.toggle_vi_mode();
+    }
+}
+
+/// Leave vi mode, drop any selection and jump back to the live screen.
+#[cfg(target_os = "macos")]
+pub fn exit_copy_mode<T: EventListener>(term: &mut Term<T>) {
+    if term.mode().contains(TermMode::VI) {
+        term.toggle_vi_mode();
+    }
+    term.selection = None;
+    term.scroll_display(Scroll::Bottom);
+}
+
+/// Handle a key press while the pane is in copy mode.
+#[cfg(target_os = "macos")]
+pub fn handle_copy_mode_key<T: EventListener>(
+    term: &mut Term<T>,
+    state: &mut CopyModeState,
+    key: &Key,
+    modifiers: &Modifiers,
+) -> CopyModeOutcome {
+    // Cmd shortcuts keep their normal meaning (Cmd+C copies, Cmd+V pastes).
+    if modifiers.meta() {
+        return CopyModeOutcome::Ignored;
+    }
+
+    if state.search_input.is_some() {
+        return handle_search_key(term, state, key);
+    }
+
+    let pending_g = std::mem::take(&mut state.pending_g);
+    state.status = None;
+
+    match key {
+        Key::Named(named) => match named {
+            NamedKey::Escape => {
+                if term.selection.is_some() {
+                    term.selection = None;
+                    CopyModeOutcome::Handled
+                } else {
+                    CopyModeOutcome::Exit
+                }
+            }
+            NamedKey::ArrowUp => motion(term, ViMotion::Up),
+            NamedKey::ArrowDown => motion(term, ViMotion::Down),
+            NamedKey::ArrowLeft => motion(term, ViMotion::Left),
+            NamedKey::ArrowRight => motion(term, ViMotion::Right),
+            NamedKey::Home => motion(term, ViMotion::First),
+            NamedKey::End => motion(term, ViMotion::Last),
+            NamedKey::PageUp => scroll_pages(term, 1.0),
+            NamedKey::PageDown => scroll_pages(term, -1.0),
+            NamedKey::Enter => yank(term, state),
+            _ => CopyModeOutcome::Ignored,
+        },
+        Key::Character(text) => {
+            let Some(ch) = text.chars().next() else {
+                return CopyModeOutcome::Ignored;
+            };
+
+            if modifiers.control() {
+                return match ch.to_ascii_lowercase() {
+                    'u' => scroll_pages(term, 0.5),
+                    'd' => scroll_pages(term, -0.5),
+                    'b' => scroll_pages(term, 1.0),
+                    'f' => scroll_pages(term, -1.0),
+                    'v' => toggle_selection(term, SelectionType::Block),
+                    'c' => CopyModeOutcome::Exit,
+                    _ => CopyModeOutcome::Ignored,
+                };
+            }
+
+            match ch {
+                'h' => motion(term, ViMotion::Left),
+                'j' => motion(term, ViMotion::Down),
+                'k' => motion(term, ViMotion::Up),
+                'l' => motion(term, ViMotion::Right),
+                'w' => motion(term, ViMotion::SemanticRight),
+                'b' => motion(term, ViMotion::SemanticLeft),
+                'e' => motion(term, ViMotion::SemanticRightEnd),
+                'W' => motion(term, ViMotion::WordRight),
+                'B' => motion(term, ViMotion::WordLeft),
+                'E' => motion(term, ViMotion::WordRightEnd),
+                '0' => motion(term, ViMotion::First),
+                '^' => motion(term, ViMotion::FirstOccupied),
+                '$' => motion(term, ViMotion::Last),
+                'H' => motion(term, ViMotion::High),
+                'M' => motion(term, ViMotion::Middle),
+                'L' => motion(term, ViMotion::Low),
+                '%' => motion(term, ViMotion::Bracket),
+                '{' => motion(term, ViMotion::ParagraphUp),
+                '}' => motion(term, ViMotion::ParagraphDown),
+                'g' => {
+                    if pending_g {
+                        let top = Point::new(term.topmost_line(), Column(0));
+                        term.vi_goto_point(top);
+                    } else {
+                        state.pending_g = true;
+                    }
+                    CopyModeOutcome::Handled
+                }
+                'G' => {
+                    let bottom = Point::new(term.bottommost_line(), Column(0));
+                    term.vi_goto_point(bottom);
+                    CopyModeOutcome::Handled
+                }
+                'v' => toggle_selection(term, SelectionType::Simple),
+                'V' => toggle_selection(term, SelectionType::Lines),
+                'y' => yank(term, state),
+                '/' => start_search(state, Direction::Right),
+                '?' => start_search(state, Direction::Left),
+                'n' => repeat_search(term, state, false),
+                'N' => repeat_search(term, state, true),
+                'q' | 'i' => CopyModeOutcome::Exit,
+                _ => CopyModeOutcome::Ignored,
+            }
+        }
+        _ => CopyModeOutcome::Ignored,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn motion<T: EventListener>(term: &mut Term<T>, motion: ViMotion) -> CopyModeOutcome {
+    term.vi_motion(motion);
+    CopyModeOutcome::Handled
+}
+
+/// Scroll the viewport by a fraction of the screen height (positive = towards history).
+/// `Term::scroll_display` keeps the vi cursor clamped inside the viewport.
+#[cfg(target_os = "macos")]
+fn scroll_pages<T: EventListener>(term: &mut Term<T>, pages: f64) -> CopyModeOutcome {
+    let lines = (term.screen_lines() as f64 * pages).round() as i32;
+    term.scroll_display(Scroll::Delta(lines));
+    CopyModeOutcome::Handled
+}
+
+#[cfg(target_os = "macos")]
+fn toggle_selection<T: EventListener>(term: &mut Term<T>, ty: SelectionType) -> CopyModeOutcome {
+    match term.selection.as_mut() {
+        Some(selection) if selection.ty == ty => {
+            term.selection = None;
+        }
+        Some(selection) => {
+            selection.ty = ty;
+        }
+        None => {
+            let mut selection = Selection::new(ty, term.vi_mode_cursor.point, Side::Left);
+            selection.include_all();
+            term.selection = Some(selection);
+        }
+    }
+    CopyModeOutcome::Handled
+}
+
+#[cfg(target_os = "macos")]
+fn yank<T: EventListener>(term: &mut Term<T>, state: &mut CopyModeState) -> CopyModeOutcome {
+    match term.selection_to_string() {
+        Some(text) if !text.is_empty() => CopyModeOutcome::Yank(text),
+        _ => {
+            state.status = Some("nothing selected".to_string());
+            CopyModeOutcome::Handled
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn start_search(state: &mut CopyModeState, direction: Direction) -> CopyModeOutcome {
+    state.search_input = Some(SearchInput {
+        query: String::new(),
+        direction,
+    });
+    CopyModeOutcome::Handled
+}
+
+#[cfg(target_os = "macos")]
+fn handle_search_key<T: EventListener>(
+    term: &mut Term<T>,
+    state: &mut CopyModeState,
+    key: &Key,
+) -> CopyModeOutcome {
+    let Some(input) = state.search_input.as_mut() else {
+        return CopyModeOutcome::Ignored;
+    };
+
+    match key {
+        Key::Named(NamedKey::Escape) => {
+            state.search_input = None;
+        }
+        Key::Named(NamedKey::Backspace) => {
+            if input.query.pop().is_none() {
+                state.search_input = None;
+            }
+        }
+        Key::Named(NamedKey::Space) => {
+            input.query.push(' ');
+        }
+        Key::Named(NamedKey::Enter) => {
+            let SearchInput { query, direction } = state
+                .search_input
+                .take()
+                .expect("search input checked above");
+            if !query.is_empty() {
+                run_search(term, state, &query, direction);
+                state.last_search = Some((query, direction));
+            }
+        }
+        Key::Character(text) => {
+            input.query.push_str(text);
+        }
+        _ => return CopyModeOutcome::Ignored,
+    }
+
+    CopyModeOutcome::Handled
+}
+
+#[cfg(target_os = "macos")]
+fn repeat_search<T: EventListener>(
+    term: &mut Term<T>,
+    state: &mut CopyModeState,
+    reverse: bool,
+) -> CopyModeOutcome {
+    let Some((query, direction)) = state.last_search.clone() else {
+        state.status = Some("no previous search".to_string());
+        return CopyModeOutcome::Handled;
+    };
+
+    let direction = if reverse {
+        direction.opposite()
+    } else {
+        direction
+    };
+    run_search(term, state, &query, direction);
+    CopyModeOutcome::Handled
+}
+
+/// Find the next match starting just past the vi cursor and move the cursor onto it.
+#[cfg(target_os = "macos")]
+fn run_search<T: EventListener>(
+    term: &mut Term<T>,
+    state: &mut CopyModeState,
+    query: &str,
+    direction: Direction,
+) {
+    let mut regex = match RegexSearch::new(query) {
+        Ok(regex) => regex,
+        Err(_) => {
+            state.status = Some(format!("invalid pattern: {query}"));
+            state.last_match = None;
+            return;
+        }
+    };
+
+    let cursor = term.vi_mode_cursor.point;
+    let origin = match direction {
+        Direction::Right => cursor.add(term, Boundary::None, 1),
+        Direction::Left => cursor.sub(term, Boundary::None, 1),
+    };
+
+    match term.search_next(&mut regex, origin, direction, Side::Left, None) {
+        Some(found) => {
+            term.vi_goto_point(*found.start());
+            state.last_match = Some(found);
+        }
+        None => {
+            state.status = Some(format!("pattern not found: {query}"));
+            state.last_match = None;
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "macos"))]
+mod tests {
+    use super::*;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::index::Line;
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+
+    /// A 20x4 terminal in copy mode, with `output` printed before entering it.
+    struct Pane {
+        term: Term<VoidListener>,
+        state: CopyModeState,
+    }
+
+    impl Pane {
+        fn new(output: &str) -> Self {
+            let mut term = Term::new(Config::default(), &TermSize::new(20, 4), VoidListener);
+            let mut parser: Processor<StdSyncHandler> = Processor::new();
+            parser.advance(&mut term, output.as_bytes());
+            enter_copy_mode(&mut term);
+            Self {
+                term,
+                state: CopyModeState::default(),
+            }
+        }
+
+        fn key(&mut self, key: Key, modifiers: Modifiers) -> CopyModeOutcome {
+            handle_copy_mode_key(&mut self.term, &mut self.state, &key, &modifiers)
+        }
+
+        /// Type `keys` one character at a time; the outcome of the last one.
+        fn keys(&mut self, keys: &str) -> CopyModeOutcome {
+            let mut outcome = CopyModeOutcome::Ignored;
+            for ch in keys.chars() {
+                outcome = self.key(Key::Character(ch.to_string().into()), Modifiers::empty());
+            }
+            outcome
+        }
+
+        fn enter(&mut self) -> CopyModeOutcome {
+            self.key(Key::Named(NamedKey::Enter), Modifiers::empty())
+        }
+
+        fn ctrl(&mut self, ch: char) -> CopyModeOutcome {
+            self.key(Key::Character(ch.to_string().into()), Modifiers::CONTROL)
+        }
+
+        /// Line and column of the vi cursor.
+        fn cursor(&self) -> (i32, usize) {
+            let point = self.term.vi_mode_cursor.point;
+            (point.line.0, point.column.0)
+        }
+    }
+
+    #[test]
+    fn motions_move_the_vi_cursor() {
+        let mut pane = Pane::new("one two three\r\nfour five\r\nsix");
+        assert!(pane.term.mode().contains(TermMode::VI));
+        assert_eq!(pane.cursor(), (2, 3));
+
+        assert_eq!(pane.keys("0"), CopyModeOutcome::Handled);
+        assert_eq!(pane.cursor(), (2, 0));
+        pane.keys("k");
+        assert_eq!(pane.cursor(), (1, 0));
+        pane.keys("w");
+        assert_eq!(pane.cursor(), (1, 5));
+        pane.keys("$");
+        assert_eq!(pane.cursor(), (1, 8));
+        pane.key(Key::Named(NamedKey::ArrowUp), Modifiers::empty());
+        pane.keys("b");
+        assert_eq!(pane.cursor(), (0, 4));
+
+        // `gg` takes two presses; another key in between cancels it
+        pane.keys("G");
+        assert_eq!(pane.cursor(), (3, 0));
+        pane.keys("gj");
+        assert_eq!(pane.cursor(), (3, 0));
+        pane.keys("g");
+        assert_eq!(pane.cursor(), (3, 0));
+        pane.keys("g");
+        assert_eq!(pane.cursor(), (0, 0));
+
+        assert_eq!(pane.keys("x"), CopyModeOutcome::Ignored);
+        assert_eq!(
+            pane.key(Key::Character("c".into()), Modifiers::META),
+            CopyModeOutcome::Ignored
+        );
+    }
+
+    #[test]
+    fn pages_scroll_through_the_history() {
+        let output: Vec<String> = (1..=12).map(|n| format!("line {n}")).collect();
+        let mut pane = Pane::new(&output.join("\r\n"));
+        assert_eq!(pane.term.grid().display_offset(), 0);
+
+        pane.ctrl('b');
+        assert_eq!(pane.term.grid().display_offset(), 4);
+        pane.ctrl('u');
+        assert_eq!(pane.term.grid().display_offset(), 6);
+        pane.ctrl('d');
+        assert_eq!(pane.term.grid().display_offset(), 4);
+        pane.key(Key::Named(NamedKey::PageDown), Modifiers::empty());
+        assert_eq!(pane.term.grid().display_offset(), 0);
+
+        pane.keys("gg");
+        assert_eq!(pane.cursor(), (-8, 0));
+        assert_eq!(pane.term.grid().display_offset(), 8);
+
+        exit_copy_mode(&mut pane.term);
+        assert!(!pane.term.mode().contains(TermMode::VI));
+        assert_eq!(pane.term.grid().display_offset(), 0);
+    }
+
+    #[test]
+    fn search_finds_matches_and_repeats() {
+        let mut pane = Pane::new("cat dog\r\ndog cat\r\nbird");
+        pane.keys("gg/do");
+        assert_eq!(pane.state.status_line(), "/do");
+        pane.keys("g");
+        assert_eq!(pane.enter(), CopyModeOutcome::Handled);
+        assert_eq!(pane.cursor(), (0, 4));
+        assert_eq!(pane.state.status_line(), "-- COPY --");
+        let found = pane.state.last_match.clone().unwrap();
+        assert_eq!(
+            (*found.start(), *found.end()),
+            (
+                Point::new(Line(0), Column(4)),
+                Point::new(Line(0), Column(6))
+            )
+        );
+
+        pane.keys("n");
+        assert_eq!(pane.cursor(), (1, 0));
+        pane.keys("N");
+        assert_eq!(pane.cursor(), (0, 4));
+
+        // `?` searches backwards
+        pane.keys("G?cat");
+        pane.enter();
+        assert_eq!(pane.cursor(), (1, 4));
+
+        pane.keys("/zebra");
+        pane.enter();
+        assert_eq!(pane.cursor(), (1, 4));
+        assert_eq!(pane.state.last_match, None);
+        assert_eq!(
+            pane.state.status_line(),
+            "-- COPY -- pattern not found: zebra"
+        );
+
+        // Esc leaves the prompt, not copy mode
+        pane.keys("/x");
+        assert_eq!(
+            pane.key(Key::Named(NamedKey::Escape), Modifiers::empty()),
+            CopyModeOutcome::Handled
+        );
+        assert!(pane.state.search_input.is_none());
+    }
+
+    #[test]
+    fn selections_are_yanked() {
+        let mut pane = Pane::new("hello world\r\nsecond line");
+        assert_eq!(pane.enter(), CopyModeOutcome::Handled);
+        assert_eq!(pane.state.status_line(), "-- COPY -- nothing selected");
+
+        pane.keys("gg");
+        assert_eq!(pane.keys("ve"), CopyModeOutcome::Handled);
+        assert_eq!(pane.keys("y"), CopyModeOutcome::Yank("hello".to_string()));
+
+        // `V` turns the selection into whole lines
+        pane.keys("Vj");
+        assert_eq!(
+            pane.enter(),
+            CopyModeOutcome::Yank("hello world\nsecond line\n".to_string())
+        );
+
+        // Block selection keeps the same columns on each line
+        pane.keys("V");
+        assert!(pane.term.selection.is_none());
+        pane.keys("gg");
+        pane.ctrl('v');
+        pane.keys("lj");
+        assert_eq!(pane.keys("y"), CopyModeOutcome::Yank("he\nse".to_string()));
+
+        // Esc drops the selection first, then leaves copy mode
+        assert_eq!(
+            pane.key(Key::Named(NamedKey::Escape), Modifiers::empty()),
+            CopyModeOutcome::Handled
+        );
+        assert!(pane.term.selection.is_none());
+        assert_eq!(
+            pane.key(Key::Named(NamedKey::Escape), Modifiers::empty()),
+            CopyModeOutcome::Exit
+        );
+        assert_eq!(pane.keys("q"), CopyModeOutcome::Exit);
+    }
+
+    #[test]
+    fn toggle_is_ctrl_shift_space() {
+        let ctrl_shift = Modifiers::CONTROL | Modifiers::SHIFT;
+        assert!(is_copy_mode_toggle(
+            &Key::Named(NamedKey::Space),
+            &ctrl_shift
+        ));
+        assert!(is_copy_mode_toggle(
+            &Key::Character(" ".into()),
+            &ctrl_shift
+        ));
+        assert!(!is_copy_mode_toggle(
+            &Key::Named(NamedKey::Space),
+            &Modifiers::CONTROL
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+//! Terminal instance hint mode - label matches on the visible grid and pick one by typing.
+//!
+//! Matching and labelling live in `services::hints`; this module turns the visible part
+//! of the `Term` into text rows and handles the keys typed while labels are shown.
+
+#[cfg(target_os = "macos")]
+use crate::services::hints::{compile_patterns, find_hints, Hint, HintAction, HintRow, HintSettings};
+
+#[cfg(target_os = "macos")]
+use alacritty_terminal::{
+    event::EventListener,
+    grid::Dimensions,
+    index::{Column, Line},
+    term::{cell::Flags, Term},
+};
+
+#[cfg(target_os = "macos")]
+use floem::keyboard::{Key, Modifiers, NamedKey};
+
+/// Labels shown over the visible grid, and what has been typed so far.
+#[cfg(target_os = "macos")]
+#[derive(Clone, Default)]
+pub struct HintModeState {
+    pub hints: Vec<Hint>,
+    /// Label prefix typed so far
+    pub typed: String,
+    /// Action chosen with Tab, replacing each pattern's own action
+    pub action_override: Option<HintAction>,
+    /// Transient status message (e.g. pattern errors)
+    pub status: Option<String>,
+}
+
+#[cfg(target_os = "macos")]
+impl HintModeState {
+    /// Hints whose label still matches what has been typed.
+    pub fn visible_hints(&self) -> impl Iterator<Item = &Hint> {
+        self.hints.iter().filter(|hint| hint.label.starts_with(&self.typed))
+    }
+
+    /// Text for the status line drawn at the bottom of the pane.
+    pub fn status_line(&self) -> String {
+        let action = match self.action_override {
+            Some(action) => action.label(),
+            None => "default action",
+        };
+        let mut line = format!("-- HINTS -- {action} (Tab: change, Shift: copy)");
+        if let Some(status) = &self.status {
+            line.push_str(" -- ");
+            line.push_str(status);
+        }
+        line
+    }
+}
+
+/// Result of feeding a key press to hint mode.
+#[cfg(target_os = "macos")]
+pub enum HintModeOutcome {
+    /// Key was consumed; keep showing labels.
+    Handled,
+    /// Leave hint mode without acting.
+    Exit,
+    /// A label was typed: run `action` on `text` and leave hint mode.
+    Chosen { text: String, action: HintAction },
+}
+
+/// Shortcut that shows hints (Ctrl+Shift+E, same as kitty).
+#[cfg(target_os = "macos")]
+pub fn is_hint_mode_toggle(key: &Key, modifiers: &Modifiers) -> bool {
+    modifiers.control()
+        && modifiers.shift()
+        && matches!(key, Key::Character(text) if text.eq_ignore_ascii_case("e"))
+}
+
+/// Label every match on the visible screen.
+#[cfg(target_os = "macos")]
+pub fn start_hint_mode<T: EventListener>(term: &Term<T>, settings: &HintSettings) -> HintModeState {
+    let (patterns, errors) = compile_patterns(&settings.patterns);
+    let hints = find_hints(&visible_rows(term), &patterns, &settings.alphabet);
+    let status = if !errors.is_empty() {
+        Some(errors.join("; "))
+    } else if hints.is_empty() {
+        Some("No matches".to_string())
+    } else {
+        None
+    };
+    HintModeState {
+        hints,
+        status,
+        ..HintModeState::default()
+    }
+}
+
+/// Visible rows as text, soft-wrapped rows joined so wrapped URLs match whole.
+#[cfg(target_os = "macos")]
+fn visible_rows<T: EventListener>(term: &Term<T>) -> Vec<HintRow> {
+    let grid = term.grid();
+    let columns = grid.columns();
+    let top = -(grid.display_offset() as i32);
+
+    let mut rows = Vec::new();
+    let mut current = HintRow { text: String::new(), cells: Vec::new() };
+    for line in top..top + grid.screen_lines() as i32 {
+        let row = &grid[Line(line)];
+        for col in 0..columns {
+            let cell = &row[Column(col)];
+            if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+                continue;
+            }
+            current.text.push(cell.c);
+            current.cells.push((line, col));
+        }
+        let wraps = columns > 0 && row[Column(columns - 1)].flags.contains(Flags::WRAPLINE);
+        if !wraps {
+            rows.push(std::mem::replace(&mut current, HintRow { text: String::new(), cells: Vec::new() }));
+        }
+    }
+    if !current.text.is_empty() {
+        rows.push(current);
+    }
+    rows
+}
+
+/// Handle a key press while hints are shown.
+#[cfg(target_os = "macos")]
+pub fn handle_hint_key(state: &mut HintModeState, key: &Key, modifiers: &Modifiers) -> HintModeOutcome {
+    state.status = None;
+    match key {
+        Key::Named(NamedKey::Escape) => HintModeOutcome::Exit,
+        Key::Named(NamedKey::Backspace) => {
+            if state.typed.pop().is_none() {
+                return HintModeOutcome::Exit;
+            }
+            HintModeOutcome::Handled
+        }
+        Key::Named(NamedKey::Tab) => {
+            state.action_override = match state.action_override {
+                None => Some(HintAction::ALL[0]),
+                Some(action) => HintAction::ALL
+                    .iter()
+                    .position(|candidate| *candidate == action)
+                    .and_then(|index| HintAction::ALL.get(index + 1).copied()),
+            };
+            HintModeOutcome::Handled
+        }
+        Key::Character(text) if !modifiers.control() && !modifiers.meta() => {
+            let Some(ch) = text.chars().next() else {
+                return HintModeOutcome::Handled;
+            };
+            let copy = modifiers.shift() || ch.is_uppercase();
+            state.typed.push(ch.to_ascii_lowercase());
+
+            if let Some(hint) = state.hints.iter().find(|hint| hint.label == state.typed) {
+                let action = if copy {
+                    HintAction::Copy
+                } else {
+                    state.action_override.unwrap_or(hint.action)
+                };
+                return HintModeOutcome::Chosen { text: hint.text.clone(), action };
+            }
+            if state.visible_hints().next().is_none() {
+                state.status = Some(format!("No hint '{}'", state.typed));
+                state.typed.pop();
+            }
+            HintModeOutcome::Handled
+        }
+        _ => HintModeOutcome::Handled,
+    }
+}
@@ -5,6 +5,10 @@ mod state;
 pub mod renderer;
 #[cfg(target_os = "macos")]
 pub mod input;
+#[cfg(target_os = "macos")]
+pub mod copy_mode;
+#[cfg(target_os = "macos")]
+pub mod hints;
 
 #[cfg(target_os = "macos")]
 pub use state::TerminalInstanceState;
@@ -4,43 +4,67 @@
 use super::super::colors::{TerminalColorList, resolve_bg_color, resolve_fg_color};
 
 #[cfg(target_os = "macos")]
-use super::super::constants::{CELL_PADDING, TERMINAL_FONT_SIZE, terminal_font_families};
+use super::super::constants::{CELL_PADDING, MAX_TERMINAL_FONT_SIZE, MIN_TERMINAL_FONT_SIZE};
 
 #[cfg(target_os = "macos")]
 use alacritty_terminal::{
+    grid::{Dimensions, Grid},
+    index::{Column, Point},
     term::{
-        cell::Flags,
+        cell::{Cell, Flags},
         color::Colors as TermColors,
+        search::Match,
     },
-    vte::ansi::{Color as AnsiColor, NamedColor},
+    vte::ansi::{Color as AnsiColor, CursorShape, CursorStyle, NamedColor},
 };
 
+#[cfg(target_os = "macos")]
+use crate::services::settings::{CursorSettings, CursorShape as CursorShapeSetting};
+
+#[cfg(target_os = "macos")]
+use crate::services::inline_image::{InlineImage, VisiblePlacement};
+
+#[cfg(target_os = "macos")]
+use crate::services::text_width::joins_next_cell;
+
 #[cfg(target_os = "macos")]
 use floem::{
-    peniko::Color,
-    text::{Attrs, AttrsList, TextLayout},
+    kurbo::{BezPath, Rect},
+    peniko::{Blob, Color, Format, Image},
+    text::{Attrs, AttrsList, FamilyOwned, TextLayout},
 };
 
 /// Measures cell dimensions from font metrics.
+/// `line_height` scales the font's line height; `letter_spacing` is added to the cell width.
 /// Returns (cell_width, cell_height, y_offset).
 #[cfg(target_os = "macos")]
-pub fn measure_cell_size() -> (f64, f64, f64) {
-    let font_families = terminal_font_families();
+pub fn measure_cell_size(
+    font_families: &[FamilyOwned],
+    font_size: f32,
+    line_height: f64,
+    letter_spacing: f64,
+) -> (f64, f64, f64) {
     let attrs = Attrs::new()
-        .font_size(TERMINAL_FONT_SIZE)
-        .family(&font_families);
+        .font_size(font_size)
+        .family(font_families);
     let base_attrs_list = AttrsList::new(attrs);
     let mut metrics_layout = TextLayout::new();
     metrics_layout.set_text("m", base_attrs_list, None);
     let metrics_size = metrics_layout.size();
 
-    let cell_width = metrics_size.width.max(1.0);
-    let cell_height = (metrics_size.height * 1.25).max(1.0);
+    let cell_width = (metrics_size.width + letter_spacing).max(1.0);
+    let cell_height = (metrics_size.height * line_height.max(1.0)).max(1.0);
     let y_offset = (cell_height - metrics_size.height) / 2.0;
 
     (cell_width, cell_height, y_offset)
 }
 
+/// Applies a pane's zoom offset to the configured font size, clamped to a readable range.
+#[cfg(target_os = "macos")]
+pub fn zoomed_font_size(base_size: f32, zoom: f32) -> f32 {
+    (base_size + zoom).clamp(MIN_TERMINAL_FONT_SIZE, MAX_TERMINAL_FONT_SIZE)
+}
+
 /// Calculates terminal grid dimensions from canvas size and cell metrics.
 /// Returns (cols, rows).
 #[cfg(target_os = "macos")]
@@ -118,6 +142,212 @@ pub fn resolve_cell_colors(
     (fg, bg)
 }
 
+/// Calculates the rectangles covering a search match, one per visible line.
+/// Lines scrolled out of the viewport are skipped.
+#[cfg(target_os = "macos")]
+pub fn match_highlight_rects(
+    range: &Match,
+    display_offset: usize,
+    columns: usize,
+    screen_lines: usize,
+    cell_width: f64,
+    cell_height: f64,
+) -> Vec<Rect> {
+    let (start, end) = (*range.start(), *range.end());
+    let mut rects = Vec::new();
+    if columns == 0 {
+        return rects;
+    }
+
+    for line in start.line.0..=end.line.0 {
+        let viewport_line = line + display_offset as i32;
+        if viewport_line < 0 || viewport_line as usize >= screen_lines {
+            continue;
+        }
+
+        let first_col = if line == start.line.0 { start.column.0 } else { 0 };
+        let last_col = if line == end.line.0 { end.column.0 } else { columns - 1 };
+        let (x0, y0) = cell_position(first_col as f64, viewport_line as f64, cell_width, cell_height);
+        let x1 = x0 + (last_col.saturating_sub(first_col) + 1) as f64 * cell_width;
+        rects.push(Rect::new(x0, y0, x1, y0 + cell_height));
+    }
+
+    rects
+}
+
+/// Collect into `text` the grapheme cluster starting in the cell at `point` and return
+/// the columns it spans. Combining marks are stored with their cell, but ZWJ emoji
+/// sequences and flags continue over the following cells; they are shaped as one run.
+#[cfg(target_os = "macos")]
+pub fn grapheme_run(grid: &Grid<Cell>, point: Point, text: &mut String) -> usize {
+    let row = &grid[point.line];
+    let columns = grid.columns();
+    text.clear();
+
+    let mut col = point.column.0;
+    loop {
+        let cell = &row[Column(col)];
+        text.push(cell.c);
+        text.extend(cell.zerowidth().into_iter().flatten());
+        col += if cell.flags.contains(Flags::WIDE_CHAR) { 2 } else { 1 };
+        if col >= columns || !joins_next_cell(text, row[Column(col)].c) {
+            break;
+        }
+    }
+    col.min(columns) - point.column.0
+}
+
+/// Font size at which text measuring `text_width` at `font_size` fits in
+/// `available_width` (e.g. the emoji presentation of a one-column character, or an
+/// ambiguous-width glyph the font draws wide).
+#[cfg(target_os = "macos")]
+pub fn fit_font_size(text_width: f64, available_width: f64, font_size: f32) -> f32 {
+    if text_width <= available_width + 0.5 {
+        font_size
+    } else {
+        (font_size as f64 * available_width / text_width) as f32
+    }
+}
+
+/// Rectangle an inline image is drawn in: its cells, keeping the image's aspect ratio
+/// unless the program asked for it to be stretched over them.
+#[cfg(target_os = "macos")]
+pub fn image_rect(visible: &VisiblePlacement, cell_width: f64, cell_height: f64) -> Rect {
+    let (x, y) = cell_position(visible.column as f64, visible.line as f64, cell_width, cell_height);
+    let width = visible.placement.cols as f64 * cell_width;
+    let height = visible.placement.rows as f64 * cell_height;
+    if visible.placement.stretch {
+        return Rect::new(x, y, x + width, y + height);
+    }
+    let (image_width, image_height) = (visible.image.width.max(1) as f64, visible.image.height.max(1) as f64);
+    let scale = (width / image_width).min(height / image_height);
+    Rect::new(x, y, x + image_width * scale, y + image_height * scale)
+}
+
+/// An inline image as something the canvas can draw; the pixels are shared, not copied.
+#[cfg(target_os = "macos")]
+pub fn image_texture(image: &InlineImage) -> Image {
+    Image::new(Blob::new(image.rgba.clone()), Format::Rgba8, image.width, image.height)
+}
+
+/// Line style drawn under a cell (SGR 4 and its `4:x` sub-parameters).
+#[cfg(target_os = "macos")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnderlineStyle {
+    Single,
+    Double,
+    Curly,
+    Dotted,
+    Dashed,
+}
+
+#[cfg(target_os = "macos")]
+pub fn underline_style(flags: Flags) -> Option<UnderlineStyle> {
+    if flags.contains(Flags::UNDERCURL) {
+        Some(UnderlineStyle::Curly)
+    } else if flags.contains(Flags::DOUBLE_UNDERLINE) {
+        Some(UnderlineStyle::Double)
+    } else if flags.contains(Flags::DOTTED_UNDERLINE) {
+        Some(UnderlineStyle::Dotted)
+    } else if flags.contains(Flags::DASHED_UNDERLINE) {
+        Some(UnderlineStyle::Dashed)
+    } else if flags.contains(Flags::UNDERLINE) {
+        Some(UnderlineStyle::Single)
+    } else {
+        None
+    }
+}
+
+/// Rectangles for a straight underline spanning `width` pixels of the cell at (x, y).
+/// Dots and dashes are aligned to the pane origin so they line up across cells.
+/// Curly underlines are paths, see `undercurl_path`.
+#[cfg(target_os = "macos")]
+pub fn underline_rects(style: UnderlineStyle, x: f64, y: f64, width: f64, cell_height: f64) -> Vec<Rect> {
+    const THICKNESS: f64 = 1.0;
+
+    let bottom = y + cell_height - 1.0;
+    let line = |top: f64| Rect::new(x, top, x + width, top + THICKNESS);
+    let pattern = |on: f64, period: f64| {
+        let top = bottom - 2.0 * THICKNESS;
+        let mut rects = Vec::new();
+        let mut start = ((x - CELL_PADDING) / period).floor() * period + CELL_PADDING;
+        while start < x + width {
+            let (x0, x1) = (start.max(x), (start + on).min(x + width));
+            if x1 > x0 {
+                rects.push(Rect::new(x0, top, x1, top + THICKNESS));
+            }
+            start += period;
+        }
+        rects
+    };
+
+    match style {
+        UnderlineStyle::Single => vec![line(bottom - 2.0 * THICKNESS)],
+        UnderlineStyle::Double => vec![line(bottom - 3.0 * THICKNESS), line(bottom - THICKNESS)],
+        UnderlineStyle::Dotted => pattern(THICKNESS, 2.0 * THICKNESS),
+        UnderlineStyle::Dashed => pattern(3.0, 5.0),
+        UnderlineStyle::Curly => Vec::new(),
+    }
+}
+
+/// Wave for a curly underline (undercurl). Each cell holds whole periods so adjacent cells join.
+#[cfg(target_os = "macos")]
+pub fn undercurl_path(x: f64, y: f64, width: f64, cell_height: f64) -> BezPath {
+    let amplitude = (cell_height * 0.08).clamp(1.0, 2.5);
+    let mid = y + cell_height - 1.0 - amplitude;
+    let periods = ((width / cell_height.max(1.0) * 2.0).round() as usize).max(1);
+    let period = width / periods as f64;
+
+    let mut path = BezPath::new();
+    path.move_to((x, mid));
+    for i in 0..periods {
+        let start = x + i as f64 * period;
+        path.quad_to((start + period * 0.25, mid - amplitude * 2.0), (start + period * 0.5, mid));
+        path.quad_to((start + period * 0.75, mid + amplitude * 2.0), (start + period, mid));
+    }
+    path
+}
+
+/// Strikethrough bar across the middle of the cell.
+#[cfg(target_os = "macos")]
+pub fn strikeout_rect(x: f64, y: f64, width: f64, cell_height: f64) -> Rect {
+    let mid = (y + cell_height * 0.55).round();
+    Rect::new(x, mid, x + width, mid + 1.0)
+}
+
+/// Session cursor style for the user's cursor settings.
+#[cfg(target_os = "macos")]
+pub fn default_cursor_style(settings: CursorSettings) -> CursorStyle {
+    let shape = match settings.shape {
+        CursorShapeSetting::Block => CursorShape::Block,
+        CursorShapeSetting::Beam => CursorShape::Beam,
+        CursorShapeSetting::Underline => CursorShape::Underline,
+    };
+    CursorStyle { shape, blinking: settings.blinking }
+}
+
+/// Rectangles making up a cursor of `shape` in the cell at (x, y).
+/// Beam and underline are thin bars; the hollow block is four 1px edges.
+#[cfg(target_os = "macos")]
+pub fn cursor_rects(shape: CursorShape, x: f64, y: f64, cell_width: f64, cell_height: f64) -> Vec<Rect> {
+    const BAR_WIDTH: f64 = 2.0;
+    const EDGE_WIDTH: f64 = 1.0;
+
+    let (right, bottom) = (x + cell_width, y + cell_height);
+    match shape {
+        CursorShape::Block => vec![Rect::new(x, y, right, bottom)],
+        CursorShape::Beam => vec![Rect::new(x, y, x + BAR_WIDTH, bottom)],
+        CursorShape::Underline => vec![Rect::new(x, bottom - BAR_WIDTH, right, bottom)],
+        CursorShape::HollowBlock => vec![
+            Rect::new(x, y, right, y + EDGE_WIDTH),
+            Rect::new(x, bottom - EDGE_WIDTH, right, bottom),
+            Rect::new(x, y, x + EDGE_WIDTH, bottom),
+            Rect::new(right - EDGE_WIDTH, y, right, bottom),
+        ],
+        CursorShape::Hidden => Vec::new(),
+    }
+}
+
 /// Creates the resize overlay view showing grid dimensions.
 #[cfg(target_os = "macos")]
 pub fn create_grid_overlay_style() -> impl Fn(floem::style::Style) -> floem::style::Style {
@@ -3,12 +3,18 @@
 #[cfg(target_os = "macos")]
 use floem::{
     ext_event::ExtSendTrigger,
-    reactive::RwSignal,
+    reactive::{RwSignal, Trigger},
 };
 
 #[cfg(target_os = "macos")]
 use std::time::Instant;
 
+#[cfg(target_os = "macos")]
+use super::copy_mode::CopyModeState;
+
+#[cfg(target_os = "macos")]
+use super::hints::HintModeState;
+
 /// Bundles all reactive signals for a single terminal instance.
 /// This consolidates the state that was previously scattered across terminal_pane_view.
 #[cfg(target_os = "macos")]
@@ -33,6 +39,8 @@ pub struct TerminalInstanceState {
     pub ime_focused: RwSignal<bool>,
     /// Tick counter for forcing IME cursor updates
     pub ime_update_tick: RwSignal<u64>,
+    /// Uncommitted IME composition text and its cursor byte range, drawn inline at the cursor
+    pub ime_preedit: RwSignal<Option<(String, Option<(usize, usize)>)>>,
     /// Last IME cursor area (position, size) for candidate window placement
     pub last_ime_cursor_area: RwSignal<Option<(floem::kurbo::Point, floem::kurbo::Size)>>,
     /// Last canvas size (width, height) for change detection
@@ -47,6 +55,20 @@ pub struct TerminalInstanceState {
     pub overlay_show_time: RwSignal<Instant>,
     /// Trigger for hiding overlay after delay
     pub overlay_hide_trigger: ExtSendTrigger,
+    /// Copy mode state (`Some` while keyboard copy mode is active)
+    pub copy_mode: RwSignal<Option<CopyModeState>>,
+    /// Hint mode state (`Some` while match labels are shown)
+    pub hint_mode: RwSignal<Option<HintModeState>>,
+    /// Current blink phase (`true` = cursor drawn); stays `true` while not blinking
+    pub cursor_blink_on: RwSignal<bool>,
+    /// Whether a blink timer tick is pending
+    pub cursor_blink_running: RwSignal<bool>,
+    /// Last keyboard input or terminal output; blinking pauses once this is old
+    pub last_cursor_activity: RwSignal<Instant>,
+    /// Trigger fired by the blink timer
+    pub cursor_blink_trigger: Trigger,
+    /// Per-pane font size offset in points (Cmd/Ctrl +/-; 0 = configured size)
+    pub font_zoom: RwSignal<f32>,
 }
 
 #[cfg(target_os = "macos")]
@@ -64,6 +86,7 @@ impl TerminalInstanceState {
             last_pty_resize_at: RwSignal::new(Instant::now()),
             ime_focused: RwSignal::new(false),
             ime_update_tick: RwSignal::new(0),
+            ime_preedit: RwSignal::new(None),
             last_ime_cursor_area: RwSignal::new(None),
             last_canvas_size: RwSignal::new((0.0, 0.0)),
             scroll_accumulator: RwSignal::new(0.0),
@@ -71,6 +94,13 @@ impl TerminalInstanceState {
             resize_overlay_text: RwSignal::new(String::new()),
             overlay_show_time: RwSignal::new(Instant::now()),
             overlay_hide_trigger: ExtSendTrigger::new(),
+            copy_mode: RwSignal::new(None),
+            hint_mode: RwSignal::new(None),
+            cursor_blink_on: RwSignal::new(true),
+            cursor_blink_running: RwSignal::new(false),
+            last_cursor_activity: RwSignal::new(Instant::now()),
+            cursor_blink_trigger: Trigger::new(),
+            font_zoom: RwSignal::new(0.0),
         }
     }
 }
@@ -5,29 +5,81 @@ mod instance;
 mod panel;
 
 use crate::model::{TerminalPane, WorkspaceTab};
-use crate::services::{Launcher, LauncherRunIn, TerminalSession};
+use crate::services::{Launcher, LauncherRunIn, Settings, TerminalSession};
+#[cfg(target_os = "macos")]
+use crate::services::SessionOptions;
+#[cfg(target_os = "macos")]
+use crate::model::BackgroundTask;
+#[cfg(target_os = "macos")]
+use crate::services::{format_duration, TaskStatus};
+use crate::services::diagnostics::Diagnostic;
+#[cfg(target_os = "macos")]
+use crate::services::git_branch;
+#[cfg(target_os = "macos")]
+use crate::services::background::BACKGROUND_SCROLLBACK;
+#[cfg(target_os = "macos")]
+use crate::components::launcher_editor;
+#[cfg(target_os = "macos")]
+use crate::services::launcher_vars::{CommandTemplate, Placeholder, VarError};
+#[cfg(target_os = "macos")]
+use crate::services::process_title::process_cwd;
+#[cfg(target_os = "macos")]
+use crate::components::post_notification;
+#[cfg(target_os = "macos")]
+use crate::services::TerminalNotification;
 use crate::theme::UiTheme;
 
+#[cfg(target_os = "macos")]
+use crate::services::FontSettings;
+#[cfg(target_os = "macos")]
+use crate::services::hints::{hint_path, HintAction};
+#[cfg(target_os = "macos")]
+use crate::services::process_title::resolve_pane_title;
+#[cfg(target_os = "macos")]
+use crate::services::remote_control::{with_pane_env, PaneAddress};
+#[cfg(target_os = "macos")]
+use crate::services::shell_profile::DEFAULT_PROFILE_NAME;
+#[cfg(target_os = "macos")]
+use crate::services::text_width::str_width;
 #[cfg(target_os = "macos")]
 use crate::theme::TerminalPalette;
+#[cfg(target_os = "macos")]
+use crate::services::color_scheme::{load_named_color_scheme, ColorSchemeError};
 use floem::prelude::*;
 use std::path::PathBuf;
 
 #[cfg(target_os = "macos")]
 use colors::{
-    TerminalColorList, background_brush, cursor_brush, resolve_bg_color, resolve_fg_color,
+    TerminalColorList, ansi_rgb_to_color, background_brush, copy_mode_cursor_brush, cursor_brush,
+    resolve_bg_color, resolve_fg_color, search_match_brush, selection_colors,
 };
 
 #[cfg(target_os = "macos")]
 use instance::TerminalInstanceState;
 
+#[cfg(target_os = "macos")]
+use instance::copy_mode::{
+    CopyModeOutcome, CopyModeState, enter_copy_mode, exit_copy_mode, handle_copy_mode_key,
+    is_copy_mode_toggle,
+};
+
+#[cfg(target_os = "macos")]
+use instance::hints::{handle_hint_key, is_hint_mode_toggle, start_hint_mode, HintModeOutcome, HintModeState};
+
+#[cfg(target_os = "macos")]
+use instance::renderer::{
+    cursor_rects, default_cursor_style, fit_font_size, grapheme_run, image_rect, image_texture,
+    match_highlight_rects, measure_cell_size, strikeout_rect, undercurl_path, underline_rects,
+    underline_style, zoomed_font_size, UnderlineStyle,
+};
+
 #[cfg(target_os = "macos")]
 use panel::{SplitterDragState, calculate_splitter_drag, DRAG_STATE_SENTINEL};
 
 #[cfg(target_os = "macos")]
 use constants::{
-    CELL_PADDING, OVERLAY_MIN_VISIBLE_MS, OVERLAY_SHOW_DURATION_MS, PTY_RESIZE_DEBOUNCE_MS,
-    SPLITTER_WIDTH, SPLIT_SECOND_WAVE_MS, SPLIT_TRIGGER_DELAY_MS, TERMINAL_FONT_SIZE,
+    CELL_PADDING, CURSOR_BLINK_INTERVAL_MS, CURSOR_BLINK_TIMEOUT_SECS, OVERLAY_MIN_VISIBLE_MS, OVERLAY_SHOW_DURATION_MS, PTY_RESIZE_DEBOUNCE_MS,
+    FONT_ZOOM_STEP, SPLITTER_WIDTH, SPLIT_SECOND_WAVE_MS, SPLIT_TRIGGER_DELAY_MS,
     terminal_font_families,
 };
 
@@ -37,15 +89,16 @@ use crate::logging;
 #[cfg(target_os = "macos")]
 use alacritty_terminal::{
     grid::{Dimensions, Indexed},
-    index::{Column, Side},
+    index::{Column, Line, Point, Side},
     selection::{Selection, SelectionType},
     term::{
         cell::Flags,
+        color::COUNT as COLOR_COUNT,
         point_to_viewport,
         viewport_to_point,
         RenderableContent,
     },
-    vte::ansi::{Color as AnsiColor, CursorShape as AnsiCursorShape, NamedColor},
+    vte::ansi::{Color as AnsiColor, CursorShape as AnsiCursorShape, NamedColor, Rgb as AnsiRgb},
 };
 
 #[cfg(target_os = "macos")]
@@ -55,13 +108,16 @@ use floem::{
     keyboard::{Key, NamedKey},
     menu::{Menu, MenuItem},
     peniko::{
-        kurbo::Rect,
+        kurbo::{Rect, Stroke},
         Brush, Color,
     },
     reactive::{create_effect, RwSignal},
-    text::{Attrs, AttrsList, TextLayout, Weight},
+    text::{Attrs, AttrsList, Style as FontStyle, TextLayout, Weight},
 };
 
+#[cfg(target_os = "macos")]
+use floem_renderer::Img;
+
 #[cfg(target_os = "macos")]
 use std::sync::Arc;
 
@@ -71,6 +127,9 @@ use std::time::{Duration, Instant};
 #[cfg(target_os = "macos")]
 use std::sync::{Mutex, OnceLock};
 
+#[cfg(target_os = "macos")]
+use std::collections::HashMap;
+
 /// Global trigger for forcing terminal repaint from WindowResized events.
 /// This allows layout.rs to bypass the normal canvas paint flow during macOS animations.
 #[cfg(target_os = "macos")]
@@ -101,13 +160,55 @@ pub fn force_terminal_repaint() {
     }
 }
 
+/// Save the scrollback of every pane so it can be restored on the next launch.
+/// Only the first tab of each workspace root is saved (restore fills the first tab);
+/// tabs showing tmux windows are skipped.
+#[cfg(target_os = "macos")]
+pub fn save_scrollback(tabs: &[WorkspaceTab]) {
+    use crate::services::scrollback::{capture_scrollback, save_workspace_scrollback, SavedScrollback};
+
+    let mut saved_roots: Vec<PathBuf> = Vec::new();
+    for tab in tabs.iter().filter(|tab| tab.tmux_window.is_none()) {
+        let root = tab.root.get_untracked();
+        if saved_roots.contains(&root) {
+            continue;
+        }
+        let panes: Vec<SavedScrollback> = tab.terminal_panes.with_untracked(|panes| {
+            panes
+                .iter()
+                .map(|pane| {
+                    pane.session
+                        .get_untracked()
+                        .and_then(|session| session.with_term(capture_scrollback))
+                        .unwrap_or_else(|| SavedScrollback::new(Vec::new()))
+                })
+                .collect()
+        });
+        logging::log_line(
+            "INFO",
+            &format!("Saving scrollback for {} ({} panes)", root.display(), panes.len()),
+        );
+        save_workspace_scrollback(&root, &panes);
+        saved_roots.push(root);
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn save_scrollback(_tabs: &[WorkspaceTab]) {}
+
 /// Platform-gated terminal view entry point.
 ///
 /// On macOS this hosts the real PTY-backed terminal backed by
 /// `alacritty_terminal` and `portable-pty`.
 /// On non-macOS platforms it shows a simple placeholder message.
 #[cfg(target_os = "macos")]
-pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, launchers: RwSignal<Vec<Launcher>>) -> impl IntoView {
+pub fn terminal_view(
+    theme: UiTheme,
+    workspace: WorkspaceTab,
+    launchers: RwSignal<Vec<Launcher>>,
+    config_problems: RwSignal<Vec<(PathBuf, Diagnostic)>>,
+    settings: RwSignal<Settings>,
+) -> impl IntoView {
     use floem::style::CursorStyle;
 
     let workspace_name = workspace.name;
@@ -115,6 +216,30 @@ pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, launchers: RwSigna
     let terminal_panes = workspace.terminal_panes;
     let next_pane_id = workspace.next_pane_id;
     let focused_pane_id = workspace.focused_pane_id;
+    let header_workspace = workspace.clone();
+
+    // Terminal palette for this workspace: the chosen color scheme, or the built-in one
+    let palette = RwSignal::new(TerminalPalette::for_theme(theme));
+    let scheme_error: RwSignal<Option<String>> = RwSignal::new(None);
+    create_effect(move |_| {
+        let root = workspace_root.get();
+        let scheme = settings.with(|settings| settings.workspace(&root).color_scheme);
+        match workspace_palette(theme, scheme.as_deref()) {
+            Ok(loaded) => {
+                if let Some(name) = scheme {
+                    logging::log_line("INFO", &format!("Loaded color scheme '{name}'"));
+                }
+                palette.set(loaded);
+                scheme_error.set(None);
+            }
+            Err(err) => {
+                let msg = err.to_string();
+                logging::log_line("ERROR", &msg);
+                palette.set(TerminalPalette::for_theme(theme));
+                scheme_error.set(Some(msg));
+            }
+        }
+    });
 
     // Track splitter drag state at parent level (not inside dyn_stack)
     // This prevents pane views from being rebuilt when drag state changes
@@ -138,10 +263,13 @@ pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, launchers: RwSigna
             let pane_view = terminal_pane_view(
                 theme,
                 pane.clone(),
+                workspace.clone(),
                 workspace_root,
                 terminal_panes,
                 next_pane_id,
                 focused_pane_id,
+                palette,
+                settings,
             );
 
             // Splitter element (only visible if not last pane)
@@ -258,7 +386,7 @@ pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, launchers: RwSigna
         });
 
     v_stack((
-        control_center_header(launchers, terminal_panes, next_pane_id, focused_pane_id, workspace_root, theme),
+        control_center_header(launchers, config_problems, header_workspace, settings, scheme_error, theme),
         container(panes_stack).style(move |s| {
             s.width_full()
                 .flex_grow(1.0)  // Fill remaining height
@@ -278,10 +406,13 @@ pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, launchers: RwSigna
 fn terminal_pane_view(
     theme: UiTheme,
     pane: TerminalPane,
+    workspace: WorkspaceTab,
     workspace_root: RwSignal<PathBuf>,
     terminal_panes: RwSignal<Vec<TerminalPane>>,
     next_pane_id: RwSignal<usize>,
     focused_pane_id: RwSignal<Option<usize>>,
+    palette: RwSignal<TerminalPalette>,
+    settings: RwSignal<Settings>,
 ) -> impl IntoView {
     let session = pane.session;
     let term_update_trigger = pane.trigger;
@@ -289,6 +420,14 @@ fn terminal_pane_view(
     let pane_title = pane.title;
     let pane_should_focus = pane.should_focus;
     let pane_title_buffer = pane.title_buffer.clone();
+    let pane_process_title_buffer = pane.process_title_buffer.clone();
+    let pinned_title = pane.pinned_title;
+    // The canvas starts the session on its first paint
+    let session_workspace = workspace.clone();
+    let session_pane = pane.clone();
+    let osc_title: RwSignal<Option<String>> = RwSignal::new(None);
+    let process_title: RwSignal<Option<String>> = RwSignal::new(None);
+    let title_editing = RwSignal::new(false);
 
     // Signal to store the ID of the focusable wrapper view
     let focus_handle: RwSignal<Option<floem::ViewId>> = RwSignal::new(None);
@@ -307,6 +446,7 @@ fn terminal_pane_view(
     let last_pty_resize_at = state.last_pty_resize_at;
     let ime_focused = state.ime_focused;
     let ime_update_tick = state.ime_update_tick;
+    let ime_preedit = state.ime_preedit;
     let last_ime_cursor_area = state.last_ime_cursor_area;
     let last_canvas_size = state.last_canvas_size;
     let scroll_accumulator = state.scroll_accumulator;
@@ -314,27 +454,84 @@ fn terminal_pane_view(
     let resize_overlay_text = state.resize_overlay_text;
     let overlay_show_time = state.overlay_show_time;
     let overlay_hide_trigger = state.overlay_hide_trigger.clone();
+    let copy_mode = state.copy_mode;
+    let hint_mode = state.hint_mode;
+    let font_zoom = state.font_zoom;
+    let cursor_blink_on = state.cursor_blink_on;
+    let cursor_blink_running = state.cursor_blink_running;
+    let last_cursor_activity = state.last_cursor_activity;
+    let cursor_blink_trigger = state.cursor_blink_trigger;
+
+    // Run the action of a hint chosen in hint mode
+    let run_hint_action = move |text: String, action: HintAction| {
+        logging::log_line("INFO", &format!("Terminal: hint {} {text:?}", action.label()));
+        let root = workspace_root.get_untracked();
+        match action {
+            HintAction::Copy => crate::services::set_clipboard_string(&text),
+            HintAction::Open => {
+                let target = if text.contains("://") {
+                    text
+                } else {
+                    hint_path(&text, &root).to_string_lossy().into_owned()
+                };
+                if let Err(err) = std::process::Command::new("open").arg(&target).spawn() {
+                    logging::log_line("ERROR", &format!("Failed to open {target}: {err}"));
+                }
+            }
+            HintAction::SendToPane => {
+                let panes = terminal_panes.get_untracked();
+                let other = panes
+                    .iter()
+                    .position(|pane| pane.id == pane_id)
+                    .filter(|_| panes.len() > 1)
+                    .map(|index| &panes[(index + 1) % panes.len()]);
+                match other.and_then(|pane| pane.session.get_untracked()) {
+                    Some(other_session) => {
+                        if let Err(err) = other_session.write(text.as_bytes()) {
+                            logging::log_line("ERROR", &format!("Hint send to pane failed: {err}"));
+                        }
+                    }
+                    None => logging::log_line("WARN", "Hint send to pane: no other pane"),
+                }
+            }
+            HintAction::OpenInEditor => {
+                let path = hint_path(&text, &root);
+                if path.is_file() {
+                    workspace.open_editor_file(path, true, None);
+                } else {
+                    logging::log_line("WARN", &format!("Hint open in editor: {} is not a file", path.display()));
+                }
+            }
+        }
+    };
 
     // Register the resize trigger globally so layout.rs can force repaint after animation
     register_force_repaint_trigger(resize_trigger.clone());
 
     let terminal_canvas = canvas({
         move |cx, size| {
-            // Check for title updates
+            // Check for title updates (OSC title and foreground process)
             if let Ok(mut guard) = pane_title_buffer.try_lock() {
                 if let Some(new_title) = guard.take() {
-                    pane_title.set(new_title);
+                    osc_title.set((!new_title.is_empty()).then_some(new_title));
+                }
+            }
+            if let Ok(mut guard) = pane_process_title_buffer.try_lock() {
+                if let Some(new_title) = guard.take() {
+                    process_title.set((!new_title.is_empty()).then_some(new_title));
                 }
             }
 
-            let workspace_root = workspace_root.get_untracked();
-            let font_families = terminal_font_families();
+            let palette = palette.get_untracked();
+            let font = settings.with_untracked(|settings| settings.font.clone());
+            let font_families = terminal_font_families(&font);
+            let font_size = zoomed_font_size(font.size, font_zoom.get_untracked());
             let render_start = Instant::now();
             let mut rendered_cells: usize = 0;
 
             // 1. Draw background
             let bg_rect = Rect::new(0.0, 0.0, size.width, size.height);
-            let bg_brush = background_brush(theme);
+            let bg_brush = background_brush(&palette);
             cx.fill(&bg_rect, &bg_brush, 0.0);
 
             // 2. Check for error state
@@ -355,30 +552,8 @@ fn terminal_pane_view(
             // 3. Initialize session if needed
             let mut current_session = session.get_untracked();
             if current_session.is_none() {
-                // Callback to trigger repaint from IO thread
-                let notify = {
-                    let term_update_trigger = term_update_trigger;
-                    Arc::new(move || {
-                        register_ext_trigger(term_update_trigger);
-                    })
-                };
-
-                let title_cb = {
-                    let buf = pane_title_buffer.clone();
-                    let trig = term_update_trigger.clone();
-                    Arc::new(move |title: String| {
-                        if let Ok(mut guard) = buf.lock() {
-                            *guard = Some(title);
-                        }
-                        register_ext_trigger(trig);
-                    })
-                };
-
-                match TerminalSession::new(&workspace_root, notify, title_cb) {
-                    Ok(new_session) => {
-                        current_session = Some(new_session.clone());
-                        session.set(Some(new_session));
-                    }
+                match start_pane_session(&session_workspace, &session_pane, settings) {
+                    Ok(new_session) => current_session = Some(new_session),
                     Err(err) => {
                         let msg = format!("Failed to start terminal session: {err}");
                         crate::logging::log_line("ERROR", &msg);
@@ -400,22 +575,24 @@ fn terminal_pane_view(
             
             let (mut cell_width, mut cell_height) = cell_size.get_untracked();
             
-            // Ensure cell metrics are always up-to-date
+            // Ensure cell metrics are always up-to-date.
+            // Font or zoom changes reset cell_size to zero to force a re-measure.
             if canvas_size_changed || cell_width <= 0.0 || cell_height <= 0.0 {
-                let attrs = Attrs::new()
-                    .font_size(TERMINAL_FONT_SIZE)
-                    .family(&font_families);
-                let base_attrs_list = AttrsList::new(attrs);
-                let mut metrics_layout = TextLayout::new();
-                metrics_layout.set_text("m", base_attrs_list, None);
-                let metrics_size = metrics_layout.size();
-                cell_width = metrics_size.width.max(1.0);
-                cell_height = (metrics_size.height * 1.25).max(1.0);
+                let (width, height, y_offset) = measure_cell_size(
+                    &font_families,
+                    font_size,
+                    font.line_height,
+                    font.letter_spacing,
+                );
+                cell_width = width;
+                cell_height = height;
 
                 cell_size.set((cell_width, cell_height));
-                cell_y_offset.set((cell_height - metrics_size.height) / 2.0);
+                cell_y_offset.set(y_offset);
                 last_canvas_size.set((size.width, size.height));
             }
+            // Images sent with a pixel size take as many cells as that covers
+            session.set_cell_size(cell_width, cell_height);
 
             let y_offset = cell_y_offset.get_untracked();
 
@@ -468,8 +645,9 @@ fn terminal_pane_view(
             // This ensures immediate visual update even before PTY resize completes
 
             // 6. Render content
-            let palette = TerminalPalette::for_theme(theme);
             let palette_list = TerminalColorList::from_palette(&palette);
+            let (selection_fg, selection_bg) = selection_colors(&palette);
+            let images = session.visible_images();
 
             session.with_term(|term| {
                 // DEBUG: Log PTY's actual grid size vs canvas calculated size
@@ -489,10 +667,19 @@ fn terminal_pane_view(
                 let selection = content.selection;
                 let cursor = content.cursor;
                 let term_colors = content.colors;
-                let default_bg = theme.panel_bg;
+                // Honor a background set by the program (OSC 11) over the palette one
+                let default_bg = ansi_rgb_to_color(
+                    palette_list.color_for_index(NamedColor::Background as usize, term_colors),
+                );
+                if term_colors[NamedColor::Background].is_some() {
+                    cx.fill(&Rect::new(0.0, 0.0, size.width, size.height), &Brush::from(default_bg), 0.0);
+                }
                 let mut text = String::with_capacity(8);
                 let mut cell_layout = TextLayout::new();
                 let has_selection = selection.is_some();
+                // End of the last grapheme run drawn over several cells; text of the
+                // cells it covers is not drawn again
+                let mut run_end: Option<(Line, usize)> = None;
 
                 for indexed in content.display_iter.by_ref() {
                     rendered_cells += 1;
@@ -526,7 +713,7 @@ fn terminal_pane_view(
                     // This is the common case (especially after a big resize), and
                     // skipping it avoids per-cell color resolution and text layout.
                     if !has_selection
-                        && !flags.contains(Flags::INVERSE)
+                        && !flags.intersects(Flags::INVERSE | Flags::ALL_UNDERLINES | Flags::STRIKEOUT)
                         && cell.zerowidth().is_none()
                         && cell.c.is_whitespace()
                         && matches!(cell.bg, AnsiColor::Named(NamedColor::Background))
@@ -557,10 +744,8 @@ fn terminal_pane_view(
 
                     // Selection uses consistent colors for clean appearance (no grid effect)
                     if is_selected {
-                        // White background, dark text for selection
+                        // Scheme selection colors (white background, dark text by default)
                         // Extend rect by 1px to eliminate sub-pixel gaps between cells
-                        let selection_bg = Color::from_rgb8(255, 255, 255);
-                        let selection_fg = Color::from_rgb8(30, 30, 30);
                         fg_color = selection_fg;
                         let bg_brush = Brush::from(selection_bg);
                         let cell_rect = Rect::new(x, y, x + cell_display_width + 1.0, y + cell_height + 1.0);
@@ -572,40 +757,137 @@ fn terminal_pane_view(
                         cx.fill(&cell_rect, &bg_brush, 0.0);
                     }
 
-                    // Skip text layout/draw for empty cells.
-                    if cell.c.is_whitespace() && cell.zerowidth().is_none() {
+                    // Concealed text (SGR 8) keeps its background only
+                    if flags.contains(Flags::HIDDEN) {
                         continue;
                     }
 
-                    text.clear();
-                    text.push(cell.c);
-                    if let Some(extra) = cell.zerowidth() {
-                        for ch in extra {
-                            text.push(*ch);
+                    // Underline and strikethrough, drawn for blank cells too (underlined spaces).
+                    // The underline uses the SGR 58 color when set.
+                    if let Some(style) = underline_style(flags) {
+                        let underline_color = match cell.underline_color() {
+                            Some(color) if !is_selected => {
+                                resolve_bg_color(term_colors, &palette_list, &color)
+                            }
+                            _ => fg_color,
+                        };
+                        let brush = Brush::from(underline_color);
+                        if style == UnderlineStyle::Curly {
+                            let path = undercurl_path(x, y, cell_display_width, cell_height);
+                            cx.stroke(&path, &brush, &Stroke::new(1.0));
+                        } else {
+                            for rect in underline_rects(style, x, y, cell_display_width, cell_height) {
+                                cx.fill(&rect, &brush, 0.0);
+                            }
                         }
                     }
+                    if flags.contains(Flags::STRIKEOUT) {
+                        let rect = strikeout_rect(x, y, cell_display_width, cell_height);
+                        cx.fill(&rect, &Brush::from(fg_color), 0.0);
+                    }
 
-                    let attrs = Attrs::new()
-                        .color(fg_color)
-                        .font_size(TERMINAL_FONT_SIZE)
-                        .family(&font_families);
-                    let attrs_list = AttrsList::new(attrs);
+                    if run_end.is_some_and(|(line, end)| {
+                        line == indexed.point.line && indexed.point.column.0 < end
+                    }) {
+                        continue;
+                    }
+
+                    // Skip text layout/draw for empty cells.
+                    if cell.c.is_whitespace() && cell.zerowidth().is_none() {
+                        continue;
+                    }
 
-                    cell_layout.set_text(&text, attrs_list, None);
+                    let run_columns = grapheme_run(term.grid(), indexed.point, &mut text);
+                    if run_columns > if is_wide { 2 } else { 1 } {
+                        run_end = Some((indexed.point.line, indexed.point.column.0 + run_columns));
+                    }
+
+                    let text_attrs = |size: f32| {
+                        let mut attrs = Attrs::new()
+                            .color(fg_color)
+                            .font_size(size)
+                            .family(&font_families);
+                        if flags.contains(Flags::BOLD) {
+                            attrs = attrs.weight(Weight::BOLD);
+                        }
+                        if flags.contains(Flags::ITALIC) {
+                            attrs = attrs.style(FontStyle::Italic);
+                        }
+                        attrs
+                    };
+
+                    cell_layout.set_text(&text, AttrsList::new(text_attrs(font_size)), None);
+
+                    // Shrink glyphs wider than the cells the grid gives them
+                    let mut text_y = y + y_offset;
+                    let text_size = cell_layout.size();
+                    let fitted_size =
+                        fit_font_size(text_size.width, run_columns as f64 * cell_width, font_size);
+                    if fitted_size < font_size {
+                        cell_layout.set_text(&text, AttrsList::new(text_attrs(fitted_size)), None);
+                        text_y += (text_size.height - cell_layout.size().height) / 2.0;
+                    }
                     
                     // For wide characters, ensure we don't clip the text
                     if is_wide {
                         cx.save();
-                        cx.draw_text(&cell_layout, floem::kurbo::Point::new(x, y + y_offset));
+                        cx.draw_text(&cell_layout, floem::kurbo::Point::new(x, text_y));
                         cx.restore();
                     } else {
-                        cx.draw_text(&cell_layout, floem::kurbo::Point::new(x, y + y_offset));
+                        cx.draw_text(&cell_layout, floem::kurbo::Point::new(x, text_y));
+                    }
+                }
+
+                // Inline images over the cells they cover, clipped to the grid
+                if !images.is_empty() {
+                    cx.save();
+                    cx.clip(&Rect::new(
+                        CELL_PADDING,
+                        CELL_PADDING,
+                        size.width - CELL_PADDING,
+                        size.height - CELL_PADDING,
+                    ));
+                    for visible in &images {
+                        let key = visible.image.key.to_le_bytes();
+                        cx.draw_img(
+                            Img {
+                                img: image_texture(&visible.image),
+                                hash: &key,
+                            },
+                            image_rect(visible, cell_width, cell_height),
+                        );
+                    }
+                    cx.restore();
+                }
+
+                // Copy mode: highlight the current search match
+                let copy_state = copy_mode.get_untracked();
+                if let Some(range) = copy_state.as_ref().and_then(|state| state.last_match.as_ref()) {
+                    let brush = search_match_brush(theme);
+                    for rect in match_highlight_rects(
+                        range,
+                        content.display_offset,
+                        term.columns(),
+                        term.screen_lines(),
+                        cell_width,
+                        cell_height,
+                    ) {
+                        cx.fill(&rect, &brush, 0.0);
                     }
                 }
 
-                // Cursor - only show on focused pane
+                // Cursor in the shape requested by the program (DECSCUSR).
+                // Unfocused panes show a hollow block; in copy mode the renderable cursor
+                // is the vi cursor, drawn as requested even when unfocused.
                 let is_focused = focused_pane_id.get_untracked() == Some(pane_id);
-                if cursor.shape != AnsiCursorShape::Hidden && session.is_active() && is_focused {
+                let in_copy_mode = copy_state.is_some();
+                let shape = if is_focused || in_copy_mode || cursor.shape == AnsiCursorShape::Hidden {
+                    cursor.shape
+                } else {
+                    AnsiCursorShape::HollowBlock
+                };
+                let blinked_off = is_focused && !in_copy_mode && !cursor_blink_on.get_untracked();
+                if shape != AnsiCursorShape::Hidden && session.is_active() && !blinked_off {
                     if let Some(viewport_cursor) =
                         point_to_viewport(content.display_offset, cursor.point)
                     {
@@ -614,13 +896,170 @@ fn terminal_pane_view(
                         // Add padding offset
                         let x = CELL_PADDING + col * cell_width;
                         let y = CELL_PADDING + row * cell_height;
-                        let cursor_rect = Rect::new(x, y, x + cell_width, y + cell_height);
-                        let brush = cursor_brush(theme);
-                        cx.fill(&cursor_rect, &brush, 0.0);
+                        let brush = if in_copy_mode {
+                            copy_mode_cursor_brush()
+                        } else if let Some(rgb) = term_colors[NamedColor::Cursor] {
+                            // Cursor color set by the program (OSC 12)
+                            Brush::from(ansi_rgb_to_color(rgb))
+                        } else {
+                            cursor_brush(theme, &palette)
+                        };
+                        for rect in cursor_rects(shape, x, y, cell_width, cell_height) {
+                            cx.fill(&rect, &brush, 0.0);
+                        }
+
+                        // Redraw the character under an opaque scheme block cursor in the cursor text color
+                        if let (AnsiCursorShape::Block, Some(_), Some(cursor_text), false) =
+                            (shape, palette.cursor, palette.cursor_text, in_copy_mode)
+                        {
+                            let cell = &term.grid()[cursor.point];
+                            if !cell.c.is_whitespace() {
+                                let attrs = Attrs::new()
+                                    .color(ansi_rgb_to_color(cursor_text))
+                                    .font_size(font_size)
+                                    .family(&font_families);
+                                cell_layout.set_text(&cell.c.to_string(), AttrsList::new(attrs), None);
+                                cx.draw_text(&cell_layout, floem::kurbo::Point::new(x, y + y_offset));
+                            }
+                        }
+                    }
+                }
+
+                // IME composition: draw the uncommitted text over the cursor cell with an
+                // underline. Nothing reaches the PTY until the IME commits.
+                if let Some((preedit, preedit_cursor)) = ime_preedit.get_untracked() {
+                    if let Some(viewport_cursor) =
+                        point_to_viewport(content.display_offset, cursor.point)
+                    {
+                        let fg = ansi_rgb_to_color(palette.primary_foreground);
+                        let attrs = || {
+                            Attrs::new()
+                                .color(fg)
+                                .font_size(font_size)
+                                .family(&font_families)
+                        };
+                        let mut layout = TextLayout::new();
+                        layout.set_text(&preedit, AttrsList::new(attrs()), None);
+                        // Reserve the columns the text will take once committed
+                        let ambiguous = settings.with_untracked(|settings| settings.ambiguous_width);
+                        let text_width = (str_width(&preedit, ambiguous) as f64 * cell_width)
+                            .max(layout.size().width);
+
+                        // Keep the composition inside the pane when the cursor is near the right edge
+                        let cursor_x = CELL_PADDING + viewport_cursor.column.0 as f64 * cell_width;
+                        let max_x = (size.width - CELL_PADDING - text_width).max(CELL_PADDING);
+                        let x = cursor_x.min(max_x);
+                        let y = CELL_PADDING + viewport_cursor.line as f64 * cell_height;
+
+                        cx.fill(
+                            &Rect::new(x, y, x + text_width, y + cell_height),
+                            &Brush::from(default_bg),
+                            0.0,
+                        );
+                        cx.draw_text(&layout, floem::kurbo::Point::new(x, y + y_offset));
+
+                        let underline_y = y + cell_height - 2.0;
+                        let fg_brush = Brush::from(fg);
+                        cx.fill(
+                            &Rect::new(x, underline_y, x + text_width, underline_y + 1.0),
+                            &fg_brush,
+                            0.0,
+                        );
+
+                        // Highlight the IME's active segment, or draw a caret for an empty range
+                        if let Some((start, end)) = preedit_cursor {
+                            let prefix_width = |idx: usize| -> f64 {
+                                match preedit.get(..idx) {
+                                    Some(prefix) if !prefix.is_empty() => {
+                                        let mut prefix_layout = TextLayout::new();
+                                        prefix_layout.set_text(prefix, AttrsList::new(attrs()), None);
+                                        prefix_layout.size().width
+                                    }
+                                    _ => 0.0,
+                                }
+                            };
+                            let start_x = x + prefix_width(start);
+                            if end > start {
+                                let end_x = x + prefix_width(end);
+                                cx.fill(
+                                    &Rect::new(start_x, underline_y - 1.0, end_x, underline_y + 1.0),
+                                    &fg_brush,
+                                    0.0,
+                                );
+                            } else {
+                                cx.fill(
+                                    &Rect::new(start_x, y, start_x + 1.5, y + cell_height),
+                                    &fg_brush,
+                                    0.0,
+                                );
+                            }
+                        }
                     }
                 }
             });
 
+            // Hint mode: highlight every match and draw the rest of its label
+            if let Some(hint_state) = hint_mode.get_untracked() {
+                let (display_offset, columns, screen_lines) = session.with_term(|term| {
+                    (term.grid().display_offset(), term.columns(), term.screen_lines())
+                });
+                let highlight = search_match_brush(theme);
+                let label_attrs = Attrs::new()
+                    .color(theme.panel_bg)
+                    .font_size(font_size)
+                    .weight(Weight::BOLD)
+                    .family(&font_families);
+                for hint in hint_state.visible_hints() {
+                    let range = Point::new(Line(hint.start.0), Column(hint.start.1))
+                        ..=Point::new(Line(hint.end.0), Column(hint.end.1));
+                    let rects = match_highlight_rects(
+                        &range,
+                        display_offset,
+                        columns,
+                        screen_lines,
+                        cell_width,
+                        cell_height,
+                    );
+                    for rect in &rects {
+                        cx.fill(rect, &highlight, 0.0);
+                    }
+
+                    let Some(first) = rects.first() else { continue };
+                    let remaining = &hint.label[hint_state.typed.len()..];
+                    let mut layout = TextLayout::new();
+                    layout.set_text(remaining, AttrsList::new(label_attrs.clone()), None);
+                    let label_rect = Rect::new(
+                        first.x0,
+                        first.y0,
+                        first.x0 + layout.size().width + 2.0,
+                        first.y0 + cell_height,
+                    );
+                    cx.fill(&label_rect, &theme.accent, 0.0);
+                    cx.draw_text(&layout, floem::kurbo::Point::new(first.x0 + 1.0, first.y0 + y_offset));
+                }
+            }
+
+            // Mode status line (copy mode indicator and search prompt, or hint mode)
+            let status_line = copy_mode
+                .with_untracked(|state| state.as_ref().map(CopyModeState::status_line))
+                .or_else(|| hint_mode.with_untracked(|state| state.as_ref().map(HintModeState::status_line)));
+            if let Some(status_line) = status_line {
+                let attrs = Attrs::new()
+                    .color(theme.text)
+                    .font_size(12.0)
+                    .family(&font_families);
+                let mut layout = TextLayout::new();
+                layout.set_text(&status_line, AttrsList::new(attrs), None);
+                let text_size = layout.size();
+                let bar_height = text_size.height + 6.0;
+                let bar_rect = Rect::new(0.0, size.height - bar_height, size.width, size.height);
+                cx.fill(&bar_rect, &theme.panel_bg.with_alpha(0.95), 0.0);
+                cx.draw_text(
+                    &layout,
+                    floem::kurbo::Point::new(CELL_PADDING, size.height - bar_height + 3.0),
+                );
+            }
+
             // 7. Render "Session Ended" overlay if inactive
             if !session.is_active() {
                 let overlay_rect = Rect::new(0.0, 0.0, size.width, size.height);
@@ -705,6 +1144,25 @@ fn terminal_pane_view(
 
     let canvas_id = terminal_canvas.id();
 
+    // Re-measure cells when the font settings or this pane's zoom change.
+    // The next paint computes a new grid size, which resizes the PTY through the debounced path.
+    create_effect(move |prev: Option<(FontSettings, f32)>| {
+        let current = (settings.with(|settings| settings.font.clone()), font_zoom.get());
+        if prev.is_some_and(|prev| prev != current) {
+            cell_size.set((0.0, 0.0));
+            canvas_id.request_paint();
+        }
+        current
+    });
+
+    // Keep the session's color query palette in sync with the workspace palette
+    create_effect(move |_| {
+        let palette = palette.get();
+        if let Some(sess) = session.get() {
+            sess.set_color_palette(TerminalColorList::from_palette(&palette).colors());
+        }
+    });
+
     // Effect to handle programmatic focus request
     create_effect(move |_| {
         if pane_should_focus.get() {
@@ -735,6 +1193,11 @@ fn terminal_pane_view(
         error_msg.track();
         term_update_trigger.track();
         focused_pane_id.track();  // Repaint when focus changes to show/hide cursor
+        copy_mode.track();
+        hint_mode.track();
+        palette.track();
+        ime_preedit.track();
+        cursor_blink_on.track();
         // Request layout first so canvas can detect new size, then repaint
         canvas_id.request_layout();
         canvas_id.request_paint();
@@ -905,6 +1368,98 @@ fn terminal_pane_view(
         }
     });
 
+    // Apply the configured default cursor style; programs override it with DECSCUSR
+    create_effect(move |_| {
+        let cursor_settings = settings.with(|settings| settings.cursor);
+        if let Some(sess) = session.get() {
+            sess.set_default_cursor_style(default_cursor_style(cursor_settings));
+        }
+    });
+
+    // Apply the inline image settings
+    create_effect(move |_| {
+        let image_settings = settings.with(|settings| settings.images);
+        if let Some(sess) = session.get() {
+            sess.set_image_settings(image_settings);
+        }
+    });
+
+    // Apply the ambiguous-width setting to the parser
+    create_effect(move |_| {
+        let ambiguous_width = settings.with(|settings| settings.ambiguous_width);
+        if let Some(sess) = session.get() {
+            sess.set_ambiguous_width(ambiguous_width);
+        }
+    });
+
+    // Cursor blinking. A UI timer fires `cursor_blink_trigger` every blink interval
+    // while the focused cursor blinks; it stops once the pane has been idle for
+    // CURSOR_BLINK_TIMEOUT_SECS so idle panes do not keep repainting.
+    let cursor_should_blink = move || {
+        focused_pane_id.get_untracked() == Some(pane_id)
+            && copy_mode.with_untracked(|state| state.is_none())
+            && session.get_untracked().map_or(false, |sess| {
+                sess.is_active() && sess.with_term(|term| term.cursor_style().blinking)
+            })
+    };
+
+    let schedule_blink = move || {
+        floem::action::exec_after(Duration::from_millis(CURSOR_BLINK_INTERVAL_MS), move |_| {
+            cursor_blink_trigger.notify()
+        });
+    };
+
+    // Any input or output shows the cursor and (re)starts the timer if needed
+    create_effect(move |_| {
+        term_update_trigger.track();
+        ime_update_tick.track();
+        focused_pane_id.track();
+        copy_mode.track();
+        settings.track();
+
+        last_cursor_activity.set(Instant::now());
+        if !cursor_blink_on.get_untracked() {
+            cursor_blink_on.set(true);
+        }
+        if !cursor_blink_running.get_untracked() && cursor_should_blink() {
+            cursor_blink_running.set(true);
+            schedule_blink();
+        }
+    });
+
+    create_effect(move |prev: Option<()>| {
+        cursor_blink_trigger.track();
+        if prev.is_none() {
+            return;
+        }
+
+        cursor_blink_running.set(false);
+        let idle = last_cursor_activity.get_untracked().elapsed()
+            >= Duration::from_secs(CURSOR_BLINK_TIMEOUT_SECS);
+        if idle || !cursor_should_blink() {
+            if !cursor_blink_on.get_untracked() {
+                cursor_blink_on.set(true);
+            }
+            return;
+        }
+
+        cursor_blink_on.update(|on| *on = !*on);
+        cursor_blink_running.set(true);
+        schedule_blink();
+    });
+
+    // Displayed title: pinned name, then OSC title, then foreground process
+    create_effect(move |_| {
+        let title = resolve_pane_title(
+            pinned_title.get().as_deref(),
+            osc_title.get().as_deref(),
+            process_title.get().as_deref(),
+        );
+        if pane_title.get_untracked() != title {
+            pane_title.set(title);
+        }
+    });
+
     let terminal_wrapper = terminal_wrapper.keyboard_navigable();
     
     // Capture the ID of the navigable view for focus requests
@@ -936,6 +1491,7 @@ fn terminal_pane_view(
         .on_event(EventListener::FocusLost, move |_| {
             logging::breadcrumb(format!("terminal pane {} focus lost", pane_id));
             ime_focused.set(false);
+            ime_preedit.set(None);
             floem::action::set_ime_allowed(false);
             // Only clear if we were the focused pane
             if focused_pane_id.get_untracked() == Some(pane_id) {
@@ -943,7 +1499,14 @@ fn terminal_pane_view(
             }
             EventPropagation::Continue
         })
-        .on_event(EventListener::ImePreedit, move |_| {
+        .on_event(EventListener::ImePreedit, move |event| {
+            if let Event::ImePreedit { text, cursor } = event {
+                if text.is_empty() {
+                    ime_preedit.set(None);
+                } else {
+                    ime_preedit.set(Some((text.clone(), *cursor)));
+                }
+            }
             ime_update_tick.update(|tick| *tick = tick.wrapping_add(1));
             EventPropagation::Stop
         })
@@ -965,11 +1528,19 @@ fn terminal_pane_view(
                     }
                     ime_update_tick.update(|tick| *tick = tick.wrapping_add(1));
 
+                    // While the IME is composing, keys belong to the IME; the result
+                    // arrives via ImeCommit.
+                    if ime_preedit.with_untracked(|preedit| preedit.is_some()) {
+                        return EventPropagation::Stop;
+                    }
+
                     // Check for restart if session is inactive
                     if let Some(session_arc) = session.get_untracked() {
                         if !session_arc.is_active() {
                             if matches!(key, Key::Named(NamedKey::Enter)) {
                                 logging::breadcrumb("terminal restart".to_string());
+                                copy_mode.set(None);
+                                hint_mode.set(None);
                                 session.set(None);
                                 error_msg.set(None);
                                 return EventPropagation::Stop;
@@ -1002,6 +1573,82 @@ fn terminal_pane_view(
 
                                         let modifiers = key_event.modifiers;
 
+                    // Hint mode consumes keys until a label is typed or it is dismissed.
+                    if let Some(mut hint_state) = hint_mode.get_untracked() {
+                        match handle_hint_key(&mut hint_state, key, &modifiers) {
+                            HintModeOutcome::Handled => hint_mode.set(Some(hint_state)),
+                            HintModeOutcome::Exit => {
+                                logging::breadcrumb("terminal hint mode exit".to_string());
+                                hint_mode.set(None);
+                            }
+                            HintModeOutcome::Chosen { text, action } => {
+                                hint_mode.set(None);
+                                run_hint_action(text, action);
+                            }
+                        }
+                        return EventPropagation::Stop;
+                    }
+
+                    // Copy mode consumes keys before they reach the PTY.
+                    if let Some(mut copy_state) = copy_mode.get_untracked() {
+                        let outcome = session.with_term_mut(|term| {
+                            handle_copy_mode_key(term, &mut copy_state, key, &modifiers)
+                        });
+                        match outcome {
+                            CopyModeOutcome::Handled => {
+                                copy_mode.set(Some(copy_state));
+                                return EventPropagation::Stop;
+                            }
+                            // Let Cmd shortcuts (e.g. Cmd+C) fall through; swallow the rest.
+                            CopyModeOutcome::Ignored if modifiers.meta() => {}
+                            CopyModeOutcome::Ignored => return EventPropagation::Stop,
+                            CopyModeOutcome::Exit => {
+                                logging::breadcrumb("terminal copy mode exit".to_string());
+                                session.with_term_mut(exit_copy_mode);
+                                copy_mode.set(None);
+                                return EventPropagation::Stop;
+                            }
+                            CopyModeOutcome::Yank(text) => {
+                                crate::services::set_clipboard_string(&text);
+                                logging::log_line("INFO", "Terminal: copy mode yanked selection to clipboard");
+                                session.with_term_mut(exit_copy_mode);
+                                copy_mode.set(None);
+                                return EventPropagation::Stop;
+                            }
+                        }
+                    } else if is_copy_mode_toggle(key, &modifiers) {
+                        logging::breadcrumb("terminal copy mode enter".to_string());
+                        session.with_term_mut(enter_copy_mode);
+                        copy_mode.set(Some(CopyModeState::default()));
+                        return EventPropagation::Stop;
+                    } else if is_hint_mode_toggle(key, &modifiers) {
+                        logging::breadcrumb("terminal hint mode enter".to_string());
+                        let hint_settings = settings.with_untracked(|settings| settings.hints.clone());
+                        let hint_state = session.with_term(|term| start_hint_mode(term, &hint_settings));
+                        hint_mode.set(Some(hint_state));
+                        return EventPropagation::Stop;
+                    }
+
+                    // Cmd/Ctrl +/-/0: per-pane font zoom
+                    if modifiers.meta() || modifiers.control() {
+                        if let Key::Character(ch) = key {
+                            let zoom = match ch.as_str() {
+                                "=" | "+" => Some(font_zoom.get_untracked() + FONT_ZOOM_STEP),
+                                "-" | "_" => Some(font_zoom.get_untracked() - FONT_ZOOM_STEP),
+                                "0" => Some(0.0),
+                                _ => None,
+                            };
+                            if let Some(zoom) = zoom {
+                                let base = settings.with_untracked(|settings| settings.font.size);
+                                // Keep the offset within the clamped range so zooming back is immediate
+                                let zoom = zoomed_font_size(base, zoom) - base;
+                                logging::breadcrumb(format!("terminal zoom: pane {pane_id} offset {zoom}"));
+                                font_zoom.set(zoom);
+                                return EventPropagation::Stop;
+                            }
+                        }
+                    }
+
                     // Handle Cmd+C / Cmd+V for clipboard integration.
                     if modifiers.meta() {
                         if let Key::Character(ch) = key {
@@ -1137,6 +1784,7 @@ fn terminal_pane_view(
             logging::measure_ui_event("terminal ime commit", || {
                 if let Event::ImeCommit(text) = event {
                     logging::breadcrumb(format!("terminal ime commit: len={}", text.len()));
+                    ime_preedit.set(None);
                     ime_update_tick.update(|tick| *tick = tick.wrapping_add(1));
                     
                     let Some(session) = session.get_untracked() else { return EventPropagation::Continue; };
@@ -1361,6 +2009,7 @@ fn terminal_pane_view(
         })
         .context_menu(move || {
             let session_for_reset = session.clone();
+            let copy_mode_for_reset = copy_mode;
             let error_msg_for_reset = error_msg;
 
             // Check if there's a selection to show copy option
@@ -1383,6 +2032,25 @@ fn terminal_pane_view(
                 }));
             }
 
+            if copy_mode.get_untracked().is_none() {
+                menu = menu.entry(MenuItem::new("Copy Mode").action(move || {
+                    if let Some(sess) = session.get_untracked() {
+                        sess.with_term_mut(enter_copy_mode);
+                        copy_mode.set(Some(CopyModeState::default()));
+                    }
+                }));
+            }
+
+            if hint_mode.get_untracked().is_none() {
+                menu = menu.entry(MenuItem::new("Show Hints").action(move || {
+                    if let Some(sess) = session.get_untracked() {
+                        let hint_settings = settings.with_untracked(|settings| settings.hints.clone());
+                        hint_mode.set(Some(sess.with_term(|term| start_hint_mode(term, &hint_settings))));
+                        pane_should_focus.set(true);
+                    }
+                }));
+            }
+
             menu = menu.entry(MenuItem::new("Paste").action(move || {
                 if let Some(sess) = session.get_untracked() {
                     if let Some(text) = crate::services::get_clipboard_string() {
@@ -1396,56 +2064,108 @@ fn terminal_pane_view(
                 }
             }));
 
-            // Split actions
+            // Title actions
             menu = menu
                 .separator()
-                .entry(MenuItem::new("Split Right").action(move || {
-                    logging::log_line("INFO", &format!("Terminal: Split right from pane {pane_id}"));
-                    let new_id = next_pane_id.get();
-                    next_pane_id.set(new_id + 1);
+                .entry(MenuItem::new("Rename Pane…").action(move || {
+                    title_editing.set(true);
+                }));
+            if pinned_title.get_untracked().is_some() {
+                menu = menu.entry(MenuItem::new("Unpin Title").action(move || {
+                    pinned_title.set(None);
+                }));
+            }
 
-                                                                let new_pane = TerminalPane {
-                                                                    id: new_id,
-                                                                    session: RwSignal::new(None),
-                                                                    trigger: floem::ext_event::ExtSendTrigger::new(),
-                                                                    flex_ratio: RwSignal::new(1.0),
-                                                                    title: RwSignal::new("Terminal".to_string()),
-                                                                    should_focus: RwSignal::new(true),
-                                                                    title_buffer: Arc::new(Mutex::new(None)),
-                                                                };                    terminal_panes.update(|panes| {
-                        // Find current pane index and insert after it
-                        if let Some(idx) = panes.iter().position(|p| p.id == pane_id) {
-                            panes.insert(idx + 1, new_pane);
-                        } else {
-                            panes.push(new_pane);
-                        }
-                    });
-                    // Collect triggers before spawning thread (RwSignal is not Send)
-                    let triggers: Vec<_> = terminal_panes.get_untracked()
-                        .iter()
-                        .map(|p| (p.id, p.trigger.clone()))
-                        .collect();
-                    let pane_count = triggers.len();
-                    // Delay trigger to allow layout to recalculate after pane list changes
-                    std::thread::spawn(move || {
-                        logging::log_line(
-                            "DEBUG",
-                            &format!("[Split Right] triggering {} panes after {}ms delay", pane_count, SPLIT_TRIGGER_DELAY_MS),
-                        );
-                        std::thread::sleep(std::time::Duration::from_millis(SPLIT_TRIGGER_DELAY_MS));
-                        for (id, trigger) in triggers.iter() {
-                            logging::log_line("DEBUG", &format!("[Split Right] trigger pane {}", id));
-                            register_ext_trigger(trigger.clone());
-                        }
-                        // Second wave trigger to ensure layout is complete
-                        logging::log_line("DEBUG", &format!("[Split Right] second wave after {}ms", SPLIT_SECOND_WAVE_MS));
-                        std::thread::sleep(std::time::Duration::from_millis(SPLIT_SECOND_WAVE_MS));
-                        for (id, trigger) in triggers.iter() {
-                            logging::log_line("DEBUG", &format!("[Split Right] trigger pane {} (2nd)", id));
-                            register_ext_trigger(trigger.clone());
-                        }
-                    });
-                }))
+            // New pane to the right of this one (`profile` = None uses the workspace default)
+            let split_right = move |profile: Option<String>| {
+                logging::log_line("INFO", &format!("Terminal: Split right from pane {pane_id}"));
+                let new_id = next_pane_id.get();
+                next_pane_id.set(new_id + 1);
+
+                let new_pane = TerminalPane {
+                    id: new_id,
+                    session: RwSignal::new(None),
+                    trigger: floem::ext_event::ExtSendTrigger::new(),
+                    flex_ratio: RwSignal::new(1.0),
+                    title: RwSignal::new("Terminal".to_string()),
+                    should_focus: RwSignal::new(true),
+                    title_buffer: Arc::new(Mutex::new(None)),
+                    process_title_buffer: Arc::new(Mutex::new(None)),
+                    pinned_title: RwSignal::new(None),
+                    profile,
+                    restored_output: Arc::new(Mutex::new(None)),
+                    tmux: None,
+                };
+                terminal_panes.update(|panes| {
+                    // Find current pane index and insert after it
+                    if let Some(idx) = panes.iter().position(|p| p.id == pane_id) {
+                        panes.insert(idx + 1, new_pane);
+                    } else {
+                        panes.push(new_pane);
+                    }
+                });
+                // Collect triggers before spawning thread (RwSignal is not Send)
+                let triggers: Vec<_> = terminal_panes.get_untracked()
+                    .iter()
+                    .map(|p| (p.id, p.trigger.clone()))
+                    .collect();
+                let pane_count = triggers.len();
+                // Delay trigger to allow layout to recalculate after pane list changes
+                std::thread::spawn(move || {
+                    logging::log_line(
+                        "DEBUG",
+                        &format!("[Split Right] triggering {} panes after {}ms delay", pane_count, SPLIT_TRIGGER_DELAY_MS),
+                    );
+                    std::thread::sleep(std::time::Duration::from_millis(SPLIT_TRIGGER_DELAY_MS));
+                    for (id, trigger) in triggers.iter() {
+                        logging::log_line("DEBUG", &format!("[Split Right] trigger pane {}", id));
+                        register_ext_trigger(trigger.clone());
+                    }
+                    // Second wave trigger to ensure layout is complete
+                    logging::log_line("DEBUG", &format!("[Split Right] second wave after {}ms", SPLIT_SECOND_WAVE_MS));
+                    std::thread::sleep(std::time::Duration::from_millis(SPLIT_SECOND_WAVE_MS));
+                    for (id, trigger) in triggers.iter() {
+                        logging::log_line("DEBUG", &format!("[Split Right] trigger pane {} (2nd)", id));
+                        register_ext_trigger(trigger.clone());
+                    }
+                });
+            };
+
+            // Shell profiles for new panes
+            let root = workspace_root.get_untracked();
+            let (profiles, current_profile) = settings.with_untracked(|settings| {
+                (settings.profiles.clone(), settings.workspace(&root).default_profile)
+            });
+            let profile_names: Vec<String> = std::iter::once(DEFAULT_PROFILE_NAME.to_string())
+                .chain(profiles.iter().map(|profile| profile.name.clone()))
+                .collect();
+            let mut split_with_menu = Menu::new("Split Right With Profile");
+            let mut default_profile_menu = Menu::new("Default Shell Profile");
+            for name in profile_names {
+                let split_name = name.clone();
+                split_with_menu = split_with_menu.entry(
+                    MenuItem::new(name.clone()).action(move || split_right(Some(split_name.clone()))),
+                );
+
+                let profile = (name != DEFAULT_PROFILE_NAME).then(|| name.clone());
+                let checked = profile == current_profile;
+                let root = root.clone();
+                default_profile_menu = default_profile_menu.entry(
+                    MenuItem::new(if checked { format!("✓ {name}") } else { name }).action(move || {
+                        logging::log_line("INFO", &format!("Terminal: default profile set to {profile:?}"));
+                        settings.update(|settings| {
+                            settings.workspace_mut(&root).default_profile = profile.clone();
+                        });
+                        crate::services::save_settings(&settings.get_untracked());
+                    }),
+                );
+            }
+
+            // Split actions
+            menu = menu
+                .separator()
+                .entry(MenuItem::new("Split Right").action(move || split_right(None)))
+                .entry(split_with_menu)
                 .entry(MenuItem::new("Split Left").action(move || {
                     logging::log_line("INFO", &format!("Terminal: Split left from pane {pane_id}"));
                     let new_id = next_pane_id.get();
@@ -1459,6 +2179,11 @@ fn terminal_pane_view(
                                                                     title: RwSignal::new("Terminal".to_string()),
                                                                     should_focus: RwSignal::new(true),
                                                                     title_buffer: Arc::new(Mutex::new(None)),
+                                                                    process_title_buffer: Arc::new(Mutex::new(None)),
+                                                                    pinned_title: RwSignal::new(None),
+                                                                    profile: None,
+                                                                    restored_output: Arc::new(Mutex::new(None)),
+                                                                    tmux: None,
                                                                 };                    terminal_panes.update(|panes| {
                         // Find current pane index and insert before it
                         if let Some(idx) = panes.iter().position(|p| p.id == pane_id) {
@@ -1494,10 +2219,61 @@ fn terminal_pane_view(
                     });
                 }));
 
+            // Color scheme for this workspace (files in ~/.config/tide/themes)
+            let root = workspace_root.get_untracked();
+            let current_scheme = settings.with_untracked(|settings| settings.workspace(&root).color_scheme);
+            let scheme_item = |label: String, scheme: Option<String>| {
+                let checked = scheme == current_scheme;
+                let root = root.clone();
+                MenuItem::new(if checked { format!("✓ {label}") } else { label }).action(move || {
+                    logging::log_line("INFO", &format!("Terminal: color scheme set to {scheme:?}"));
+                    settings.update(|settings| {
+                        settings.workspace_mut(&root).color_scheme = scheme.clone();
+                    });
+                    crate::services::save_settings(&settings.get_untracked());
+                })
+            };
+            let mut scheme_menu = Menu::new("Color Scheme").entry(scheme_item("Default".to_string(), None));
+            let schemes = crate::services::color_scheme::list_color_schemes();
+            if !schemes.is_empty() {
+                scheme_menu = scheme_menu.separator();
+            }
+            for scheme in schemes {
+                scheme_menu = scheme_menu.entry(scheme_item(scheme.name.clone(), Some(scheme.name)));
+            }
+            scheme_menu = scheme_menu
+                .separator()
+                .entry(MenuItem::new("Open Themes Folder").action(|| {
+                    let dir = crate::services::config::themes_dir();
+                    let _ = std::fs::create_dir_all(&dir);
+                    let _ = std::process::Command::new("open").arg(dir).spawn();
+                }));
+            // Program notifications (OSC 9 / OSC 777) for this workspace
+            let notifications_on = settings.with_untracked(|settings| settings.notifications_enabled(&root));
+            let notifications_item = MenuItem::new(if notifications_on {
+                "✓ Show Program Notifications"
+            } else {
+                "Show Program Notifications"
+            })
+            .action(move || {
+                logging::log_line("INFO", &format!("Terminal: notifications set to {}", !notifications_on));
+                settings.update(|settings| {
+                    settings.workspace_mut(&root).notifications = Some(!notifications_on);
+                });
+                crate::services::save_settings(&settings.get_untracked());
+            });
+            menu = menu
+                .separator()
+                .entry(scheme_menu)
+                .entry(default_profile_menu)
+                .entry(notifications_item);
+
             menu = menu
                 .separator()
                 .entry(MenuItem::new("Reset Terminal").action(move || {
                     logging::log_line("INFO", "Terminal: Reset requested");
+                    copy_mode_for_reset.set(None);
+                    hint_mode.set(None);
                     session_for_reset.set(None);
                     error_msg_for_reset.set(None);
                 }));
@@ -1508,13 +2284,19 @@ fn terminal_pane_view(
     // Overlay is now drawn directly in canvas (no event blocking)
     // Wrap with pane header
     v_stack((
-        pane_header(pane_title, theme),
+        pane_header(pane_title, pinned_title, title_editing, theme),
         terminal_wrapper.style(|s| s.flex_grow(1.0).width_full().height_full())
     )).style(|s| s.width_full().height_full().min_width(0.0))
 }
 
 #[cfg(not(target_os = "macos"))]
-pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, _launchers: RwSignal<Vec<Launcher>>) -> impl IntoView {
+pub fn terminal_view(
+    theme: UiTheme,
+    workspace: WorkspaceTab,
+    _launchers: RwSignal<Vec<Launcher>>,
+    _config_problems: RwSignal<Vec<(PathBuf, Diagnostic)>>,
+    _settings: RwSignal<Settings>,
+) -> impl IntoView {
     let workspace_name = workspace.name;
     v_stack((
         label(|| "Terminal").style(move |s| {
@@ -1539,13 +2321,466 @@ pub fn terminal_view(theme: UiTheme, workspace: WorkspaceTab, _launchers: RwSign
     .style(|s| s.width_full().height_full().row_gap(8.0).min_width(0.0))
 }
 
+/// Terminal palette of a workspace using color scheme `scheme` (`None` for the one
+/// built from `theme`).
+#[cfg(target_os = "macos")]
+fn workspace_palette(theme: UiTheme, scheme: Option<&str>) -> Result<TerminalPalette, ColorSchemeError> {
+    match scheme {
+        Some(name) => load_named_color_scheme(name),
+        None => Ok(TerminalPalette::for_theme(theme)),
+    }
+}
+
+/// Palette a new session of the workspace at `root` answers color queries with, until
+/// its pane sets the palette it shows.
+#[cfg(target_os = "macos")]
+fn session_palette(root: &std::path::Path, settings: RwSignal<Settings>) -> [AnsiRgb; COLOR_COUNT] {
+    let theme = UiTheme::new();
+    let scheme = settings.with_untracked(|settings| settings.workspace(root).color_scheme);
+    let palette = workspace_palette(theme, scheme.as_deref()).unwrap_or_else(|_| TerminalPalette::for_theme(theme));
+    TerminalColorList::from_palette(&palette).colors()
+}
+
+/// Start the session of `pane`: its tmux pane, or a shell with its profile in the
+/// workspace root, which gets the output saved by the previous run. Panes start theirs on
+/// first paint; calling this earlier lets input be written before the pane is shown.
+#[cfg(target_os = "macos")]
+pub fn start_pane_session(
+    workspace: &WorkspaceTab,
+    pane: &TerminalPane,
+    settings: RwSignal<Settings>,
+) -> std::io::Result<Arc<TerminalSession>> {
+    let notify = {
+        let trigger = pane.trigger;
+        Arc::new(move || {
+            register_ext_trigger(trigger);
+        })
+    };
+
+    let title_cb = {
+        let buf = pane.title_buffer.clone();
+        let trig = pane.trigger;
+        Arc::new(move |title: String| {
+            if let Ok(mut guard) = buf.lock() {
+                *guard = Some(title);
+            }
+            register_ext_trigger(trig);
+        })
+    };
+
+    let process_title_cb = {
+        let buf = pane.process_title_buffer.clone();
+        let trig = pane.trigger;
+        Arc::new(move |title: Option<String>| {
+            if let Ok(mut guard) = buf.lock() {
+                *guard = Some(title.unwrap_or_default());
+            }
+            register_ext_trigger(trig);
+        })
+    };
+
+    let (workspace_id, pane_id) = (workspace.id, pane.id);
+    let notification_cb = Arc::new(move |notification: TerminalNotification| {
+        post_notification(workspace_id, pane_id, notification);
+    });
+
+    let root = workspace.root.get_untracked();
+    let profile = settings.with_untracked(|settings| match pane.profile.as_deref() {
+        Some(name) => settings.profile(Some(name)),
+        None => settings.workspace_profile(&root),
+    });
+    let profile = with_pane_env(&profile, PaneAddress::new(workspace_id, pane_id));
+
+    // Output saved by the previous run is replayed only into the first session
+    let replay = pane
+        .restored_output
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take())
+        .unwrap_or_default();
+
+    let session = match pane.tmux {
+        Some(tmux) => TerminalSession::tmux_pane(tmux.gateway, tmux.pane, notify, title_cb)?,
+        None => {
+            let options = SessionOptions { replay: &replay, palette: Some(session_palette(&root, settings)) };
+            TerminalSession::new(&root, &profile, options, notify, title_cb, process_title_cb, notification_cb)?
+        }
+    };
+    pane.session.set(Some(session.clone()));
+    Ok(session)
+}
+
+/// Insert a new pane to the right of pane `after` (the last pane when `None`) and start
+/// its shell with `profile` (`None` uses the workspace default).
+#[cfg(target_os = "macos")]
+pub fn split_pane(
+    workspace: &WorkspaceTab,
+    after: Option<usize>,
+    profile: Option<String>,
+    settings: RwSignal<Settings>,
+) -> std::io::Result<TerminalPane> {
+    let new_id = workspace.next_pane_id.get_untracked();
+    workspace.next_pane_id.set(new_id + 1);
+
+    let new_pane = TerminalPane {
+        id: new_id,
+        session: RwSignal::new(None),
+        trigger: ExtSendTrigger::new(),
+        flex_ratio: RwSignal::new(1.0),
+        title: RwSignal::new("Terminal".to_string()),
+        should_focus: RwSignal::new(true),
+        title_buffer: Arc::new(Mutex::new(None)),
+        process_title_buffer: Arc::new(Mutex::new(None)),
+        pinned_title: RwSignal::new(None),
+        profile,
+        restored_output: Arc::new(Mutex::new(None)),
+        tmux: None,
+    };
+    start_pane_session(workspace, &new_pane, settings)?;
+    insert_pane(workspace, after, new_pane.clone());
+    Ok(new_pane)
+}
+
+/// Insert `new_pane` to the right of pane `after` (the last pane when `None`).
+#[cfg(target_os = "macos")]
+fn insert_pane(workspace: &WorkspaceTab, after: Option<usize>, new_pane: TerminalPane) {
+    let terminal_panes = workspace.terminal_panes;
+    terminal_panes.update(|panes| {
+        match after.and_then(|id| panes.iter().position(|p| p.id == id)) {
+            Some(idx) => panes.insert(idx + 1, new_pane),
+            None => panes.push(new_pane),
+        }
+    });
+
+    // Trigger layout update
+    let triggers: Vec<_> = terminal_panes
+        .get_untracked()
+        .iter()
+        .map(|p| p.trigger)
+        .collect();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(SPLIT_TRIGGER_DELAY_MS));
+        for trigger in triggers {
+            register_ext_trigger(trigger);
+        }
+    });
+}
+
+/// Run a `background` launcher's (expanded) `command` in a session that no pane shows,
+/// and list the run in the workspace's Tasks panel.
+#[cfg(target_os = "macos")]
+fn start_background_task(
+    workspace: &WorkspaceTab,
+    launcher: &Launcher,
+    command: &str,
+    settings: RwSignal<Settings>,
+) -> std::io::Result<BackgroundTask> {
+    let trigger = ExtSendTrigger::new();
+    let viewer: Arc<Mutex<Option<ExtSendTrigger>>> = Arc::new(Mutex::new(None));
+    let notify = {
+        let viewer = Arc::clone(&viewer);
+        Arc::new(move || {
+            register_ext_trigger(trigger);
+            // Repaint the pane showing the output, if any
+            if let Some(pane_trigger) = viewer.lock().ok().and_then(|guard| *guard) {
+                register_ext_trigger(pane_trigger);
+            }
+        })
+    };
+
+    let root = workspace.root.get_untracked();
+    let profile = launcher.background_profile(command, &root);
+    let options = SessionOptions {
+        palette: Some(session_palette(&root, settings)),
+        scrollback: Some(BACKGROUND_SCROLLBACK),
+        ..SessionOptions::default()
+    };
+    let session = TerminalSession::new(
+        &root,
+        &profile,
+        options,
+        notify,
+        Arc::new(|_: String| {}),
+        Arc::new(|_: Option<String>| {}),
+        Arc::new(|_: TerminalNotification| {}),
+    )?;
+
+    let id = workspace.next_task_id.get_untracked();
+    workspace.next_task_id.set(id + 1);
+    let task = BackgroundTask {
+        id,
+        name: launcher.name.clone(),
+        command: command.to_string(),
+        session,
+        trigger,
+        viewer,
+        started: Instant::now(),
+        status: RwSignal::new(TaskStatus::Running),
+        duration: RwSignal::new(Duration::ZERO),
+    };
+    workspace.background_tasks.update(|tasks| tasks.push(task.clone()));
+    Ok(task)
+}
+
+/// Keep the status and running time of the runs in `tasks` current: when one prints or
+/// exits, and every second while one runs. Called once, by the workspace's Tasks panel.
+#[cfg(target_os = "macos")]
+pub fn track_background_tasks(tasks: RwSignal<Vec<BackgroundTask>>) {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    // Ticks while a run is going, so the running time moves while the command is quiet;
+    // stops once the effect below, which owns `running`, is disposed with the panel
+    let tick = ExtSendTrigger::new();
+    let running = Arc::new(AtomicBool::new(false));
+    let ticking = Arc::downgrade(&running);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(1));
+        match ticking.upgrade() {
+            Some(running) if running.load(Ordering::Relaxed) => register_ext_trigger(tick),
+            Some(_) => {}
+            None => break,
+        }
+    });
+
+    create_effect(move |_| {
+        tick.track();
+        let mut any_running = false;
+        tasks.with(|tasks| {
+            for task in tasks.iter().filter(|task| task.status.get_untracked().is_running()) {
+                task.trigger.track();
+                task.duration.set(task.started.elapsed());
+                let code = task.session.exit_code();
+                if code.is_none() {
+                    any_running = true;
+                    continue;
+                }
+                let finished = TaskStatus::from_exit_code(code);
+                logging::log_line(
+                    "INFO",
+                    &format!(
+                        "Background launcher {} finished with {} after {}",
+                        task.name,
+                        finished.label(),
+                        format_duration(task.started.elapsed()),
+                    ),
+                );
+                task.status.set(finished);
+            }
+        });
+        running.store(any_running, Ordering::Relaxed);
+    });
+}
+
+/// Show the output of background run `task` in a new pane to the right of the focused
+/// pane, or focus the pane already showing it.
+#[cfg(target_os = "macos")]
+pub fn show_background_task(workspace: &WorkspaceTab, task: &BackgroundTask) {
+    let panes = workspace.terminal_panes.get_untracked();
+    let showing = panes.iter().find(|pane| {
+        pane.session
+            .with_untracked(|session| session.as_ref().is_some_and(|session| Arc::ptr_eq(session, &task.session)))
+    });
+    if let Some(pane) = showing {
+        pane.should_focus.set(true);
+        return;
+    }
+
+    let after = workspace
+        .focused_pane_id
+        .get_untracked()
+        .filter(|id| panes.iter().any(|p| p.id == *id))
+        .or_else(|| panes.last().map(|p| p.id));
+    let new_id = workspace.next_pane_id.get_untracked();
+    workspace.next_pane_id.set(new_id + 1);
+    let new_pane = TerminalPane {
+        id: new_id,
+        session: RwSignal::new(Some(Arc::clone(&task.session))),
+        trigger: ExtSendTrigger::new(),
+        flex_ratio: RwSignal::new(1.0),
+        title: RwSignal::new(task.name.clone()),
+        should_focus: RwSignal::new(true),
+        title_buffer: Arc::new(Mutex::new(None)),
+        process_title_buffer: Arc::new(Mutex::new(None)),
+        pinned_title: RwSignal::new(Some(task.name.clone())),
+        profile: None,
+        restored_output: Arc::new(Mutex::new(None)),
+        tmux: None,
+    };
+    if let Ok(mut viewer) = task.viewer.lock() {
+        *viewer = Some(new_pane.trigger);
+    }
+    logging::log_line("INFO", &format!("Showing background launcher {} in pane {new_id}", task.name));
+    insert_pane(workspace, after, new_pane);
+}
+
+/// A launcher to run in a new workspace tab on `root`; the app opens the tab.
+#[cfg(target_os = "macos")]
+pub struct TabLaunch {
+    pub root: PathBuf,
+    pub profile: Option<String>,
+    /// Typed into the first pane once its shell starts
+    pub input: String,
+}
+
+#[cfg(target_os = "macos")]
+type TabLaunchHandler = Arc<dyn Fn(TabLaunch) + Send + Sync>;
+
+#[cfg(target_os = "macos")]
+static TAB_LAUNCH_HANDLER: OnceLock<Mutex<Option<TabLaunchHandler>>> = OnceLock::new();
+
+/// Register the handler opening tabs for `new_tab` launchers.
+#[cfg(target_os = "macos")]
+pub fn set_tab_launch_handler(handler: TabLaunchHandler) {
+    let mutex = TAB_LAUNCH_HANDLER.get_or_init(|| Mutex::new(None));
+    if let Ok(mut guard) = mutex.lock() {
+        *guard = Some(handler);
+    }
+}
+
+/// Run `launcher` against pane `target` of `workspace` (the focused pane when `None`).
+/// Returns the pane the command was written to; `None` for `new_tab` launchers (the tab
+/// opens once the app gets to it) and `background` ones.
+#[cfg(target_os = "macos")]
+pub fn run_launcher(
+    launcher: &Launcher,
+    workspace: &WorkspaceTab,
+    target: Option<usize>,
+    inputs: &HashMap<String, String>,
+    settings: RwSignal<Settings>,
+) -> std::io::Result<Option<TerminalPane>> {
+    logging::log_line("INFO", &format!("Executing launcher: {}", launcher.name));
+    let panes = workspace.terminal_panes.get_untracked();
+    let target = target.or_else(|| workspace.focused_pane_id.get_untracked());
+    // Fall back to the first pane
+    let source = target
+        .and_then(|id| panes.iter().find(|p| p.id == id))
+        .or_else(|| panes.first());
+
+    // Resolve placeholders before touching any pane, so a missing value types nothing
+    let command = launcher_command(&launcher.command, workspace, source, inputs)?;
+    let root = workspace.root.get_untracked();
+    let input = launcher.terminal_input(&command, &root);
+
+    let pane = match launcher.run_in {
+        LauncherRunIn::Current => {
+            let pane = source
+                .cloned()
+                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "workspace has no panes"))?;
+            pane.should_focus.set(true);
+            pane
+        }
+        LauncherRunIn::NewSplit => {
+            // Split right from the target pane (or the last pane)
+            let after = target
+                .filter(|id| panes.iter().any(|p| p.id == *id))
+                .or_else(|| panes.last().map(|p| p.id));
+            split_pane(workspace, after, launcher.profile.clone(), settings)?
+        }
+        LauncherRunIn::NewTab => {
+            let handler = TAB_LAUNCH_HANDLER
+                .get()
+                .and_then(|mutex| mutex.lock().ok().and_then(|guard| guard.clone()))
+                .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Unsupported, "tabs cannot be opened"))?;
+            handler(TabLaunch {
+                root,
+                profile: launcher.profile.clone(),
+                input,
+            });
+            return Ok(None);
+        }
+        LauncherRunIn::Background => {
+            start_background_task(workspace, launcher, &command, settings)?;
+            return Ok(None);
+        }
+    };
+
+    let session = match pane.session.get_untracked() {
+        Some(session) => session,
+        None => start_pane_session(workspace, &pane, settings)?,
+    };
+    session.write(input.as_bytes())?;
+    Ok(Some(pane))
+}
+
+/// `command` with its placeholders filled in from the workspace, the `source` pane and
+/// the answers to its `${input:…}` prompts.
+#[cfg(target_os = "macos")]
+fn launcher_command(
+    command: &str,
+    workspace: &WorkspaceTab,
+    source: Option<&TerminalPane>,
+    inputs: &HashMap<String, String>,
+) -> std::io::Result<String> {
+    let invalid = |err: VarError| std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string());
+    let template = CommandTemplate::parse(command).map_err(invalid)?;
+
+    let root = workspace.root.get_untracked();
+    let session = source.and_then(|pane| pane.session.get_untracked());
+    let file = workspace.active_editor_tab.get_untracked().and_then(|id| {
+        workspace
+            .editor_tabs
+            .with_untracked(|tabs| tabs.iter().find(|tab| tab.id == id).map(|tab| tab.path.clone()))
+    });
+    let path_text = |path: &std::path::Path| path.to_string_lossy().into_owned();
+
+    template
+        .expand(|placeholder| match placeholder {
+            Placeholder::WorkspaceRoot => Some(path_text(&root)).filter(|root| !root.is_empty()),
+            Placeholder::PaneCwd => session
+                .as_ref()
+                .and_then(|session| session.foreground_pid())
+                .and_then(process_cwd)
+                .map(|cwd| path_text(&cwd)),
+            Placeholder::File => file.as_deref().map(path_text),
+            Placeholder::RelativeFile => file
+                .as_deref()
+                .and_then(|file| file.strip_prefix(&root).ok())
+                .map(path_text),
+            Placeholder::SelectedText => session
+                .as_ref()
+                .and_then(|session| session.with_term(|term| term.selection_to_string())),
+            Placeholder::GitBranch => git_branch(&root),
+            Placeholder::Env(name) => std::env::var(name).ok(),
+            Placeholder::Input(prompt) => inputs.get(prompt).cloned(),
+        })
+        .map_err(invalid)
+}
+
+/// A clicked launcher waiting for confirmation or for answers to its `${input:…}` prompts.
+#[cfg(target_os = "macos")]
+#[derive(Clone)]
+struct PendingLaunch {
+    launcher: Launcher,
+    confirmed: bool,
+    prompts: Vec<String>,
+    inputs: HashMap<String, String>,
+}
+
+#[cfg(target_os = "macos")]
+#[derive(Clone, PartialEq)]
+enum LaunchStep {
+    Confirm,
+    Prompt(String),
+}
+
+#[cfg(target_os = "macos")]
+impl PendingLaunch {
+    /// What the user is asked next; `None` once the launcher can run.
+    fn step(&self) -> Option<LaunchStep> {
+        if !self.confirmed {
+            return Some(LaunchStep::Confirm);
+        }
+        self.prompts.get(self.inputs.len()).cloned().map(LaunchStep::Prompt)
+    }
+}
+
 #[cfg(target_os = "macos")]
 fn control_center_header(
     launchers: RwSignal<Vec<Launcher>>,
-    terminal_panes: RwSignal<Vec<TerminalPane>>,
-    next_pane_id: RwSignal<usize>,
-    focused_pane_id: RwSignal<Option<usize>>,
-    workspace_root: RwSignal<PathBuf>,
+    config_problems: RwSignal<Vec<(PathBuf, Diagnostic)>>,
+    workspace: WorkspaceTab,
+    settings: RwSignal<Settings>,
+    scheme_error: RwSignal<Option<String>>,
     theme: UiTheme,
 ) -> impl IntoView {
     // Label
@@ -1556,6 +2791,8 @@ fn control_center_header(
             .margin_right(12.0)
     });
 
+    // The launcher editor opens below the header
+    let editor_open = RwSignal::new(false);
     let config_button = {
         container(label(|| "Settings").style(move |s| {
             s.font_size(11.0).color(theme.text_soft)
@@ -1570,167 +2807,361 @@ fn control_center_header(
                 .hover(|s| s.background(theme.accent.with_alpha(0.2)))
                 .cursor(floem::style::CursorStyle::Pointer)
         })
-        .on_click_stop(move |_| {
-            let path = crate::services::config::launchers_file_path();
-            if !path.exists() {
-                let _ = std::fs::write(&path, "[]");
-            }
-            #[cfg(target_os = "macos")]
-            {
-                let _ = std::process::Command::new("open").arg("-R").arg(path).spawn();
+        .on_click_stop(move |_| editor_open.update(|open| *open = !*open))
+    };
+
+    // A launcher with `confirm` asks before it runs, and one with `${input:…}`
+    // placeholders asks for them one at a time; a failed launcher shows its error until
+    // the next one succeeds.
+    let pending = RwSignal::new(None::<PendingLaunch>);
+    let launch_error = RwSignal::new(None::<String>);
+    let launch = move |launcher: &Launcher, workspace: &WorkspaceTab, inputs: &HashMap<String, String>| {
+        match run_launcher(launcher, workspace, None, inputs, settings) {
+            Ok(_) => launch_error.set(None),
+            Err(err) => {
+                logging::log_line("ERROR", &format!("Launcher {} failed: {err}", launcher.name));
+                launch_error.set(Some(format!("{}: {err}", launcher.name)));
             }
-        })
+        }
+    };
+    // Run the launcher once nothing is left to ask, otherwise show the next question
+    let advance = move |state: PendingLaunch, workspace: &WorkspaceTab| {
+        if state.step().is_some() {
+            pending.set(Some(state));
+        } else {
+            pending.set(None);
+            launch(&state.launcher, workspace, &state.inputs);
+        }
     };
 
-    // Launcher buttons
-    let launchers_list = dyn_stack(
-        move || launchers.get(),
-        |launcher| launcher.name.clone(),
-        move |launcher| {
-            let cmd = launcher.command.clone();
-            let run_in = launcher.run_in.clone();
-            let name = launcher.name.clone();
-            let name_label = name.clone();
-            let terminal_panes = terminal_panes;
-            let next_pane_id = next_pane_id;
-            let focused_pane_id = focused_pane_id;
-            let workspace_root = workspace_root;
-            
-            container(label(move || name_label.clone()).style(move |s| {
-                s.font_size(11.0).color(theme.text)
-            }))
-            .style(move |s| {
-                s.padding_horiz(8.0)
-                    .padding_vert(4.0)
-                    .border(1.0)
-                    .border_color(theme.border_subtle)
-                    .border_radius(4.0)
-                    .background(theme.element_bg)
-                    .hover(|s| s.background(theme.accent.with_alpha(0.2)))
-                    .cursor(floem::style::CursorStyle::Pointer)
-            })
+    let button_style = move |s: floem::style::Style| {
+        s.padding_horiz(8.0)
+            .padding_vert(4.0)
+            .border(1.0)
+            .border_color(theme.border_subtle)
+            .border_radius(4.0)
+            .background(theme.element_bg)
+            .hover(|s| s.background(theme.accent.with_alpha(0.2)))
+            .cursor(floem::style::CursorStyle::Pointer)
+    };
+    let list_workspace = workspace.clone();
+    let banner_workspace = workspace.clone();
+    let editor_workspace = workspace.clone();
+    let launcher_button = move |launcher: Launcher| {
+        let workspace = list_workspace.clone();
+        let text = launcher.label();
+        // Launchers from the workspace's `.tide/launchers.json` carry a tag
+        let from_project = launcher.from_project;
+        h_stack((
+            label(move || text.clone()).style(move |s| s.font_size(11.0).color(theme.text)),
+            label(|| "project").style(move |s| {
+                s.display(if from_project { floem::style::Display::Flex } else { floem::style::Display::None })
+                    .font_size(9.0)
+                    .padding_horiz(4.0)
+                    .border_radius(3.0)
+                    .color(theme.text_muted)
+                    .background(theme.accent.with_alpha(0.15))
+            }),
+        ))
+            .style(move |s| button_style(s).items_center().col_gap(4.0))
             .on_click_stop(move |_| {
-                logging::log_line("INFO", &format!("Executing launcher: {}", name));
-                
-                match run_in {
-                    LauncherRunIn::Current => {
-                        // Find focused pane or fallback to first pane
-                        let pane_id_opt = focused_pane_id.get_untracked();
-                        let panes = terminal_panes.get_untracked();
-                        
-                        let target_pane = if let Some(id) = pane_id_opt {
-                            panes.iter().find(|p| p.id == id).cloned()
-                        } else {
-                            panes.first().cloned()
-                        };
-                        
-                        if let Some(pane) = target_pane {
-                            pane.should_focus.set(true);
-                            if let Some(session) = pane.session.get_untracked() {
-                                let cmd_str = cmd.clone();
-                                if let Err(err) = session.write(cmd_str.as_bytes()) {
-                                    logging::log_line("ERROR", &format!("Launcher write failed: {}", err));
-                                }
-                            }
-                        }
-                    },
-                    LauncherRunIn::NewSplit => {
-                        // Split right from currently focused pane (or last pane)
-                        let pane_id_opt = focused_pane_id.get_untracked();
-                        let panes = terminal_panes.get_untracked();
-                        
-                        let target_idx = if let Some(id) = pane_id_opt {
-                            panes.iter().position(|p| p.id == id).unwrap_or(panes.len().saturating_sub(1))
-                        } else {
-                            panes.len().saturating_sub(1)
-                        };
+                let prompts = match CommandTemplate::parse(&launcher.command) {
+                    Ok(template) => template.prompts(),
+                    Err(err) => {
+                        launch_error.set(Some(format!("{}: {err}", launcher.name)));
+                        return;
+                    }
+                };
+                let state = PendingLaunch {
+                    launcher: launcher.clone(),
+                    confirmed: !launcher.confirm,
+                    prompts,
+                    inputs: HashMap::new(),
+                };
+                advance(state, &workspace);
+            })
+    };
 
-                        let new_id = next_pane_id.get();
-                        next_pane_id.set(new_id + 1);
-
-                        let new_pane = TerminalPane {
-                            id: new_id,
-                            session: RwSignal::new(None),
-                            trigger: ExtSendTrigger::new(),
-                            flex_ratio: RwSignal::new(1.0),
-                            title: RwSignal::new("Terminal".to_string()),
-                            should_focus: RwSignal::new(true),
-                            title_buffer: Arc::new(Mutex::new(None)),
-                        };
-                        
-                        // Initialize session immediately so we can write to it
-                        let notify = {
-                            let trigger = new_pane.trigger.clone();
-                            Arc::new(move || {
-                                register_ext_trigger(trigger);
-                            })
-                        };
-                        
-                        let title_cb = {
-                            let buf = new_pane.title_buffer.clone();
-                            let trig = new_pane.trigger.clone();
-                            Arc::new(move |title: String| {
-                                if let Ok(mut guard) = buf.lock() {
-                                    *guard = Some(title);
-                                }
-                                register_ext_trigger(trig);
-                            })
-                        };
-                        
-                        match TerminalSession::new(&workspace_root.get_untracked(), notify, title_cb) {
-                            Ok(session) => {
-                                // Write command
-                                let cmd_str = cmd.clone();
-                                let _ = session.write(cmd_str.as_bytes());
-                                
-                                new_pane.session.set(Some(session));
-                            }
-                            Err(e) => logging::log_line("ERROR", &format!("Failed to init session for launcher: {}", e)),
-                        }
+    // Launcher buttons, grouped in the order their groups first appear
+    let launchers_list = dyn_stack(
+        move || {
+            let mut groups: Vec<(Option<String>, Vec<Launcher>)> = Vec::new();
+            for launcher in launchers.get() {
+                match groups.iter_mut().find(|(group, _)| *group == launcher.group) {
+                    Some((_, members)) => members.push(launcher),
+                    None => groups.push((launcher.group.clone(), vec![launcher])),
+                }
+            }
+            groups
+        },
+        // Rebuild a group whenever any of its launchers changes
+        |(group, members)| format!("{group:?}{members:?}"),
+        move |(group, members)| {
+            let group_label = group.unwrap_or_default();
+            let has_group = !group_label.is_empty();
+            h_stack((
+                label(move || group_label.clone()).style(move |s| {
+                    s.display(if has_group { floem::style::Display::Flex } else { floem::style::Display::None })
+                        .font_size(10.0)
+                        .color(theme.text_muted)
+                }),
+                h_stack_from_iter(members.into_iter().map(&launcher_button)).style(|s| s.col_gap(8.0)),
+            ))
+            .style(|s| s.items_center().col_gap(6.0))
+        }
+    ).style(move |s| {
+        let prompting = pending.with(|pending| pending.is_some());
+        s.display(if prompting { floem::style::Display::None } else { floem::style::Display::Flex })
+            .flex_row()
+            .col_gap(12.0)
+            .items_center()
+            .min_width(0.0)
+    });
 
-                        // Insert after target
-                        terminal_panes.update(|panes| {
-                             if target_idx < panes.len() {
-                                panes.insert(target_idx + 1, new_pane.clone());
-                             } else {
-                                panes.push(new_pane.clone());
-                             }
-                        });
-                        
-                        // Trigger layout update
-                         let triggers: Vec<_> = terminal_panes.get_untracked()
-                            .iter()
-                            .map(|p| p.trigger.clone())
-                            .collect();
-                        std::thread::spawn(move || {
-                            std::thread::sleep(std::time::Duration::from_millis(100));
-                            for trigger in triggers {
-                                register_ext_trigger(trigger);
+    // Question bar in place of the buttons. A prompt is answered with Enter; Escape (or
+    // Cancel) drops the launch.
+    let draft = RwSignal::new(String::new());
+    let prompt_bar = dyn_container(
+        move || pending.with(|pending| pending.as_ref().and_then(PendingLaunch::step)),
+        move |step| {
+            let workspace = workspace.clone();
+            match step {
+                None => empty().into_any(),
+                Some(LaunchStep::Confirm) => {
+                    let Some(state) = pending.get_untracked() else {
+                        return empty().into_any();
+                    };
+                    let question = if state.launcher.from_project {
+                        format!("Run {} from this project's .tide/launchers.json?", state.launcher.label())
+                    } else {
+                        format!("Run {}?", state.launcher.label())
+                    };
+                    let command = state.launcher.command.clone();
+                    h_stack((
+                        label(move || question.clone()).style(move |s| s.font_size(11.0).color(theme.text)),
+                        label(move || command.clone()).style(move |s| {
+                            s.font_size(11.0)
+                                .font_family("monospace".to_string())
+                                .color(theme.text_soft)
+                                .text_ellipsis()
+                                .min_width(0.0)
+                                .max_width(320.0)
+                        }),
+                        container(label(|| "Run").style(move |s| s.font_size(11.0).color(theme.text)))
+                            .style(button_style)
+                            .on_click_stop(move |_| {
+                                if let Some(mut state) = pending.get_untracked() {
+                                    state.confirmed = true;
+                                    advance(state, &workspace);
+                                }
+                            }),
+                        container(label(|| "Cancel").style(move |s| s.font_size(11.0).color(theme.text_soft)))
+                            .style(button_style)
+                            .on_click_stop(move |_| pending.set(None)),
+                    ))
+                    .style(|s| s.items_center().col_gap(6.0).min_width(0.0))
+                    .into_any()
+                }
+                Some(LaunchStep::Prompt(prompt)) => {
+                    draft.set(String::new());
+                    let input = text_input(draft)
+                        .style(move |s| {
+                            s.font_size(11.0)
+                                .width(240.0)
+                                .padding_horiz(4.0)
+                                .color(theme.text)
+                                .background(theme.element_bg)
+                        })
+                        .on_event(EventListener::KeyDown, move |event| {
+                            if let Event::KeyDown(key_event) = event {
+                                match key_event.key.logical_key {
+                                    Key::Named(NamedKey::Enter) => {
+                                        if let Some(mut state) = pending.get_untracked() {
+                                            if let Some(LaunchStep::Prompt(prompt)) = state.step() {
+                                                state.inputs.insert(prompt, draft.get_untracked());
+                                            }
+                                            advance(state, &workspace);
+                                        }
+                                        return EventPropagation::Stop;
+                                    }
+                                    Key::Named(NamedKey::Escape) => {
+                                        pending.set(None);
+                                        return EventPropagation::Stop;
+                                    }
+                                    _ => {}
+                                }
                             }
+                            EventPropagation::Continue
                         });
-                    }
+                    input.id().request_focus();
+                    h_stack((
+                        label(move || format!("{prompt}:")).style(move |s| s.font_size(11.0).color(theme.text_soft)),
+                        input,
+                    ))
+                    .style(|s| s.items_center().col_gap(6.0))
+                    .into_any()
+                }
+            }
+        },
+    );
+
+    // Launcher failures (missing placeholder values, pane errors)
+    let launch_error_label = label(move || launch_error.get().unwrap_or_default()).style(move |s| {
+        let visible = launch_error.with(|err| err.is_some());
+        s.display(if visible { floem::style::Display::Flex } else { floem::style::Display::None })
+            .font_size(11.0)
+            .color(Color::from_rgb8(235, 87, 87))
+            .text_ellipsis()
+            .min_width(0.0)
+            .flex_shrink(1.0)
+    });
+
+    // Color scheme load failure (the built-in palette is used meanwhile)
+    let scheme_error_label = label(move || scheme_error.get().unwrap_or_default()).style(move |s| {
+        let visible = scheme_error.with(|err| err.is_some());
+        s.display(if visible { floem::style::Display::Flex } else { floem::style::Display::None })
+            .font_size(11.0)
+            .color(Color::from_rgb8(235, 87, 87))
+            .text_ellipsis()
+            .min_width(0.0)
+            .flex_shrink(1.0)
+    });
+
+    // Problems in the config files: the first one opens its file in the editor, and
+    // dismissing hides them until they change
+    let dismissed = RwSignal::new(Vec::<(PathBuf, Diagnostic)>::new());
+    let problems_banner = h_stack((
+        label(move || {
+            config_problems.with(|problems| match problems.first() {
+                Some((path, diagnostic)) => {
+                    let more = match problems.len() {
+                        1 => String::new(),
+                        count => format!(" (+{} more)", count - 1),
+                    };
+                    format!("⚠ {}:{diagnostic}{more}", path.display())
                 }
+                None => String::new(),
             })
-        }
-    ).style(|s| s.flex_row().col_gap(8.0).items_center().min_width(0.0));
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .color(theme.text)
+                .text_ellipsis()
+                .min_width(0.0)
+                .flex_grow(1.0)
+                .cursor(floem::style::CursorStyle::Pointer)
+                .hover(|s| s.color(theme.accent))
+        })
+        .on_click_stop(move |_| {
+            if let Some((path, diagnostic)) = config_problems.with_untracked(|problems| problems.first().cloned()) {
+                banner_workspace.open_editor_file(path, true, Some((diagnostic.line, diagnostic.column)));
+            }
+        }),
+        label(|| "✕")
+            .style(move |s| {
+                s.font_size(11.0)
+                    .padding_horiz(4.0)
+                    .color(theme.text_muted)
+                    .cursor(floem::style::CursorStyle::Pointer)
+                    .hover(|s| s.color(theme.text))
+            })
+            .on_click_stop(move |_| dismissed.set(config_problems.get_untracked())),
+    ))
+    .style(move |s| {
+        let visible = config_problems.with(|problems| !problems.is_empty() && dismissed.with(|d| d != problems));
+        s.display(if visible { floem::style::Display::Flex } else { floem::style::Display::None })
+            .width_full()
+            .items_center()
+            .col_gap(8.0)
+            .padding_horiz(8.0)
+            .padding_vert(3.0)
+            .min_width(0.0)
+            .background(Color::from_rgb8(235, 170, 60).with_alpha(0.18))
+    });
 
-    h_stack((label_view, launchers_list, config_button))
-        .style(|s| s.width_full().items_center().height(28.0).padding_horiz(8.0).min_width(0.0).col_gap(8.0))
+    v_stack((
+        h_stack((label_view, launchers_list, prompt_bar, launch_error_label, scheme_error_label, config_button))
+            .style(|s| s.width_full().items_center().height(28.0).padding_horiz(8.0).min_width(0.0).col_gap(8.0)),
+        problems_banner,
+        launcher_editor(editor_open, editor_workspace, theme),
+    ))
+    .style(|s| s.width_full().min_width(0.0))
 }
 
 
 
 #[cfg(target_os = "macos")]
-fn pane_header(title: RwSignal<String>, theme: UiTheme) -> impl IntoView {
-    let title_label = label(move || title.get()).style(move |s| {
+fn pane_header(
+    title: RwSignal<String>,
+    pinned_title: RwSignal<Option<String>>,
+    editing: RwSignal<bool>,
+    theme: UiTheme,
+) -> impl IntoView {
+    let title_style = move |s: floem::style::Style| {
         s.font_size(11.0)
             .color(theme.text)
             .font_bold()
-            .text_ellipsis()
             .width(0.0)
             .min_width(0.0)
             .flex_grow(1.0)
-    });
+    };
+
+    // Double-click (or "Rename Pane…") edits the title; Enter pins it, an empty name
+    // unpins, Escape cancels.
+    let draft = RwSignal::new(String::new());
+    let commit = move || {
+        if !editing.get_untracked() {
+            return;
+        }
+        let name = draft.get_untracked().trim().to_string();
+        pinned_title.set((!name.is_empty()).then_some(name));
+        editing.set(false);
+    };
+    let title_view = dyn_container(
+        move || editing.get(),
+        move |is_editing| {
+            if is_editing {
+                draft.set(title.get_untracked());
+                let input = text_input(draft)
+                    .style(move |s| title_style(s).padding_horiz(4.0).background(theme.element_bg))
+                    .on_event(EventListener::KeyDown, move |event| {
+                        if let Event::KeyDown(key_event) = event {
+                            match key_event.key.logical_key {
+                                Key::Named(NamedKey::Enter) => {
+                                    commit();
+                                    return EventPropagation::Stop;
+                                }
+                                Key::Named(NamedKey::Escape) => {
+                                    editing.set(false);
+                                    return EventPropagation::Stop;
+                                }
+                                _ => {}
+                            }
+                        }
+                        EventPropagation::Continue
+                    })
+                    .on_event_cont(EventListener::FocusLost, move |_| commit());
+                input.id().request_focus();
+                input.into_any()
+            } else {
+                label(move || {
+                    let title = title.get();
+                    if pinned_title.with(|pinned| pinned.is_some()) {
+                        format!("📌 {title}")
+                    } else {
+                        title
+                    }
+                })
+                .style(move |s| title_style(s).text_ellipsis())
+                .on_event(EventListener::DoubleClick, move |_| {
+                    editing.set(true);
+                    EventPropagation::Stop
+                })
+                .into_any()
+            }
+        },
+    )
+    .style(|s| s.flex_grow(1.0).width(0.0).min_width(0.0));
     
     // Placeholder buttons style
     let btn_style = move |s: floem::style::Style| {
@@ -1753,7 +3184,7 @@ fn pane_header(title: RwSignal<String>, theme: UiTheme) -> impl IntoView {
         .on_click_stop(|_| { logging::log_line("INFO", "Close Pane clicked (placeholder)"); });
 
     h_stack((
-        title_label,
+        title_view,
         copy_path,
         copy_output,
         close
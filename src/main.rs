@@ -1,4 +1,5 @@
 mod app;
+mod cli;
 mod components;
 mod logging;
 mod model;
@@ -6,7 +7,30 @@ mod services;
 mod theme;
 
 fn main() {
+    let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let args = match cli::parse_args(std::env::args().skip(1), &cwd) {
+        Ok(cli::CliAction::Run(args)) => args,
+        Ok(cli::CliAction::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(cli::CliAction::Version) => {
+            println!("tide {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(err) => {
+            eprintln!("tide: {err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     logging::init();
+    if args.forwardable() && cli::forward_to_running_instance(&args) {
+        return;
+    }
+    if let Some(path) = &args.state_file {
+        services::state::set_state_file(path.clone());
+    }
     let window_config = if cfg!(target_os = "macos") {
         // Debug toggles (no library patch needed):
         // - Default: standard titlebar (no blur during zoom animation)
@@ -50,9 +74,18 @@ fn main() {
         floem::window::WindowConfig::default()
     };
 
-    let initial_state = services::load_state();
+    let (initial_state, state_problems) = if args.no_restore {
+        services::state::disable_saving();
+        (services::AppState { workspaces: Vec::new(), ..services::AppState::default() }, Vec::new())
+    } else {
+        services::load_state()
+    };
+    let targets = args.targets;
 
     floem::Application::new_with_config(floem::AppConfig::default().exit_on_close(true))
-        .window(move |_| app::app_view(initial_state.clone()), Some(window_config))
+        .window(
+            move |_| app::app_view(initial_state.clone(), state_problems.clone(), targets.clone()),
+            Some(window_config),
+        )
         .run();
 }
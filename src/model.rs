@@ -1,8 +1,9 @@
-use crate::services::TerminalSession;
+use crate::services::{TaskStatus, TerminalSession};
 use floem::ext_event::ExtSendTrigger;
 use floem::reactive::RwSignal;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// A single terminal pane with its own session
 #[derive(Clone)]
@@ -16,8 +17,45 @@ pub struct TerminalPane {
     pub title: RwSignal<String>,
     /// Signal to programmatically request focus
     pub should_focus: RwSignal<bool>,
-    /// Buffer for cross-thread title updates
+    /// Buffer for cross-thread OSC title updates (empty = title reset)
     pub title_buffer: Arc<Mutex<Option<String>>>,
+    /// Buffer for cross-thread foreground process title updates (empty = shell in foreground)
+    pub process_title_buffer: Arc<Mutex<Option<String>>>,
+    /// Title pinned by the user; takes priority over OSC and process titles
+    pub pinned_title: RwSignal<Option<String>>,
+    /// Shell profile this pane starts with (`None` = the workspace default)
+    pub profile: Option<String>,
+    /// Saved output from the previous run, replayed when the session starts
+    pub restored_output: Arc<Mutex<Option<Vec<u8>>>>,
+    /// Set when the pane shows a pane of a tmux server instead of its own shell
+    pub tmux: Option<TmuxPaneRef>,
+}
+
+/// A tmux pane reached through the control-mode gateway `gateway` (see `services::tmux`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TmuxPaneRef {
+    pub gateway: usize,
+    pub pane: u32,
+}
+
+/// A run of a `background` launcher. Its output goes to a session that no pane shows
+/// until the run is opened from the Tasks panel.
+#[derive(Clone)]
+pub struct BackgroundTask {
+    pub id: usize,
+    /// Name of the launcher
+    pub name: String,
+    /// Command line with its placeholders filled in
+    pub command: String,
+    pub session: Arc<TerminalSession>,
+    /// Fired from the session's threads on output and on exit
+    pub trigger: ExtSendTrigger,
+    /// Trigger of the pane showing the output, if one does
+    pub viewer: Arc<Mutex<Option<ExtSendTrigger>>>,
+    pub started: Instant,
+    pub status: RwSignal<TaskStatus>,
+    /// Time since `started`; final once the command exits
+    pub duration: RwSignal<Duration>,
 }
 
 #[derive(Clone)]
@@ -40,6 +78,76 @@ pub struct WorkspaceTab {
     pub next_pane_id: RwSignal<usize>,
     /// ID counter for creating new editor tabs
     pub next_editor_tab_id: RwSignal<usize>,
+    /// Runs of `background` launchers, oldest first
+    pub background_tasks: RwSignal<Vec<BackgroundTask>>,
+    /// ID counter for background runs
+    pub next_task_id: RwSignal<usize>,
+    /// `(gateway, window)` when the tab shows a tmux window
+    pub tmux_window: Option<(usize, u32)>,
+}
+
+impl WorkspaceTab {
+    /// Show `path` in an editor tab: an existing tab is activated, otherwise the file
+    /// replaces the unpinned preview tab (or opens a new tab when `pin` is set).
+    /// `position` (1-based line and column) scrolls the editor to that spot.
+    pub fn open_editor_file(&self, path: PathBuf, pin: bool, position: Option<(usize, usize)>) {
+        let editor_tabs = self.editor_tabs;
+        let active_editor_tab_id = self.active_editor_tab;
+
+        // 1. Check if already open
+        let mut tabs = editor_tabs.get_untracked();
+        if let Some(existing_idx) = tabs.iter().position(|t| t.path == path) {
+            let id = tabs[existing_idx].id;
+            if pin {
+                tabs[existing_idx].is_pinned.set(true);
+            }
+            if position.is_some() {
+                tabs[existing_idx].position.set(position);
+            }
+            active_editor_tab_id.set(Some(id));
+            return;
+        }
+
+        // 2. Read file content
+        match crate::services::read_file_preview(&path) {
+            Ok(content) => {
+                let name = path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+
+                let new_id = self.next_editor_tab_id.get_untracked();
+                self.next_editor_tab_id.set(new_id + 1);
+
+                let new_tab = EditorTab {
+                    id: new_id,
+                    path,
+                    name,
+                    is_pinned: RwSignal::new(pin),
+                    content,
+                    position: RwSignal::new(position),
+                };
+
+                // 3. Find temporary tab to replace (only if single click and not forcing pin)
+                if !pin {
+                    if let Some(temp_idx) = tabs.iter().position(|t| !t.is_pinned.get_untracked()) {
+                        // Replace temp tab
+                        tabs[temp_idx] = new_tab;
+                        editor_tabs.set(tabs);
+                        active_editor_tab_id.set(Some(new_id));
+                        return;
+                    }
+                }
+
+                // Otherwise append (pinned or no temp tab found)
+                tabs.push(new_tab);
+                editor_tabs.set(tabs);
+                active_editor_tab_id.set(Some(new_id));
+            }
+            Err(e) => {
+                crate::logging::log_line("ERROR", &format!("Failed to read file {}: {}", path.display(), e));
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -49,6 +157,8 @@ pub struct EditorTab {
     pub name: String,
     pub is_pinned: RwSignal<bool>,
     pub content: String,
+    /// 1-based line and column to scroll to (e.g. from `tide FILE:LINE`)
+    pub position: RwSignal<Option<(usize, usize)>>,
 }
 
 #[derive(Clone)]
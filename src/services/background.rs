@@ -0,0 +1,92 @@
+//! Runs of `background` launchers. Each run keeps its output in a terminal session that
+//! no pane shows until it is opened from the Tasks panel.
+
+use std::time::Duration;
+
+/// Lines of output a run keeps, more than a pane: the first error of a long build is
+/// usually what matters.
+pub const BACKGROUND_SCROLLBACK: usize = 10_000;
+
+/// State of a run, from the exit code of its shell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed(u32),
+}
+
+impl TaskStatus {
+    /// Status of a run whose shell exited with `code` (`None` while it runs).
+    pub fn from_exit_code(code: Option<u32>) -> Self {
+        match code {
+            None => Self::Running,
+            Some(0) => Self::Succeeded,
+            Some(code) => Self::Failed(code),
+        }
+    }
+
+    pub fn is_running(self) -> bool {
+        self == Self::Running
+    }
+
+    /// Marker shown before the run in the Tasks panel.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Running => "●",
+            Self::Succeeded => "✓",
+            Self::Failed(_) => "✗",
+        }
+    }
+
+    /// `running`, or the exit code once the run is over.
+    pub fn label(self) -> String {
+        match self {
+            Self::Running => "running".to_string(),
+            Self::Succeeded => "exit 0".to_string(),
+            Self::Failed(code) => format!("exit {code}"),
+        }
+    }
+}
+
+/// `duration` as the Tasks panel shows it: `850ms`, `42s`, `3m 05s` or `1h 02m`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs == 0 {
+        format!("{}ms", duration.as_millis())
+    } else if secs < 60 {
+        format!("{secs}s")
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_follows_the_exit_code() {
+        assert_eq!(TaskStatus::from_exit_code(None), TaskStatus::Running);
+        assert_eq!(TaskStatus::from_exit_code(Some(0)), TaskStatus::Succeeded);
+        assert_eq!(TaskStatus::from_exit_code(Some(101)), TaskStatus::Failed(101));
+
+        let labels: Vec<_> = [None, Some(0), Some(2)]
+            .into_iter()
+            .map(|code| {
+                let status = TaskStatus::from_exit_code(code);
+                format!("{} {}", status.symbol(), status.label())
+            })
+            .collect();
+        assert_eq!(labels, ["● running", "✓ exit 0", "✗ exit 2"]);
+    }
+
+    #[test]
+    fn durations_are_short() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(42_300)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1h 02m");
+    }
+}
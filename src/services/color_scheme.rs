@@ -0,0 +1,473 @@
+use alacritty_terminal::vte::ansi::Rgb;
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::services::config::themes_dir;
+use crate::theme::TerminalPalette;
+
+const ANSI_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// Supported color scheme file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSchemeFormat {
+    /// Alacritty `colors` table in TOML (`alacritty.toml` or a theme file)
+    AlacrittyToml,
+    /// Alacritty `colors` mapping in the legacy YAML config format
+    AlacrittyYaml,
+    /// iTerm2 `.itermcolors` property list
+    Iterm2,
+    /// base16 scheme YAML (`base00` .. `base0F`)
+    Base16,
+}
+
+/// A color scheme file found in the themes directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorSchemeEntry {
+    /// File name, used as the scheme identifier in settings
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ColorSchemeErrorKind {
+    Io(std::io::Error),
+    UnknownFormat,
+    Malformed(String),
+    MissingColor(String),
+    InvalidColor { key: String, value: String },
+}
+
+/// Error loading a color scheme, always tied to the offending file.
+#[derive(Debug)]
+pub struct ColorSchemeError {
+    pub path: PathBuf,
+    pub kind: ColorSchemeErrorKind,
+}
+
+impl fmt::Display for ColorSchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        match &self.kind {
+            ColorSchemeErrorKind::Io(err) => write!(f, "Failed to read color scheme {path}: {err}"),
+            ColorSchemeErrorKind::UnknownFormat => write!(
+                f,
+                "Unrecognized color scheme format for {path} (expected .toml, .yaml/.yml or .itermcolors)"
+            ),
+            ColorSchemeErrorKind::Malformed(message) => {
+                write!(f, "Malformed color scheme {path}: {message}")
+            }
+            ColorSchemeErrorKind::MissingColor(key) => {
+                write!(f, "Color scheme {path} is missing required color `{key}`")
+            }
+            ColorSchemeErrorKind::InvalidColor { key, value } => write!(
+                f,
+                "Color scheme {path} has invalid color `{key}`: {value:?} (expected #rrggbb)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ColorSchemeError {}
+
+/// Guess the scheme format from the file extension and, for YAML, its content.
+pub fn detect_format(path: &Path, content: &str) -> Option<ColorSchemeFormat> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "toml" => Some(ColorSchemeFormat::AlacrittyToml),
+        "itermcolors" => Some(ColorSchemeFormat::Iterm2),
+        "yaml" | "yml" => {
+            if content.contains("base00") {
+                Some(ColorSchemeFormat::Base16)
+            } else {
+                Some(ColorSchemeFormat::AlacrittyYaml)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// List color scheme files in `~/.config/tide/themes`, sorted by name.
+pub fn list_color_schemes() -> Vec<ColorSchemeEntry> {
+    let Ok(entries) = fs::read_dir(themes_dir()) else {
+        return Vec::new();
+    };
+
+    let mut schemes: Vec<ColorSchemeEntry> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && detect_format(path, "").is_some())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            Some(ColorSchemeEntry { name, path })
+        })
+        .collect();
+    schemes.sort_by_key(|scheme| scheme.name.to_lowercase());
+    schemes
+}
+
+/// Load a scheme by name from the themes directory.
+pub fn load_named_color_scheme(name: &str) -> Result<TerminalPalette, ColorSchemeError> {
+    load_color_scheme(&themes_dir().join(name))
+}
+
+/// Load and parse a color scheme file.
+pub fn load_color_scheme(path: &Path) -> Result<TerminalPalette, ColorSchemeError> {
+    let error = |kind| ColorSchemeError { path: path.to_path_buf(), kind };
+
+    let bytes = fs::read(path).map_err(|err| error(ColorSchemeErrorKind::Io(err)))?;
+    let content = String::from_utf8_lossy(&bytes);
+    let format = detect_format(path, &content).ok_or_else(|| error(ColorSchemeErrorKind::UnknownFormat))?;
+
+    parse_color_scheme(&bytes, format).map_err(error)
+}
+
+/// Parse scheme file contents in the given format.
+pub fn parse_color_scheme(
+    bytes: &[u8],
+    format: ColorSchemeFormat,
+) -> Result<TerminalPalette, ColorSchemeErrorKind> {
+    match format {
+        ColorSchemeFormat::AlacrittyToml => {
+            let content = std::str::from_utf8(bytes)
+                .map_err(|err| ColorSchemeErrorKind::Malformed(err.to_string()))?;
+            let file: AlacrittyFile = toml::from_str(content)
+                .map_err(|err| ColorSchemeErrorKind::Malformed(err.to_string()))?;
+            alacritty_palette(file.colors)
+        }
+        ColorSchemeFormat::AlacrittyYaml => {
+            let file: AlacrittyFile = serde_norway::from_slice(bytes)
+                .map_err(|err| ColorSchemeErrorKind::Malformed(err.to_string()))?;
+            alacritty_palette(file.colors)
+        }
+        ColorSchemeFormat::Iterm2 => iterm2_palette(bytes),
+        ColorSchemeFormat::Base16 => base16_palette(bytes),
+    }
+}
+
+/// Parse `#rrggbb`, `0xrrggbb` or bare `rrggbb`.
+fn parse_hex_color(key: &str, value: &str) -> Result<Rgb, ColorSchemeErrorKind> {
+    let invalid = || ColorSchemeErrorKind::InvalidColor {
+        key: key.to_string(),
+        value: value.to_string(),
+    };
+
+    let trimmed = value.trim();
+    let hex = trimmed
+        .strip_prefix('#')
+        .or_else(|| trimmed.strip_prefix("0x"))
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    Ok(Rgb { r: channel(0)?, g: channel(2)?, b: channel(4)? })
+}
+
+fn required(key: &str, value: Option<&String>) -> Result<Rgb, ColorSchemeErrorKind> {
+    match value {
+        Some(value) => parse_hex_color(key, value),
+        None => Err(ColorSchemeErrorKind::MissingColor(key.to_string())),
+    }
+}
+
+/// Optional colors also accept Alacritty's `CellForeground`/`CellBackground` keywords,
+/// which mean "no override" here.
+fn optional(key: &str, value: Option<&String>) -> Result<Option<Rgb>, ColorSchemeErrorKind> {
+    match value {
+        Some(value) if value.starts_with("Cell") => Ok(None),
+        Some(value) => parse_hex_color(key, value).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Derive dim colors the same way Alacritty does when none are configured.
+fn dim_of(colors: &[Rgb; 8]) -> [Rgb; 8] {
+    colors.map(|c| Rgb {
+        r: (c.r as f32 * 0.66) as u8,
+        g: (c.g as f32 * 0.66) as u8,
+        b: (c.b as f32 * 0.66) as u8,
+    })
+}
+
+#[derive(Deserialize)]
+struct AlacrittyFile {
+    colors: AlacrittyColors,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyColors {
+    primary: AlacrittyPrimary,
+    #[serde(default)]
+    cursor: AlacrittyPair,
+    #[serde(default)]
+    selection: AlacrittyPair,
+    normal: AlacrittyAnsi,
+    bright: AlacrittyAnsi,
+    #[serde(default)]
+    dim: Option<AlacrittyAnsi>,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyPrimary {
+    background: Option<String>,
+    foreground: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct AlacrittyPair {
+    text: Option<String>,
+    #[serde(alias = "background")]
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AlacrittyAnsi {
+    black: Option<String>,
+    red: Option<String>,
+    green: Option<String>,
+    yellow: Option<String>,
+    blue: Option<String>,
+    magenta: Option<String>,
+    cyan: Option<String>,
+    white: Option<String>,
+}
+
+impl AlacrittyAnsi {
+    fn colors(&self, section: &str) -> Result<[Rgb; 8], ColorSchemeErrorKind> {
+        let values = [
+            &self.black, &self.red, &self.green, &self.yellow,
+            &self.blue, &self.magenta, &self.cyan, &self.white,
+        ];
+        let mut colors = [Rgb::default(); 8];
+        for (i, value) in values.iter().enumerate() {
+            colors[i] = required(&format!("colors.{section}.{}", ANSI_NAMES[i]), value.as_ref())?;
+        }
+        Ok(colors)
+    }
+}
+
+fn alacritty_palette(colors: AlacrittyColors) -> Result<TerminalPalette, ColorSchemeErrorKind> {
+    let normal = colors.normal.colors("normal")?;
+    let bright = colors.bright.colors("bright")?;
+    let dim = match &colors.dim {
+        Some(dim) => dim.colors("dim")?,
+        None => dim_of(&normal),
+    };
+
+    Ok(TerminalPalette {
+        primary_background: required("colors.primary.background", colors.primary.background.as_ref())?,
+        primary_foreground: required("colors.primary.foreground", colors.primary.foreground.as_ref())?,
+        normal,
+        bright,
+        dim,
+        cursor: optional("colors.cursor.cursor", colors.cursor.cursor.as_ref())?,
+        cursor_text: optional("colors.cursor.text", colors.cursor.text.as_ref())?,
+        selection_background: optional("colors.selection.background", colors.selection.cursor.as_ref())?,
+        selection_foreground: optional("colors.selection.text", colors.selection.text.as_ref())?,
+    })
+}
+
+fn iterm2_palette(bytes: &[u8]) -> Result<TerminalPalette, ColorSchemeErrorKind> {
+    let value = plist::Value::from_reader(Cursor::new(bytes))
+        .map_err(|err| ColorSchemeErrorKind::Malformed(err.to_string()))?;
+    let dict = value
+        .as_dictionary()
+        .ok_or_else(|| ColorSchemeErrorKind::Malformed("top-level value is not a dictionary".to_string()))?;
+
+    let color = |key: &str| -> Result<Option<Rgb>, ColorSchemeErrorKind> {
+        let Some(entry) = dict.get(key) else { return Ok(None) };
+        let invalid = || ColorSchemeErrorKind::InvalidColor {
+            key: key.to_string(),
+            value: format!("{entry:?}"),
+        };
+        let components = entry.as_dictionary().ok_or_else(invalid)?;
+        let channel = |name: &str| -> Result<u8, ColorSchemeErrorKind> {
+            let value = components.get(name).and_then(|v| v.as_real()).ok_or_else(invalid)?;
+            if !(0.0..=1.0).contains(&value) {
+                return Err(invalid());
+            }
+            Ok((value * 255.0).round() as u8)
+        };
+        Ok(Some(Rgb {
+            r: channel("Red Component")?,
+            g: channel("Green Component")?,
+            b: channel("Blue Component")?,
+        }))
+    };
+    let required_color = |key: &str| {
+        color(key)?.ok_or_else(|| ColorSchemeErrorKind::MissingColor(key.to_string()))
+    };
+
+    let mut normal = [Rgb::default(); 8];
+    let mut bright = [Rgb::default(); 8];
+    for i in 0..8 {
+        normal[i] = required_color(&format!("Ansi {i} Color"))?;
+        bright[i] = required_color(&format!("Ansi {} Color", i + 8))?;
+    }
+
+    Ok(TerminalPalette {
+        primary_background: required_color("Background Color")?,
+        primary_foreground: required_color("Foreground Color")?,
+        normal,
+        bright,
+        dim: dim_of(&normal),
+        cursor: color("Cursor Color")?,
+        cursor_text: color("Cursor Text Color")?,
+        selection_background: color("Selection Color")?,
+        selection_foreground: color("Selected Text Color")?,
+    })
+}
+
+fn base16_palette(bytes: &[u8]) -> Result<TerminalPalette, ColorSchemeErrorKind> {
+    let value: serde_norway::Value = serde_norway::from_slice(bytes)
+        .map_err(|err| ColorSchemeErrorKind::Malformed(err.to_string()))?;
+    // Newer tinted-theming schemes nest the colors under `palette`.
+    let colors = value.get("palette").unwrap_or(&value);
+
+    let base = |n: usize| -> Result<Rgb, ColorSchemeErrorKind> {
+        let key = format!("base{n:02X}");
+        let raw = colors
+            .get(key.as_str())
+            .or_else(|| colors.get(key.to_lowercase().as_str()));
+        match raw {
+            Some(serde_norway::Value::String(s)) => parse_hex_color(&key, s),
+            Some(other) => Err(ColorSchemeErrorKind::InvalidColor { key, value: format!("{other:?}") }),
+            None => Err(ColorSchemeErrorKind::MissingColor(key)),
+        }
+    };
+
+    // Standard base16 terminal mapping (as used by base16-shell).
+    let normal = [base(0x00)?, base(0x08)?, base(0x0B)?, base(0x0A)?, base(0x0D)?, base(0x0E)?, base(0x0C)?, base(0x05)?];
+    let bright = [base(0x03)?, base(0x08)?, base(0x0B)?, base(0x0A)?, base(0x0D)?, base(0x0E)?, base(0x0C)?, base(0x07)?];
+
+    Ok(TerminalPalette {
+        primary_background: base(0x00)?,
+        primary_foreground: base(0x05)?,
+        normal,
+        bright,
+        dim: dim_of(&normal),
+        cursor: Some(base(0x05)?),
+        cursor_text: Some(base(0x00)?),
+        selection_background: Some(base(0x02)?),
+        selection_foreground: Some(base(0x05)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALACRITTY_TOML: &str = r##"
+[colors.primary]
+background = "#1d1f21"
+foreground = "#c5c8c6"
+
+[colors.cursor]
+text = "CellBackground"
+cursor = "#ffcc00"
+
+[colors.selection]
+text = "#000000"
+background = "#ffffff"
+
+[colors.normal]
+black = "#000000"
+red = "#cc6666"
+green = "#b5bd68"
+yellow = "#f0c674"
+blue = "#81a2be"
+magenta = "#b294bb"
+cyan = "#8abeb7"
+white = "#ffffff"
+
+[colors.bright]
+black = "0x666666"
+red = "#d54e53"
+green = "#b9ca4a"
+yellow = "#e7c547"
+blue = "#7aa6da"
+magenta = "#c397d8"
+cyan = "#70c0b1"
+white = "#eaeaea"
+"##;
+
+    #[test]
+    fn parses_alacritty_toml_with_cursor_and_selection() {
+        let palette = parse_color_scheme(ALACRITTY_TOML.as_bytes(), ColorSchemeFormat::AlacrittyToml)
+            .expect("valid scheme");
+        assert_eq!(palette.primary_background, Rgb { r: 0x1d, g: 0x1f, b: 0x21 });
+        assert_eq!(palette.bright[0], Rgb { r: 0x66, g: 0x66, b: 0x66 });
+        assert_eq!(palette.cursor, Some(Rgb { r: 0xff, g: 0xcc, b: 0x00 }));
+        assert_eq!(palette.cursor_text, None);
+        assert_eq!(palette.selection_background, Some(Rgb { r: 0xff, g: 0xff, b: 0xff }));
+    }
+
+    #[test]
+    fn reports_missing_and_invalid_colors() {
+        let missing = ALACRITTY_TOML.replace("red = \"#cc6666\"\n", "");
+        match parse_color_scheme(missing.as_bytes(), ColorSchemeFormat::AlacrittyToml) {
+            Err(ColorSchemeErrorKind::MissingColor(key)) => assert_eq!(key, "colors.normal.red"),
+            other => panic!("expected missing color, got {:?}", other.err()),
+        }
+
+        let invalid = ALACRITTY_TOML.replace("#cc6666", "#cc66");
+        match parse_color_scheme(invalid.as_bytes(), ColorSchemeFormat::AlacrittyToml) {
+            Err(ColorSchemeErrorKind::InvalidColor { key, value }) => {
+                assert_eq!(key, "colors.normal.red");
+                assert_eq!(value, "#cc66");
+            }
+            other => panic!("expected invalid color, got {:?}", other.err()),
+        }
+
+        assert!(matches!(
+            parse_color_scheme(b"colors = [", ColorSchemeFormat::AlacrittyToml),
+            Err(ColorSchemeErrorKind::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn parses_base16_yaml() {
+        let yaml = "scheme: \"Test\"\nauthor: \"me\"\n".to_string()
+            + &(0..16).map(|i| format!("base{i:02X}: \"{:02x}{:02x}{:02x}\"\n", i, i, i)).collect::<String>();
+        let path = Path::new("test.yaml");
+        assert_eq!(detect_format(path, &yaml), Some(ColorSchemeFormat::Base16));
+
+        let palette = parse_color_scheme(yaml.as_bytes(), ColorSchemeFormat::Base16).expect("valid scheme");
+        assert_eq!(palette.primary_background, Rgb { r: 0, g: 0, b: 0 });
+        assert_eq!(palette.normal[1], Rgb { r: 8, g: 8, b: 8 });
+        assert_eq!(palette.bright[7], Rgb { r: 7, g: 7, b: 7 });
+        assert_eq!(palette.selection_background, Some(Rgb { r: 2, g: 2, b: 2 }));
+    }
+
+    #[test]
+    fn parses_iterm2_plist() {
+        let color = |key: &str, v: f64| {
+            format!(
+                "<key>{key}</key><dict><key>Blue Component</key><real>{v}</real>\
+                 <key>Green Component</key><real>{v}</real><key>Red Component</key><real>1</real></dict>"
+            )
+        };
+        let mut body = String::new();
+        for i in 0..16 {
+            body += &color(&format!("Ansi {i} Color"), 0.0);
+        }
+        body += &color("Background Color", 0.0);
+        body += &color("Foreground Color", 1.0);
+        body += &color("Selection Color", 0.5);
+        let plist = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+             <plist version=\"1.0\"><dict>{body}</dict></plist>"
+        );
+
+        let palette = parse_color_scheme(plist.as_bytes(), ColorSchemeFormat::Iterm2).expect("valid scheme");
+        assert_eq!(palette.primary_background, Rgb { r: 255, g: 0, b: 0 });
+        assert_eq!(palette.primary_foreground, Rgb { r: 255, g: 255, b: 255 });
+        assert_eq!(palette.selection_background, Some(Rgb { r: 255, g: 128, b: 128 }));
+        assert_eq!(palette.cursor, None);
+    }
+}
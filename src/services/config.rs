@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use dirs::home_dir;
 
 pub fn config_dir() -> PathBuf {
@@ -24,3 +24,35 @@ pub fn launchers_file_path() -> PathBuf {
     path.push("launchers.json");
     path
 }
+
+/// Launchers committed with a project, merged with the global ones.
+pub fn project_launchers_file_path(workspace_root: &Path) -> PathBuf {
+    workspace_root.join(".tide").join("launchers.json")
+}
+
+pub fn settings_file_path() -> PathBuf {
+    let mut path = config_dir();
+    path.push("settings.json");
+    path
+}
+
+/// Directory holding user color scheme files.
+pub fn themes_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("themes");
+    path
+}
+
+/// Directory holding saved pane scrollback, restored after a restart.
+pub fn scrollback_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("scrollback");
+    path
+}
+
+/// Directory holding the remote control sockets of running instances.
+pub fn sockets_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("sockets");
+    path
+}
@@ -0,0 +1,127 @@
+//! Notices edits to config files (`launchers.json`, `settings.json`, project launchers)
+//! so they can be reloaded without restarting Tide.
+//!
+//! Files are polled for their modification time and size, like the git status panel
+//! polls `git status`. Editors save in several steps (truncate, write, rename), so a
+//! change is only reported once the file has been quiet for `DEBOUNCE`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// What a file looked like when it was last polled; `None` when it did not exist.
+type Stamp = Option<(Option<SystemTime>, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+/// Collects changed files until none has changed for the debounce time.
+#[derive(Debug)]
+struct Debouncer {
+    quiet: Duration,
+    stamps: HashMap<PathBuf, Stamp>,
+    pending: BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    fn new(quiet: Duration) -> Self {
+        Self {
+            quiet,
+            stamps: HashMap::new(),
+            pending: BTreeSet::new(),
+            last_change: None,
+        }
+    }
+
+    /// Record what the watched files look like at `now`. Returns the files that changed,
+    /// once all of them have been quiet long enough. A file seen for the first time is
+    /// not a change: it was read when it started being watched.
+    fn observe(&mut self, observed: Vec<(PathBuf, Stamp)>, now: Instant) -> Vec<PathBuf> {
+        let mut stamps = HashMap::new();
+        for (path, stamp) in observed {
+            if self.stamps.get(&path).is_some_and(|previous| *previous != stamp) {
+                self.pending.insert(path.clone());
+                self.last_change = Some(now);
+            }
+            stamps.insert(path, stamp);
+        }
+        // Files no longer watched are forgotten
+        self.stamps = stamps;
+
+        match self.last_change {
+            Some(last) if now.duration_since(last) >= self.quiet => {
+                self.last_change = None;
+                std::mem::take(&mut self.pending).into_iter().collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Poll `paths` on a background thread and call `on_change` with the files that changed.
+/// The list can be updated while watching, e.g. when a workspace opens another folder.
+pub fn watch_config_files(
+    paths: Arc<Mutex<Vec<PathBuf>>>,
+    on_change: impl Fn(Vec<PathBuf>) + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let mut debouncer = Debouncer::new(DEBOUNCE);
+        loop {
+            let watched = paths.lock().map(|paths| paths.clone()).unwrap_or_default();
+            let observed = watched.into_iter().map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            });
+            let changed = debouncer.observe(observed.collect(), Instant::now());
+            if !changed.is_empty() {
+                on_change(changed);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_reported_once_the_files_are_quiet() {
+        let launchers = PathBuf::from("launchers.json");
+        let settings = PathBuf::from("settings.json");
+        let at = |secs: u64| Some((Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)), 10));
+        let start = Instant::now();
+        let ms = |ms: u64| start + Duration::from_millis(ms);
+
+        let mut debouncer = Debouncer::new(Duration::from_millis(400));
+        let watch = |launchers_stamp: Stamp, settings_stamp: Stamp| {
+            vec![(launchers.clone(), launchers_stamp), (settings.clone(), settings_stamp)]
+        };
+        // First sight is not a change
+        assert!(debouncer.observe(watch(at(1), None), ms(0)).is_empty());
+        assert!(debouncer.observe(watch(at(1), None), ms(500)).is_empty());
+
+        // An editor saving in steps: each step restarts the wait
+        assert!(debouncer.observe(watch(None, None), ms(1000)).is_empty());
+        assert!(debouncer.observe(watch(at(2), None), ms(1250)).is_empty());
+        assert!(debouncer.observe(watch(at(2), at(2)), ms(1500)).is_empty());
+        assert!(debouncer.observe(watch(at(2), at(2)), ms(1750)).is_empty());
+        assert_eq!(debouncer.observe(watch(at(2), at(2)), ms(1900)), vec![launchers.clone(), settings.clone()]);
+
+        // Reported once
+        assert!(debouncer.observe(watch(at(2), at(2)), ms(2500)).is_empty());
+
+        // A file that stops being watched is forgotten, and new again when it comes back
+        assert!(debouncer.observe(vec![(launchers.clone(), at(2))], ms(3000)).is_empty());
+        assert!(debouncer.observe(watch(at(2), at(3)), ms(3250)).is_empty());
+        assert!(debouncer.observe(watch(at(2), at(3)), ms(4000)).is_empty());
+    }
+}
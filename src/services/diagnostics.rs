@@ -0,0 +1,138 @@
+//! Positions of problems in JSON config files, for messages like
+//! `launchers.json:12:7: missing field `command``.
+//!
+//! Files are read leniently: each entry of a list is taken from the text as a
+//! `RawValue` and checked on its own, so one bad entry is reported and skipped instead
+//! of discarding the whole file.
+
+use serde_json::value::RawValue;
+use std::fmt;
+
+/// A problem at a 1-based line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// A problem at byte `offset` of `content`.
+    pub fn at(content: &str, offset: usize, message: impl Into<String>) -> Self {
+        let (line, column) = line_column(content, offset);
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// A serde error from parsing `part`, a slice of `content`, placed in `content`.
+    pub fn from_serde(content: &str, part: &str, err: &serde_json::Error) -> Self {
+        let start = offset_in(content, part);
+        // serde counts from 1; an error at line 0 has no position
+        let offset = if err.line() == 0 {
+            start
+        } else {
+            start + offset_of_line_column(part, err.line(), err.column())
+        };
+        Self::at(content, offset, serde_message(err))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Byte offset of `part` (a slice of `content`, such as a `RawValue`) in `content`.
+pub fn offset_in(content: &str, part: &str) -> usize {
+    (part.as_ptr() as usize)
+        .checked_sub(content.as_ptr() as usize)
+        .filter(|offset| *offset <= content.len())
+        .unwrap_or(0)
+}
+
+/// Byte offset of the first `"key":` of an object's text, for pointing at a field.
+pub fn key_offset(object: &RawValue, key: &str) -> Option<usize> {
+    let text = object.get();
+    let quoted = format!("\"{key}\"");
+    let mut from = 0;
+    while let Some(found) = text[from..].find(&quoted) {
+        let at = from + found;
+        let after = text[at + quoted.len()..].trim_start();
+        // Skip the text inside string values (`"a \"key\" b"`)
+        if !text[..at].ends_with('\\') && after.starts_with(':') {
+            return Some(at);
+        }
+        from = at + quoted.len();
+    }
+    None
+}
+
+/// serde's message without its " at line L column C" suffix.
+pub fn serde_message(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    match message.rfind(" at line ") {
+        Some(at) => message[..at].to_string(),
+        None => message,
+    }
+}
+
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |at| at + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+fn offset_of_line_column(text: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = text.split_inclusive('\n').take(line - 1).map(str::len).sum();
+    // serde's column counts bytes and points at the last byte read
+    let mut offset = (line_start + column.saturating_sub(1)).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_are_lines_and_columns() {
+        let content = "{\n  \"a\": 1,\n  \"bé\": x\n}";
+        let at = content.find('x').unwrap();
+        assert_eq!(Diagnostic::at(content, at, "bad"), Diagnostic { line: 3, column: 9, message: "bad".to_string() });
+        assert_eq!(Diagnostic::at(content, at, "bad").to_string(), "3:9: bad");
+
+        let err = serde_json::from_str::<serde_json::Value>(content).unwrap_err();
+        let diagnostic = Diagnostic::from_serde(content, content, &err);
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 9));
+        assert_eq!(diagnostic.message, "expected value");
+    }
+
+    #[test]
+    fn errors_in_a_slice_are_placed_in_the_file() {
+        let content = "[\n  {\"n\": 1},\n  {\"n\": \"two\"}\n]";
+        let entries: Vec<&RawValue> = serde_json::from_str(content).unwrap();
+
+        #[derive(serde::Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Entry {
+            n: u32,
+        }
+        let err = serde_json::from_str::<Entry>(entries[1].get()).unwrap_err();
+        let diagnostic = Diagnostic::from_serde(content, entries[1].get(), &err);
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 13));
+        assert!(diagnostic.message.starts_with("invalid type: string \"two\""), "{}", diagnostic.message);
+
+        let key = key_offset(entries[1], "n").unwrap();
+        assert_eq!(&entries[1].get()[key..key + 3], "\"n\"");
+        // A string value equal to the key is not the key
+        let object: &RawValue = serde_json::from_str(r#"{"m": "n", "n": 2}"#).unwrap();
+        assert_eq!(key_offset(object, "n"), Some(11));
+    }
+}
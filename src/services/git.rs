@@ -38,3 +38,18 @@ pub fn git_status_entries(root: &Path) -> Vec<String> {
         }
     }
 }
+
+/// Branch checked out in the repository at `root` (`None` outside a repository or on a
+/// detached HEAD).
+pub fn git_branch(root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
@@ -0,0 +1,305 @@
+//! Hint mode - label URLs, paths, hashes, IPs and UUIDs on screen for quick selection.
+//!
+//! The terminal collects its visible rows (soft-wrapped rows joined), every configured
+//! pattern is matched against them, and each match gets a short label from the hint
+//! alphabet. Typing a label runs the pattern's action on the matched text.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// What happens to the text of a chosen hint.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HintAction {
+    /// Copy to the clipboard
+    Copy,
+    /// Open with the system handler (browser for URLs, default app for files)
+    Open,
+    /// Type into the next terminal pane
+    SendToPane,
+    /// Open the file in an editor tab
+    OpenInEditor,
+}
+
+impl HintAction {
+    pub const ALL: [HintAction; 4] = [
+        HintAction::Copy,
+        HintAction::Open,
+        HintAction::SendToPane,
+        HintAction::OpenInEditor,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HintAction::Copy => "copy",
+            HintAction::Open => "open",
+            HintAction::SendToPane => "send to pane",
+            HintAction::OpenInEditor => "open in editor",
+        }
+    }
+}
+
+/// A kind of text to label, stored in `settings.json` under `hints.patterns`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HintPattern {
+    pub name: String,
+    /// Regular expression (Rust `regex` syntax); the whole match is the hint text
+    pub regex: String,
+    pub action: HintAction,
+}
+
+impl HintPattern {
+    fn new(name: &str, regex: &str, action: HintAction) -> Self {
+        Self {
+            name: name.to_string(),
+            regex: regex.to_string(),
+            action,
+        }
+    }
+}
+
+/// Hint mode configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HintSettings {
+    /// Characters labels are built from
+    pub alphabet: String,
+    /// Patterns in priority order: where matches overlap, the earlier pattern wins
+    pub patterns: Vec<HintPattern>,
+}
+
+impl Default for HintSettings {
+    fn default() -> Self {
+        Self {
+            alphabet: "asdfghjklqwertyuiopzxcvbnm".to_string(),
+            patterns: vec![
+                HintPattern::new(
+                    "url",
+                    r#"(?:https?|ftp|file)://[^\s<>"'`]*[^\s<>"'`.,;:!?)\]}]"#,
+                    HintAction::Open,
+                ),
+                HintPattern::new(
+                    "uuid",
+                    r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+                    HintAction::Copy,
+                ),
+                HintPattern::new(
+                    "ip",
+                    r"\b(?:\d{1,3}\.){3}\d{1,3}(?::\d{1,5})?\b",
+                    HintAction::Copy,
+                ),
+                HintPattern::new(
+                    "path",
+                    r"(?:~|\.{1,2})?/?(?:[\w.@+-]+/)+[\w.@+-]*[\w@+-](?::\d+){0,2}",
+                    HintAction::OpenInEditor,
+                ),
+                HintPattern::new("sha", r"\b[0-9a-f]{7,40}\b", HintAction::Copy),
+            ],
+        }
+    }
+}
+
+/// One on-screen line of text with the grid position of every character.
+pub struct HintRow {
+    pub text: String,
+    /// `(line, column)` of each char of `text`, in order
+    pub cells: Vec<(i32, usize)>,
+}
+
+/// A labelled match on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub label: String,
+    pub text: String,
+    /// Grid position of the first and last character
+    pub start: (i32, usize),
+    pub end: (i32, usize),
+    pub action: HintAction,
+}
+
+/// Compile the configured patterns; invalid ones are skipped and reported by name.
+pub fn compile_patterns(patterns: &[HintPattern]) -> (Vec<(Regex, HintAction)>, Vec<String>) {
+    let mut compiled = Vec::new();
+    let mut errors = Vec::new();
+    for pattern in patterns {
+        match Regex::new(&pattern.regex) {
+            Ok(regex) => compiled.push((regex, pattern.action)),
+            Err(err) => errors.push(format!("hint pattern '{}': {err}", pattern.name)),
+        }
+    }
+    (compiled, errors)
+}
+
+/// Find and label every match in `rows`, in screen order.
+pub fn find_hints(rows: &[HintRow], patterns: &[(Regex, HintAction)], alphabet: &str) -> Vec<Hint> {
+    // (row, first char, last char, action)
+    let mut found: Vec<(usize, usize, usize, HintAction)> = Vec::new();
+    for (row_index, row) in rows.iter().enumerate() {
+        let char_index = |byte: usize| row.text[..byte].chars().count();
+        let mut taken: Vec<(usize, usize)> = Vec::new();
+        for (regex, action) in patterns {
+            for found_match in regex.find_iter(&row.text) {
+                if found_match.as_str().is_empty() {
+                    continue;
+                }
+                let first = char_index(found_match.start());
+                let last = char_index(found_match.end()) - 1;
+                if taken.iter().any(|&(start, end)| first <= end && start <= last) {
+                    continue;
+                }
+                taken.push((first, last));
+                found.push((row_index, first, last, *action));
+            }
+        }
+    }
+    found.sort_by_key(|&(row, first, _, _)| (row, first));
+
+    let labels = hint_labels(found.len(), alphabet);
+    found
+        .into_iter()
+        .zip(labels)
+        .filter_map(|((row_index, first, last, action), label)| {
+            let row = &rows[row_index];
+            Some(Hint {
+                label,
+                text: row.text.chars().skip(first).take(last - first + 1).collect(),
+                start: *row.cells.get(first)?,
+                end: *row.cells.get(last)?,
+                action,
+            })
+        })
+        .collect()
+}
+
+/// `count` labels of equal length, so no label is a prefix of another.
+pub fn hint_labels(count: usize, alphabet: &str) -> Vec<String> {
+    let mut chars: Vec<char> = Vec::new();
+    for ch in alphabet.chars().map(|ch| ch.to_ascii_lowercase()) {
+        if !chars.contains(&ch) && !ch.is_whitespace() {
+            chars.push(ch);
+        }
+    }
+    if chars.len() < 2 {
+        chars = "asdfghjkl".chars().collect();
+    }
+
+    let mut length = 1;
+    while chars.len().pow(length) < count {
+        length += 1;
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = vec![' '; length as usize];
+            for slot in label.iter_mut().rev() {
+                *slot = chars[index % chars.len()];
+                index /= chars.len();
+            }
+            label.into_iter().collect()
+        })
+        .collect()
+}
+
+/// File path of a path hint: `:line:col` removed, `~/` expanded, relative paths
+/// resolved against `base`.
+pub fn hint_path(text: &str, base: &std::path::Path) -> std::path::PathBuf {
+    let mut path = text;
+    for _ in 0..2 {
+        if let Some((head, tail)) = path.rsplit_once(':') {
+            if !tail.is_empty() && tail.bytes().all(|byte| byte.is_ascii_digit()) {
+                path = head;
+            }
+        }
+    }
+
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    base.join(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    fn row(line: i32, text: &str) -> HintRow {
+        HintRow {
+            text: text.to_string(),
+            cells: (0..text.chars().count()).map(|col| (line, col)).collect(),
+        }
+    }
+
+    fn texts(rows: &[HintRow]) -> Vec<(String, HintAction)> {
+        let (patterns, errors) = compile_patterns(&HintSettings::default().patterns);
+        assert!(errors.is_empty(), "{errors:?}");
+        find_hints(rows, &patterns, "asdf")
+            .into_iter()
+            .map(|hint| (hint.text, hint.action))
+            .collect()
+    }
+
+    #[test]
+    fn default_patterns_find_each_kind() {
+        let rows = [
+            row(0, "see https://example.com/a?b=1). and src/main.rs:12:5"),
+            row(1, "commit 3f2a9c1d by 10.0.0.1:8080"),
+            row(2, "id 123e4567-e89b-12d3-a456-426614174000 done"),
+        ];
+        assert_eq!(
+            texts(&rows),
+            vec![
+                ("https://example.com/a?b=1".to_string(), HintAction::Open),
+                ("src/main.rs:12:5".to_string(), HintAction::OpenInEditor),
+                ("3f2a9c1d".to_string(), HintAction::Copy),
+                ("10.0.0.1:8080".to_string(), HintAction::Copy),
+                ("123e4567-e89b-12d3-a456-426614174000".to_string(), HintAction::Copy),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_map_to_grid_cells() {
+        // A wide char takes two columns but one char of text
+        let rows = [HintRow {
+            text: "界 deadbeef".to_string(),
+            cells: vec![(3, 0), (3, 2), (3, 3), (3, 4), (3, 5), (3, 6), (3, 7), (3, 8), (3, 9), (3, 10)],
+        }];
+        let (patterns, _) = compile_patterns(&HintSettings::default().patterns);
+        let hints = find_hints(&rows, &patterns, "asdf");
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].start, (3, 3));
+        assert_eq!(hints[0].end, (3, 10));
+    }
+
+    #[test]
+    fn labels_are_prefix_free() {
+        assert_eq!(hint_labels(3, "asdf"), vec!["a", "s", "d"]);
+        let labels = hint_labels(6, "asd");
+        assert_eq!(labels.len(), 6);
+        assert!(labels.iter().all(|label| label.len() == 2));
+        assert_eq!(labels[0], "aa");
+        assert_eq!(labels[5], "sd");
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        let patterns = vec![
+            HintPattern::new("broken", "(", HintAction::Copy),
+            HintPattern::new("word", r"\w+", HintAction::Copy),
+        ];
+        let (compiled, errors) = compile_patterns(&patterns);
+        assert_eq!(compiled.len(), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken"));
+    }
+
+    #[test]
+    fn hint_path_strips_position() {
+        let base = Path::new("/work");
+        assert_eq!(hint_path("src/main.rs:12:5", base), PathBuf::from("/work/src/main.rs"));
+        assert_eq!(hint_path("/etc/hosts", base), PathBuf::from("/etc/hosts"));
+    }
+}
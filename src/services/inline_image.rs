@@ -0,0 +1,1222 @@
+//! Inline images drawn by programs (`imgcat`, `viu`, `chafa`, matplotlib backends).
+//!
+//! Two protocols are understood: kitty graphics (`APC G … ST`) and iTerm2's
+//! `OSC 1337 ; File=… ST`. The terminal parser ignores both, so the IO thread splits them
+//! out of the PTY output with [`ImageScanner`] and places each image at the cursor. A
+//! placement is anchored to the cell under its top-left corner (the cell carries a
+//! `tide-image:` hyperlink), so it scrolls with the text and goes away once that cell is
+//! overwritten or leaves the scrollback. Decoded images count against a memory limit:
+//! images nothing shows are evicted first, then the least recently drawn.
+
+use base64::Engine as _;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::services::settings::ImageSettings;
+
+const KITTY_PREFIX: &[u8] = b"\x1b_G";
+const ITERM_PREFIX: &[u8] = b"\x1b]1337;File=";
+
+/// Longest image sequence kept while scanning; longer ones are dropped. Kitty clients
+/// send large images in chunks, so this mostly bounds iTerm2 transfers.
+pub const MAX_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+
+/// URI scheme of the hyperlink marking a placement's anchor cell.
+pub const ANCHOR_SCHEME: &str = "tide-image:";
+
+/// Placements kept before those whose anchor cell is gone are pruned.
+pub const MAX_PLACEMENTS: usize = 1024;
+
+/// Keys of images and placements, unique across panes so renderers can cache by them.
+static NEXT_KEY: AtomicU64 = AtomicU64::new(1);
+
+fn next_key() -> u64 {
+    NEXT_KEY.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A piece of PTY output split by [`ImageScanner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanItem {
+    /// Ordinary output for the terminal parser
+    Output(Vec<u8>),
+    /// Kitty graphics command: the bytes between `ESC _ G` and the terminator
+    Kitty(Vec<u8>),
+    /// iTerm2 file transfer: the bytes between `OSC 1337 ; File=` and the terminator
+    Iterm(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    Kitty,
+    Iterm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    /// Possible start of an image sequence, kept in `pending`
+    Prefix,
+    Payload(Protocol),
+    /// `ESC` seen inside a payload (start of the `ESC \` terminator)
+    PayloadEscape(Protocol),
+}
+
+/// Incremental splitter for image sequences; keeps state across PTY reads.
+#[derive(Debug)]
+pub struct ImageScanner {
+    state: ScanState,
+    pending: Vec<u8>,
+    payload: Vec<u8>,
+    overflow: bool,
+}
+
+impl Default for ImageScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImageScanner {
+    pub fn new() -> Self {
+        Self {
+            state: ScanState::Ground,
+            pending: Vec::new(),
+            payload: Vec::new(),
+            overflow: false,
+        }
+    }
+
+    /// Split a chunk of PTY output into terminal output and image sequences, in order.
+    /// Sequences never reach the output, even when they are dropped for being too long.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<ScanItem> {
+        let mut items = Vec::new();
+        let mut output = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+        while index < bytes.len() {
+            let byte = bytes[index];
+            match self.state {
+                ScanState::Ground => {
+                    match bytes[index..].iter().position(|&byte| byte == 0x1b) {
+                        Some(offset) => {
+                            output.extend_from_slice(&bytes[index..index + offset]);
+                            self.pending.push(0x1b);
+                            self.state = ScanState::Prefix;
+                            index += offset + 1;
+                        }
+                        None => {
+                            output.extend_from_slice(&bytes[index..]);
+                            index = bytes.len();
+                        }
+                    }
+                    continue;
+                }
+                ScanState::Prefix => {
+                    self.pending.push(byte);
+                    if self.pending == KITTY_PREFIX {
+                        self.start_payload(Protocol::Kitty);
+                    } else if self.pending == ITERM_PREFIX {
+                        self.start_payload(Protocol::Iterm);
+                    } else if !KITTY_PREFIX.starts_with(&self.pending)
+                        && !ITERM_PREFIX.starts_with(&self.pending)
+                    {
+                        // Some other sequence: hand it to the parser and look at `byte` again
+                        self.pending.pop();
+                        output.append(&mut self.pending);
+                        self.state = ScanState::Ground;
+                        continue;
+                    }
+                }
+                ScanState::Payload(protocol) => match byte {
+                    0x07 => self.finish(protocol, &mut output, &mut items),
+                    0x1b => self.state = ScanState::PayloadEscape(protocol),
+                    0x18 | 0x1a => {
+                        self.payload.clear();
+                        self.state = ScanState::Ground;
+                    }
+                    _ => {
+                        if self.payload.len() < MAX_SEQUENCE_LEN {
+                            self.payload.push(byte);
+                        } else {
+                            self.overflow = true;
+                        }
+                    }
+                },
+                ScanState::PayloadEscape(protocol) => {
+                    if byte == b'\\' {
+                        self.finish(protocol, &mut output, &mut items);
+                    } else {
+                        // Any other escape aborts the sequence and may start a new one
+                        self.payload.clear();
+                        self.pending.push(0x1b);
+                        self.state = ScanState::Prefix;
+                        continue;
+                    }
+                }
+            }
+            index += 1;
+        }
+        if !output.is_empty() {
+            items.push(ScanItem::Output(output));
+        }
+        items
+    }
+
+    fn start_payload(&mut self, protocol: Protocol) {
+        self.pending.clear();
+        self.payload.clear();
+        self.overflow = false;
+        self.state = ScanState::Payload(protocol);
+    }
+
+    fn finish(&mut self, protocol: Protocol, output: &mut Vec<u8>, items: &mut Vec<ScanItem>) {
+        self.state = ScanState::Ground;
+        let payload = std::mem::take(&mut self.payload);
+        if self.overflow {
+            return;
+        }
+        if !output.is_empty() {
+            items.push(ScanItem::Output(std::mem::take(output)));
+        }
+        items.push(match protocol {
+            Protocol::Kitty => ScanItem::Kitty(payload),
+            Protocol::Iterm => ScanItem::Iterm(payload),
+        });
+    }
+}
+
+/// Requested image width or height.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dimension {
+    /// From the image's pixel size (and the other dimension, keeping the aspect ratio)
+    #[default]
+    Auto,
+    Cells(u32),
+    Pixels(u32),
+    /// Percent of the pane
+    Percent(u32),
+}
+
+impl Dimension {
+    /// iTerm2 syntax: `N` (cells), `Npx`, `N%` or `auto`.
+    fn parse(value: &str) -> Dimension {
+        let number = |digits: &str| digits.trim().parse().ok();
+        if let Some(pixels) = value.strip_suffix("px").and_then(number) {
+            Dimension::Pixels(pixels)
+        } else if let Some(percent) = value.strip_suffix('%').and_then(number) {
+            Dimension::Percent(percent)
+        } else {
+            number(value).map_or(Dimension::Auto, Dimension::Cells)
+        }
+    }
+
+    /// Length in pixels given the cell length and the pane length in pixels.
+    fn resolve(self, cell: f64, pane: f64) -> Option<f64> {
+        match self {
+            Dimension::Auto => None,
+            Dimension::Cells(cells) => Some(cells as f64 * cell),
+            Dimension::Pixels(pixels) => Some(pixels as f64),
+            Dimension::Percent(percent) => Some(pane * percent as f64 / 100.0),
+        }
+    }
+}
+
+/// Cells an image of `pixels` covers when drawn at the requested size in a pane of
+/// `screen` (columns, lines) with `cell`-sized cells. Images never get wider than the pane.
+pub fn placement_cells(
+    pixels: (u32, u32),
+    width: Dimension,
+    height: Dimension,
+    preserve_aspect: bool,
+    cell: (f64, f64),
+    screen: (usize, usize),
+) -> (u16, u16) {
+    let (cell_width, cell_height) = (cell.0.max(1.0), cell.1.max(1.0));
+    let pane_width = screen.0.max(1) as f64 * cell_width;
+    let pane_height = screen.1.max(1) as f64 * cell_height;
+    let (image_width, image_height) = (pixels.0.max(1) as f64, pixels.1.max(1) as f64);
+
+    let (mut drawn_width, mut drawn_height) = match (
+        width.resolve(cell_width, pane_width),
+        height.resolve(cell_height, pane_height),
+    ) {
+        (None, None) => (image_width, image_height),
+        (Some(width), None) if preserve_aspect => (width, width * image_height / image_width),
+        (Some(width), None) => (width, image_height),
+        (None, Some(height)) if preserve_aspect => (height * image_width / image_height, height),
+        (None, Some(height)) => (image_width, height),
+        (Some(width), Some(height)) if preserve_aspect => {
+            let scale = (width / image_width).min(height / image_height);
+            (image_width * scale, image_height * scale)
+        }
+        (Some(width), Some(height)) => (width, height),
+    };
+    if drawn_width > pane_width {
+        if preserve_aspect {
+            drawn_height *= pane_width / drawn_width;
+        }
+        drawn_width = pane_width;
+    }
+
+    let cells = |length: f64, cell: f64| (length / cell).ceil().clamp(1.0, u16::MAX as f64) as u16;
+    (cells(drawn_width, cell_width), cells(drawn_height, cell_height))
+}
+
+/// A decoded image, as 8-bit RGBA rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineImage {
+    /// Unique key, also used by the renderer to cache the texture
+    pub key: u64,
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Arc<Vec<u8>>,
+}
+
+impl InlineImage {
+    fn new(width: u32, height: u32, rgba: Vec<u8>) -> Self {
+        Self {
+            key: next_key(),
+            width,
+            height,
+            rgba: Arc::new(rgba),
+        }
+    }
+
+    fn memory(&self) -> usize {
+        self.rgba.len()
+    }
+}
+
+/// An image shown on the grid, `cols` × `rows` cells from its anchor cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub image: u64,
+    pub cols: u16,
+    pub rows: u16,
+    /// Fill the cells exactly instead of keeping the aspect ratio
+    pub stretch: bool,
+}
+
+/// An image to place at the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlacementRequest {
+    pub image: u64,
+    pub width: Dimension,
+    pub height: Dimension,
+    pub preserve_aspect: bool,
+    /// Move the cursor past the image (to the right of its last row)
+    pub move_cursor: bool,
+}
+
+/// A kitty graphics command (`ESC _ G <control data> ; <payload> ESC \`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KittyCommand {
+    /// `a`: `t` transmit, `T` transmit and display, `p` display, `d` delete, `q` query
+    pub action: u8,
+    /// `f`: 24 (RGB), 32 (RGBA) or 100 (PNG)
+    pub format: u32,
+    /// `t`: `d` direct, `f` file, `t` temporary file, `s` shared memory
+    pub medium: u8,
+    /// `s` and `v`: pixel size of RGB(A) data
+    pub width: u32,
+    pub height: u32,
+    /// `i`: image id chosen by the program (0 = none)
+    pub id: u32,
+    /// `c` and `r`: cells to draw the image in (0 = from the pixel size)
+    pub cols: u32,
+    pub rows: u32,
+    /// `m=1`: more chunks of the payload follow
+    pub more: bool,
+    /// `q`: 1 suppresses OK replies, 2 errors as well
+    pub quiet: u8,
+    /// `o`: payload compression
+    pub compression: Option<u8>,
+    /// `C=1`: leave the cursor where it is
+    pub keep_cursor: bool,
+    /// `d`: what a delete removes
+    pub delete: u8,
+    /// Base64 payload
+    pub payload: Vec<u8>,
+}
+
+impl Default for KittyCommand {
+    fn default() -> Self {
+        Self {
+            action: b't',
+            format: 32,
+            medium: b'd',
+            width: 0,
+            height: 0,
+            id: 0,
+            cols: 0,
+            rows: 0,
+            more: false,
+            quiet: 0,
+            compression: None,
+            keep_cursor: false,
+            delete: b'a',
+            payload: Vec::new(),
+        }
+    }
+}
+
+/// Parse the bytes between `ESC _ G` and the terminator. Unknown keys are ignored.
+pub fn parse_kitty(bytes: &[u8]) -> KittyCommand {
+    let (control, payload) = match bytes.iter().position(|&byte| byte == b';') {
+        Some(split) => (&bytes[..split], &bytes[split + 1..]),
+        None => (bytes, &[][..]),
+    };
+    let mut command = KittyCommand {
+        payload: payload.to_vec(),
+        ..KittyCommand::default()
+    };
+    for pair in control.split(|&byte| byte == b',') {
+        let [key, b'=', value @ ..] = pair else { continue };
+        let number = || {
+            std::str::from_utf8(value)
+                .ok()
+                .and_then(|value| value.parse::<u32>().ok())
+                .unwrap_or(0)
+        };
+        let letter = value.first().copied().unwrap_or(0);
+        match *key {
+            b'a' => command.action = letter,
+            b'f' => command.format = number(),
+            b't' => command.medium = letter,
+            b's' => command.width = number(),
+            b'v' => command.height = number(),
+            b'i' => command.id = number(),
+            b'c' => command.cols = number(),
+            b'r' => command.rows = number(),
+            b'm' => command.more = number() == 1,
+            b'q' => command.quiet = number().min(2) as u8,
+            b'o' => command.compression = Some(letter),
+            b'C' => command.keep_cursor = number() == 1,
+            b'd' => command.delete = letter,
+            _ => {}
+        }
+    }
+    command
+}
+
+/// An iTerm2 file transfer (`OSC 1337 ; File=<args> : <base64> ST`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItermFile {
+    /// Only inline files are images to show; others are downloads
+    pub inline: bool,
+    pub width: Dimension,
+    pub height: Dimension,
+    pub preserve_aspect: bool,
+    /// Decoded file contents
+    pub data: Vec<u8>,
+}
+
+/// Parse the bytes between `OSC 1337 ; File=` and the terminator.
+pub fn parse_iterm(bytes: &[u8]) -> Result<ItermFile, String> {
+    let split = bytes
+        .iter()
+        .position(|&byte| byte == b':')
+        .ok_or("missing file contents")?;
+    let args = String::from_utf8_lossy(&bytes[..split]);
+    let mut file = ItermFile {
+        inline: false,
+        width: Dimension::Auto,
+        height: Dimension::Auto,
+        preserve_aspect: true,
+        data: Vec::new(),
+    };
+    for arg in args.split(';') {
+        let Some((key, value)) = arg.split_once('=') else { continue };
+        match key {
+            "inline" => file.inline = value == "1",
+            "width" => file.width = Dimension::parse(value),
+            "height" => file.height = Dimension::parse(value),
+            "preserveAspectRatio" => file.preserve_aspect = value != "0",
+            _ => {}
+        }
+    }
+    file.data = decode_base64(&bytes[split + 1..])?;
+    Ok(file)
+}
+
+fn decode_base64(data: &[u8]) -> Result<Vec<u8>, String> {
+    let data: Vec<u8> = data.iter().copied().filter(|byte| !byte.is_ascii_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(&data)
+        .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(&data))
+        .map_err(|err| format!("invalid base64: {err}"))
+}
+
+/// Decode a PNG or JPEG file, refusing images over `limit` bytes once decoded.
+fn decode_file(data: &[u8], limit: usize) -> Result<InlineImage, String> {
+    let mut limits = image::Limits::default();
+    limits.max_alloc = Some(limit as u64);
+    let mut reader = image::ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| err.to_string())?;
+    reader.limits(limits);
+    let rgba = reader.decode().map_err(|err| err.to_string())?.into_rgba8();
+    Ok(InlineImage::new(rgba.width(), rgba.height(), rgba.into_raw()))
+}
+
+/// Wrap raw RGB (`format` 24) or RGBA (32) pixels.
+fn decode_raw(format: u32, width: u32, height: u32, data: Vec<u8>) -> Result<InlineImage, String> {
+    let pixels = width as usize * height as usize;
+    if pixels == 0 {
+        return Err("EINVAL:missing image size".to_string());
+    }
+    let rgba = match format {
+        24 if data.len() == pixels * 3 => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 0xff])
+            .collect(),
+        32 if data.len() == pixels * 4 => data,
+        24 | 32 => return Err("ENODATA:payload does not match the image size".to_string()),
+        _ => return Err(format!("EINVAL:unsupported format {format}")),
+    };
+    Ok(InlineImage::new(width, height, rgba))
+}
+
+/// What the terminal does for a kitty command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KittyOutcome {
+    /// Image to place at the cursor
+    pub place: Option<PlacementRequest>,
+    /// Reply to write back to the program
+    pub reply: Option<String>,
+}
+
+struct StoredImage {
+    image: InlineImage,
+    last_used: u64,
+}
+
+/// Images and placements of one terminal.
+pub struct ImageStore {
+    enabled: bool,
+    /// Most bytes decoded images may take
+    limit: usize,
+    used: usize,
+    images: HashMap<u64, StoredImage>,
+    /// Image ids chosen by kitty clients
+    kitty_ids: HashMap<u32, u64>,
+    placements: HashMap<u64, Placement>,
+    /// Kitty transmission whose payload is still arriving in chunks
+    upload: Option<KittyCommand>,
+    /// Cell size in pixels, for sizing images given in pixels
+    cell_size: (f64, f64),
+    clock: u64,
+}
+
+impl ImageStore {
+    pub fn new(settings: ImageSettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            limit: settings.memory_limit(),
+            used: 0,
+            images: HashMap::new(),
+            kitty_ids: HashMap::new(),
+            placements: HashMap::new(),
+            upload: None,
+            cell_size: (8.0, 16.0),
+            clock: 0,
+        }
+    }
+
+    /// Apply changed settings; images over the new limit are evicted right away.
+    pub fn configure(&mut self, settings: ImageSettings) {
+        self.enabled = settings.enabled;
+        self.limit = settings.memory_limit();
+        if !self.enabled {
+            self.clear();
+        }
+        self.evict(0);
+    }
+
+    pub fn set_cell_size(&mut self, width: f64, height: f64) {
+        self.cell_size = (width, height);
+    }
+
+    pub fn cell_size(&self) -> (f64, f64) {
+        self.cell_size
+    }
+
+    /// Bytes taken by decoded images.
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn placement_count(&self) -> usize {
+        self.placements.len()
+    }
+
+    /// Most rows any placement covers: how far above the viewport an anchor can be
+    /// and still have its image show.
+    pub fn max_rows(&self) -> u16 {
+        self.placements.values().map(|placement| placement.rows).max().unwrap_or(0)
+    }
+
+    /// Drop every image and placement.
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.kitty_ids.clear();
+        self.placements.clear();
+        self.upload = None;
+        self.used = 0;
+    }
+
+    pub fn image(&self, key: u64) -> Option<&InlineImage> {
+        self.images.get(&key).map(|stored| &stored.image)
+    }
+
+    /// Store a decoded image, evicting others to stay under the memory limit.
+    pub fn insert(&mut self, image: InlineImage) -> Result<u64, String> {
+        if image.memory() > self.limit {
+            return Err("EFBIG:image exceeds the memory limit".to_string());
+        }
+        self.evict(image.memory());
+        self.clock += 1;
+        let key = image.key;
+        self.used += image.memory();
+        self.images.insert(
+            key,
+            StoredImage {
+                image,
+                last_used: self.clock,
+            },
+        );
+        Ok(key)
+    }
+
+    fn remove_image(&mut self, key: u64) {
+        if let Some(stored) = self.images.remove(&key) {
+            self.used -= stored.image.memory();
+        }
+        self.placements.retain(|_, placement| placement.image != key);
+        self.kitty_ids.retain(|_, image| *image != key);
+    }
+
+    /// Evict images until `incoming` more bytes fit: those without placements first,
+    /// then the least recently drawn.
+    fn evict(&mut self, incoming: usize) {
+        if self.used + incoming <= self.limit {
+            return;
+        }
+        let mut candidates: Vec<(bool, u64, u64)> = self
+            .images
+            .iter()
+            .map(|(key, stored)| {
+                let placed = self.placements.values().any(|placement| placement.image == *key);
+                (placed, stored.last_used, *key)
+            })
+            .collect();
+        candidates.sort_unstable();
+        for (_, _, key) in candidates {
+            if self.used + incoming <= self.limit {
+                break;
+            }
+            self.remove_image(key);
+        }
+    }
+
+    /// Add a placement of a stored image; returns the key its anchor cell refers to.
+    pub fn place(&mut self, placement: Placement) -> u64 {
+        let key = next_key();
+        self.placements.insert(key, placement);
+        key
+    }
+
+    /// The placement with anchor key `key` and its image; marks the image as used.
+    pub fn lookup(&mut self, key: u64) -> Option<(Placement, InlineImage)> {
+        let placement = *self.placements.get(&key)?;
+        let stored = self.images.get_mut(&placement.image)?;
+        self.clock += 1;
+        stored.last_used = self.clock;
+        Some((placement, stored.image.clone()))
+    }
+
+    /// Keep only the placements whose anchor cell still exists.
+    pub fn retain_placements(&mut self, live: impl Fn(u64) -> bool) {
+        self.placements.retain(|key, _| live(*key));
+    }
+
+    /// Run an iTerm2 file transfer; `Ok(None)` for files that are not inline images.
+    pub fn handle_iterm(&mut self, payload: &[u8]) -> Result<Option<PlacementRequest>, String> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let file = parse_iterm(payload)?;
+        if !file.inline {
+            return Ok(None);
+        }
+        let image = decode_file(&file.data, self.limit)?;
+        let key = self.insert(image)?;
+        Ok(Some(PlacementRequest {
+            image: key,
+            width: file.width,
+            height: file.height,
+            preserve_aspect: file.preserve_aspect,
+            move_cursor: true,
+        }))
+    }
+
+    /// Run a kitty graphics command. Programs get no reply while images are disabled,
+    /// so they fall back to text.
+    pub fn handle_kitty(&mut self, payload: &[u8]) -> KittyOutcome {
+        if !self.enabled {
+            return KittyOutcome::default();
+        }
+        let Some(command) = self.assemble(parse_kitty(payload)) else {
+            return KittyOutcome::default();
+        };
+
+        let result = match command.action {
+            b't' | b'T' | b'q' => self.transmit(&command),
+            b'p' => self
+                .kitty_ids
+                .get(&command.id)
+                .copied()
+                .filter(|key| self.images.contains_key(key))
+                .map(Some)
+                .ok_or_else(|| "ENOENT:no such image".to_string()),
+            b'd' => {
+                self.delete(&command);
+                return KittyOutcome::default();
+            }
+            _ => Err("EINVAL:unknown action".to_string()),
+        };
+
+        let place = match (&result, command.action) {
+            (Ok(Some(image)), b'T' | b'p') => Some(PlacementRequest {
+                image: *image,
+                width: if command.cols > 0 { Dimension::Cells(command.cols) } else { Dimension::Auto },
+                height: if command.rows > 0 { Dimension::Cells(command.rows) } else { Dimension::Auto },
+                preserve_aspect: command.cols == 0 || command.rows == 0,
+                move_cursor: !command.keep_cursor,
+            }),
+            _ => None,
+        };
+        let reply = match result {
+            _ if command.id == 0 => None,
+            Ok(_) if command.quiet == 0 => Some("OK".to_string()),
+            Err(message) if command.quiet < 2 => Some(message),
+            _ => None,
+        }
+        .map(|message| format!("\x1b_Gi={};{message}\x1b\\", command.id));
+        KittyOutcome { place, reply }
+    }
+
+    /// Collect chunked payloads (`m=1`); the command is complete once this returns it.
+    fn assemble(&mut self, command: KittyCommand) -> Option<KittyCommand> {
+        let Some(mut upload) = self.upload.take() else {
+            if command.more {
+                self.upload = Some(command);
+                return None;
+            }
+            return Some(command);
+        };
+        // Later chunks only carry `m` (and `q`); the first one describes the image
+        upload.payload.extend_from_slice(&command.payload);
+        if upload.payload.len() > self.limit.saturating_mul(2) {
+            crate::logging::log_line("WARN", "Dropping kitty image upload over the memory limit");
+            return None;
+        }
+        if command.more {
+            self.upload = Some(upload);
+            return None;
+        }
+        upload.more = false;
+        Some(upload)
+    }
+
+    /// Decode a transmitted image and store it (not for queries); returns its key.
+    fn transmit(&mut self, command: &KittyCommand) -> Result<Option<u64>, String> {
+        if command.compression.is_some() {
+            return Err("EINVAL:compressed payloads are not supported".to_string());
+        }
+        let data = decode_base64(&command.payload).map_err(|err| format!("EINVAL:{err}"))?;
+        let data = match command.medium {
+            b'd' => data,
+            b'f' | b't' => read_image_file(&data, command.medium == b't', self.limit)?,
+            _ => return Err("EINVAL:unsupported transmission medium".to_string()),
+        };
+        let image = match command.format {
+            100 => decode_file(&data, self.limit).map_err(|err| format!("EBADPNG:{err}"))?,
+            format => decode_raw(format, command.width, command.height, data)?,
+        };
+        if image.memory() > self.limit {
+            return Err("EFBIG:image exceeds the memory limit".to_string());
+        }
+        if command.action == b'q' {
+            return Ok(None);
+        }
+
+        if let Some(previous) = self.kitty_ids.remove(&command.id) {
+            self.remove_image(previous);
+        }
+        let key = self.insert(image)?;
+        if command.id != 0 {
+            self.kitty_ids.insert(command.id, key);
+        }
+        Ok(Some(key))
+    }
+
+    /// Delete placements (`d=a`, `d=i`) or placements and images (`A`, `I`).
+    fn delete(&mut self, command: &KittyCommand) {
+        let free = command.delete.is_ascii_uppercase();
+        let target = match command.delete.to_ascii_lowercase() {
+            b'a' => None,
+            b'i' => match self.kitty_ids.get(&command.id) {
+                Some(key) => Some(*key),
+                None => return,
+            },
+            // Deleting by position, z-index or frame is not supported
+            _ => return,
+        };
+        let keys: Vec<u64> = match target {
+            Some(key) => vec![key],
+            None => self.images.keys().copied().collect(),
+        };
+        for key in keys {
+            if free {
+                self.remove_image(key);
+            } else {
+                self.placements.retain(|_, placement| placement.image != key);
+            }
+        }
+    }
+}
+
+/// Read an image sent by path (`t=f`, `t=t`); temporary files are deleted afterwards.
+/// This runs with the terminal locked, so only regular files are read, and no more than
+/// `limit` bytes of them: a FIFO or a device such as `/dev/zero` would hang the pane.
+fn read_image_file(path: &[u8], temporary: bool, limit: usize) -> Result<Vec<u8>, String> {
+    let path = Path::new(std::str::from_utf8(path).map_err(|_| "EINVAL:invalid path".to_string())?);
+    let metadata = std::fs::metadata(path).map_err(|err| format!("EBADF:{err}"))?;
+    if !metadata.is_file() {
+        return Err("EBADF:not a regular file".to_string());
+    }
+    if metadata.len() > limit as u64 {
+        return Err("EFBIG:file exceeds the memory limit".to_string());
+    }
+    let mut data = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(limit as u64 + 1).read_to_end(&mut data))
+        .map_err(|err| format!("EBADF:{err}"))?;
+    if data.len() > limit {
+        return Err("EFBIG:file exceeds the memory limit".to_string());
+    }
+    if temporary && is_protocol_temp_file(path) {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(data)
+}
+
+/// The protocol only allows removing files that are clearly meant for it: in the
+/// temporary directory, with `tty-graphics-protocol` in their name.
+fn is_protocol_temp_file(path: &Path) -> bool {
+    let named = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().contains("tty-graphics-protocol"));
+    // Resolved, so `..` and symlinks cannot lead out of it
+    let in_temp_dir = match (path.canonicalize(), std::env::temp_dir().canonicalize()) {
+        (Ok(path), Ok(temp_dir)) => path.starts_with(temp_dir),
+        _ => false,
+    };
+    named && in_temp_dir
+}
+
+/// Image sequence handling that needs the terminal grid.
+#[cfg(target_os = "macos")]
+pub use self::platform::{handle_sequence, visible_placements, VisiblePlacement};
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{
+        placement_cells, ImageStore, InlineImage, Placement, PlacementRequest, ScanItem,
+        ANCHOR_SCHEME, MAX_PLACEMENTS,
+    };
+    use crate::logging;
+    use alacritty_terminal::event::EventListener;
+    use alacritty_terminal::grid::Dimensions;
+    use alacritty_terminal::index::{Column, Line};
+    use alacritty_terminal::term::cell::{Cell, Hyperlink};
+    use alacritty_terminal::term::Term;
+    use alacritty_terminal::vte::ansi::Handler;
+    use std::collections::HashSet;
+
+    /// A placement with at least one row in the viewport.
+    #[derive(Debug, Clone)]
+    pub struct VisiblePlacement {
+        /// Viewport line of the top row; negative when it has scrolled above the top
+        pub line: i32,
+        pub column: usize,
+        pub placement: Placement,
+        pub image: InlineImage,
+    }
+
+    fn anchor_key(cell: &Cell) -> Option<u64> {
+        cell.extra.as_ref()?;
+        cell.hyperlink()?.uri().strip_prefix(ANCHOR_SCHEME)?.parse().ok()
+    }
+
+    /// Run an image sequence split out by `ImageScanner`; returns the reply for the program.
+    pub fn handle_sequence<T: EventListener>(
+        term: &mut Term<T>,
+        store: &mut ImageStore,
+        item: ScanItem,
+    ) -> Option<String> {
+        match item {
+            ScanItem::Output(_) => None,
+            ScanItem::Kitty(payload) => {
+                let outcome = store.handle_kitty(&payload);
+                if let Some(request) = outcome.place {
+                    place_image(term, store, request);
+                }
+                outcome.reply
+            }
+            ScanItem::Iterm(payload) => {
+                match store.handle_iterm(&payload) {
+                    Ok(Some(request)) => place_image(term, store, request),
+                    Ok(None) => {}
+                    Err(err) => {
+                        logging::log_line("WARN", &format!("Inline image not shown: {err}"))
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Anchor an image at the cursor and, unless asked not to, move the cursor past it,
+    /// scrolling like the same number of lines of text would.
+    fn place_image<T: EventListener>(term: &mut Term<T>, store: &mut ImageStore, request: PlacementRequest) {
+        let Some(image) = store.image(request.image) else { return };
+        let (cols, rows) = placement_cells(
+            (image.width, image.height),
+            request.width,
+            request.height,
+            request.preserve_aspect,
+            store.cell_size(),
+            (term.columns(), term.screen_lines()),
+        );
+        if store.placement_count() >= MAX_PLACEMENTS {
+            prune_placements(term, store);
+        }
+        let key = store.place(Placement {
+            image: request.image,
+            cols,
+            rows,
+            stretch: !request.preserve_aspect,
+        });
+
+        let point = term.grid().cursor.point;
+        let anchor = Hyperlink::new(None::<String>, format!("{ANCHOR_SCHEME}{key}"));
+        term.grid_mut()[point].set_hyperlink(Some(anchor));
+        if request.move_cursor {
+            for _ in 1..rows {
+                term.linefeed();
+            }
+            term.move_forward(cols as usize);
+        }
+    }
+
+    /// Drop placements whose anchor cell has been overwritten or scrolled out of history.
+    fn prune_placements<T: EventListener>(term: &Term<T>, store: &mut ImageStore) {
+        let grid = term.grid();
+        let mut live = HashSet::new();
+        for line in term.topmost_line().0..=term.bottommost_line().0 {
+            let row = &grid[Line(line)];
+            for column in 0..term.columns() {
+                if let Some(key) = anchor_key(&row[Column(column)]) {
+                    live.insert(key);
+                }
+            }
+        }
+        store.retain_placements(|key| live.contains(&key));
+    }
+
+    /// Placements showing in the viewport, found through their anchor cells.
+    pub fn visible_placements<T: EventListener>(
+        term: &Term<T>,
+        store: &mut ImageStore,
+    ) -> Vec<VisiblePlacement> {
+        let mut visible = Vec::new();
+        if store.placement_count() == 0 {
+            return visible;
+        }
+        let grid = term.grid();
+        let top = -(grid.display_offset() as i32);
+        let bottom = top + term.screen_lines() as i32;
+        // Anchors above the viewport still show the lower part of tall images
+        let first = (top - store.max_rows() as i32 + 1).max(term.topmost_line().0);
+        for line in first..bottom {
+            let row = &grid[Line(line)];
+            for column in 0..term.columns() {
+                let Some(key) = anchor_key(&row[Column(column)]) else { continue };
+                let Some((placement, image)) = store.lookup(key) else { continue };
+                if line + placement.rows as i32 > top {
+                    visible.push(VisiblePlacement {
+                        line: line - top,
+                        column,
+                        placement,
+                        image,
+                    });
+                }
+            }
+        }
+        visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(memory_limit_mb: usize) -> ImageSettings {
+        ImageSettings {
+            enabled: true,
+            memory_limit_mb,
+        }
+    }
+
+    fn base64(data: &[u8]) -> String {
+        base64::engine::general_purpose::STANDARD.encode(data)
+    }
+
+    #[test]
+    fn scanner_splits_images_from_output() {
+        let mut scanner = ImageScanner::new();
+        let items = scanner.feed(b"ls\r\n\x1b_Ga=q,i=1;AAAA\x1b\\\x1b[1mok\x1b]1337;File=inline=1:QQ==\x07$ ");
+        assert_eq!(
+            items,
+            vec![
+                ScanItem::Output(b"ls\r\n".to_vec()),
+                ScanItem::Kitty(b"a=q,i=1;AAAA".to_vec()),
+                ScanItem::Output(b"\x1b[1mok".to_vec()),
+                ScanItem::Iterm(b"inline=1:QQ==".to_vec()),
+                ScanItem::Output(b"$ ".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn scanner_handles_sequences_split_across_reads() {
+        let mut scanner = ImageScanner::new();
+        assert_eq!(scanner.feed(b"a\x1b"), vec![ScanItem::Output(b"a".to_vec())]);
+        assert!(scanner.feed(b"_Gi=2;AA").is_empty());
+        assert!(scanner.feed(b"AA\x1b").is_empty());
+        assert_eq!(scanner.feed(b"\\b"), vec![ScanItem::Kitty(b"i=2;AAAA".to_vec()), ScanItem::Output(b"b".to_vec())]);
+        // A held-back escape that starts something else is passed on
+        assert!(scanner.feed(b"\x1b").is_empty());
+        assert_eq!(scanner.feed(b"]0;title\x07"), vec![ScanItem::Output(b"\x1b]0;title\x07".to_vec())]);
+    }
+
+    #[test]
+    fn scanner_drops_aborted_sequences() {
+        let mut scanner = ImageScanner::new();
+        assert_eq!(
+            scanner.feed(b"\x1b_Gi=1;AA\x1b[0m\x1b]1337;File=x\x18ok"),
+            vec![ScanItem::Output(b"\x1b[0mok".to_vec())]
+        );
+    }
+
+    #[test]
+    fn kitty_control_data_is_parsed() {
+        let command = parse_kitty(b"a=T,f=24,s=2,v=1,i=7,c=4,m=1,q=1,C=1,z=3;AQID");
+        assert_eq!(command.action, b'T');
+        assert_eq!((command.format, command.width, command.height), (24, 2, 1));
+        assert_eq!((command.id, command.cols, command.rows), (7, 4, 0));
+        assert!(command.more && command.keep_cursor);
+        assert_eq!(command.quiet, 1);
+        assert_eq!(command.payload, b"AQID");
+    }
+
+    #[test]
+    fn iterm_arguments_are_parsed() {
+        let file = parse_iterm(b"name=YS5wbmc=;size=3;width=50%;height=10px;preserveAspectRatio=0;inline=1:AQID").unwrap();
+        assert!(file.inline);
+        assert_eq!(file.width, Dimension::Percent(50));
+        assert_eq!(file.height, Dimension::Pixels(10));
+        assert!(!file.preserve_aspect);
+        assert_eq!(file.data, vec![1, 2, 3]);
+        assert!(parse_iterm(b"inline=1").is_err());
+    }
+
+    #[test]
+    fn placement_size_follows_the_request() {
+        let cell = (10.0, 20.0);
+        let screen = (80, 24);
+        // Natural size
+        assert_eq!(placement_cells((95, 40), Dimension::Auto, Dimension::Auto, true, cell, screen), (10, 2));
+        // Width in cells keeps the aspect ratio
+        assert_eq!(placement_cells((200, 100), Dimension::Cells(10), Dimension::Auto, true, cell, screen), (10, 3));
+        // Both given: fit inside, or stretch
+        assert_eq!(placement_cells((100, 100), Dimension::Cells(10), Dimension::Cells(10), true, cell, screen), (10, 5));
+        assert_eq!(placement_cells((100, 100), Dimension::Cells(10), Dimension::Cells(10), false, cell, screen), (10, 10));
+        // Never wider than the pane
+        assert_eq!(placement_cells((1600, 400), Dimension::Auto, Dimension::Auto, true, cell, screen), (80, 10));
+        assert_eq!(placement_cells((100, 100), Dimension::Percent(50), Dimension::Auto, true, cell, screen), (40, 20));
+    }
+
+    #[test]
+    fn kitty_transmit_and_display_replies_ok() {
+        let mut store = ImageStore::new(settings(1));
+        let pixels = [255u8, 0, 0, 0, 255, 0];
+        let payload = format!("a=T,f=24,s=2,v=1,i=9,c=2;{}", base64(&pixels));
+        let outcome = store.handle_kitty(payload.as_bytes());
+        assert_eq!(outcome.reply.as_deref(), Some("\x1b_Gi=9;OK\x1b\\"));
+        let request = outcome.place.expect("placed");
+        assert_eq!(request.width, Dimension::Cells(2));
+        let image = store.image(request.image).expect("stored");
+        assert_eq!(*image.rgba, vec![255, 0, 0, 255, 0, 255, 0, 255]);
+
+        // Displayed again by id; unknown ids are an error
+        assert!(store.handle_kitty(b"a=p,i=9").place.is_some());
+        let missing = store.handle_kitty(b"a=p,i=5");
+        assert!(missing.place.is_none());
+        assert_eq!(missing.reply.as_deref(), Some("\x1b_Gi=5;ENOENT:no such image\x1b\\"));
+    }
+
+    #[test]
+    fn kitty_files_are_read_only_when_regular_and_small() {
+        let dir = std::env::temp_dir().join(format!("tide-image-files-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let read = |path: &Path, temporary: bool, limit: usize| {
+            read_image_file(path.to_str().unwrap().as_bytes(), temporary, limit)
+        };
+
+        let file = dir.join("tty-graphics-protocol-1.png");
+        std::fs::write(&file, b"0123456789").unwrap();
+        assert_eq!(read(&file, false, 10).unwrap(), b"0123456789");
+        assert!(file.exists());
+        assert!(read(&file, false, 9).unwrap_err().starts_with("EFBIG:"));
+        // Temporary files in the temporary directory are removed once read
+        assert_eq!(read(&file, true, 10).unwrap(), b"0123456789");
+        assert!(!file.exists());
+
+        // Other files are kept, even with the marker elsewhere in their path
+        let marked_dir = dir.join("tty-graphics-protocol");
+        std::fs::create_dir_all(&marked_dir).unwrap();
+        let kept = marked_dir.join("image.png");
+        std::fs::write(&kept, b"png").unwrap();
+        assert_eq!(read(&kept, true, 10).unwrap(), b"png");
+        assert!(kept.exists());
+
+        // Directories and devices are not read at all
+        assert!(read(&marked_dir, false, 10).unwrap_err().starts_with("EBADF:"));
+        #[cfg(unix)]
+        assert!(read(Path::new("/dev/zero"), false, 10).unwrap_err().starts_with("EBADF:"));
+        assert!(read(&dir.join("missing.png"), false, 10).unwrap_err().starts_with("EBADF:"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn kitty_query_and_chunks() {
+        let mut store = ImageStore::new(settings(1));
+        // Support query: checked but not stored
+        let query = format!("a=q,i=31,s=1,v=1;{}", base64(&[0, 0, 0, 0]));
+        assert_eq!(store.handle_kitty(query.as_bytes()).reply.as_deref(), Some("\x1b_Gi=31;OK\x1b\\"));
+        assert_eq!(store.memory_used(), 0);
+        assert_eq!(
+            store.handle_kitty(b"a=q,i=32,s=2,v=2;AAAA").reply.as_deref(),
+            Some("\x1b_Gi=32;ENODATA:payload does not match the image size\x1b\\")
+        );
+
+        let encoded = base64(&[1; 16]);
+        let (first, second) = encoded.split_at(8);
+        let start = format!("a=t,i=3,q=1,s=2,v=2,m=1;{first}");
+        assert_eq!(store.handle_kitty(start.as_bytes()), KittyOutcome::default());
+        let end = format!("m=0;{second}");
+        assert_eq!(store.handle_kitty(end.as_bytes()), KittyOutcome::default());
+        assert_eq!(store.memory_used(), 16);
+    }
+
+    #[test]
+    fn eviction_prefers_unplaced_then_least_recently_used() {
+        // Room for two 512x256 RGBA images
+        let mut store = ImageStore::new(settings(1));
+        let image = || InlineImage::new(512, 256, vec![0; 512 * 256 * 4]);
+        let first = store.insert(image()).unwrap();
+        let second = store.insert(image()).unwrap();
+        let first_anchor = store.place(Placement { image: first, cols: 1, rows: 1, stretch: false });
+        store.place(Placement { image: second, cols: 1, rows: 1, stretch: false });
+
+        // Both are placed: the one drawn least recently goes
+        store.lookup(first_anchor);
+        let third = store.insert(image()).unwrap();
+        assert!(store.image(first).is_some());
+        assert!(store.image(second).is_none());
+
+        // Unplaced images go before placed ones, however recent
+        store.insert(image()).unwrap();
+        assert!(store.image(third).is_none());
+        assert!(store.image(first).is_some());
+        assert_eq!(store.memory_used(), 2 * 512 * 256 * 4);
+
+        assert!(store.insert(InlineImage::new(1024, 1024, vec![0; 1024 * 1024 * 4 + 4])).is_err());
+    }
+
+    #[test]
+    fn disabled_store_ignores_images() {
+        let mut store = ImageStore::new(ImageSettings { enabled: false, memory_limit_mb: 1 });
+        assert_eq!(store.handle_kitty(b"a=q,i=1,s=1,v=1;AAAAAA=="), KittyOutcome::default());
+        assert_eq!(store.handle_iterm(b"inline=1:AAAA"), Ok(None));
+    }
+
+    #[cfg(target_os = "macos")]
+    mod grid {
+        use super::*;
+        use alacritty_terminal::event::VoidListener;
+        use alacritty_terminal::term::test::TermSize;
+        use alacritty_terminal::term::{Config, Term};
+        use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+
+        fn run(term: &mut Term<VoidListener>, store: &mut ImageStore, bytes: &[u8]) {
+            let mut scanner = ImageScanner::new();
+            let mut parser = Processor::<StdSyncHandler>::new();
+            for item in scanner.feed(bytes) {
+                match item {
+                    ScanItem::Output(output) => parser.advance(term, &output),
+                    sequence => {
+                        handle_sequence(term, store, sequence);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn images_scroll_with_the_text() {
+            let mut term = Term::new(Config::default(), &TermSize::new(20, 5), VoidListener);
+            let mut store = ImageStore::new(settings(1));
+            store.set_cell_size(10.0, 20.0);
+            let pixels = base64(&[0; 30 * 40 * 4]);
+            let image = format!("$ cat\r\n\x1b_Ga=T,s=30,v=40;{pixels}\x1b\\\r\n");
+            run(&mut term, &mut store, image.as_bytes());
+
+            // Anchored on line 1, three cells by two; the cursor ends up below it
+            let visible = visible_placements(&term, &mut store);
+            assert_eq!(visible.len(), 1);
+            assert_eq!((visible[0].line, visible[0].column), (1, 0));
+            assert_eq!((visible[0].placement.cols, visible[0].placement.rows), (3, 2));
+            assert_eq!(term.grid().cursor.point.line.0, 3);
+
+            // Scrolling output moves it up, then partly off the top
+            run(&mut term, &mut store, b"a\r\nb\r\n");
+            assert_eq!(visible_placements(&term, &mut store)[0].line, 0);
+            run(&mut term, &mut store, b"c\r\n");
+            assert_eq!(visible_placements(&term, &mut store)[0].line, -1);
+            run(&mut term, &mut store, b"d\r\n");
+            assert!(visible_placements(&term, &mut store).is_empty());
+
+            // Clearing the screen (and history) drops it
+            run(&mut term, &mut store, image.as_bytes());
+            assert_eq!(visible_placements(&term, &mut store).len(), 1);
+            run(&mut term, &mut store, b"\x1b[2J\x1b[3J");
+            assert!(visible_placements(&term, &mut store).is_empty());
+        }
+    }
+}
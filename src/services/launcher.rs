@@ -1,22 +1,224 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::value::RawValue;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fs;
-use crate::services::config::launchers_file_path;
+use std::path::{Path, PathBuf};
+use crate::services::config::{launchers_file_path, project_launchers_file_path};
+use crate::services::diagnostics::{key_offset, offset_in, Diagnostic};
+use crate::services::launcher_vars::{shell_quote, CommandTemplate};
+use crate::services::shell_profile::{ShellProfile, DEFAULT_PROFILE_NAME};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Format of `launchers.json` written by this version. Version 2 added the optional
+/// `execute`, `env`, `cwd`, `profile`, `confirm`, `icon` and `group` fields and the
+/// `new_tab` and `background` targets.
+pub const LAUNCHER_CONFIG_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum LauncherRunIn {
     Current,
     NewSplit,
+    /// A new workspace tab on the same root
+    NewTab,
+    /// A shell without a pane; the run is listed in the Tasks panel
+    Background,
+}
+
+impl LauncherRunIn {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "current" => LauncherRunIn::Current,
+            "new_split" => LauncherRunIn::NewSplit,
+            "new_tab" => LauncherRunIn::NewTab,
+            "background" => LauncherRunIn::Background,
+            _ => return None,
+        })
+    }
+}
+
+// Unknown targets run in the current pane (reported by `parse_launcher_config`)
+impl<'de> Deserialize<'de> for LauncherRunIn {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::from_name(&name).unwrap_or(LauncherRunIn::Current))
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Launcher {
     pub name: String,
     pub command: String,
     pub run_in: LauncherRunIn,
+    /// Press Enter after typing the command
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub execute: bool,
+    /// Variables set for the command
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Directory to run in, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Shell profile of the pane or tab the launcher opens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Ask before running
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub confirm: bool,
+    /// Text (usually an emoji) shown before the name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Buttons with the same group are shown together under its name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Read from the workspace's `.tide/launchers.json` rather than the global file
+    #[serde(skip)]
+    pub from_project: bool,
+    /// Fields this Tide does not know, written back as they were read
+    #[serde(flatten, skip_deserializing)]
+    pub unknown_fields: BTreeMap<String, Value>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl Launcher {
+    /// A launcher typing `command` into the focused pane, with every optional field unset.
+    pub fn new(name: &str, command: &str, run_in: LauncherRunIn) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            run_in,
+            execute: false,
+            env: BTreeMap::new(),
+            cwd: None,
+            profile: None,
+            confirm: false,
+            icon: None,
+            group: None,
+            from_project: false,
+            unknown_fields: BTreeMap::new(),
+        }
+    }
+
+    /// Oldest `launchers.json` version that has every field and target this launcher uses.
+    pub fn format_version(&self) -> u32 {
+        let version_2 = self.execute
+            || !self.env.is_empty()
+            || self.cwd.is_some()
+            || self.profile.is_some()
+            || self.confirm
+            || self.icon.is_some()
+            || self.group.is_some()
+            || matches!(self.run_in, LauncherRunIn::NewTab | LauncherRunIn::Background);
+        if version_2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Button text: the icon, if any, and the name.
+    pub fn label(&self) -> String {
+        match self.icon.as_deref().map(str::trim) {
+            Some(icon) if !icon.is_empty() => format!("{icon} {}", self.name),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// Directory the command runs in.
+    pub fn working_dir(&self, workspace_root: &Path) -> PathBuf {
+        match self.cwd.as_deref().map(str::trim) {
+            None | Some("") => workspace_root.to_path_buf(),
+            Some(dir) => workspace_root.join(dir),
+        }
+    }
+
+    /// What is typed into a pane for the (already expanded) `command`, and Enter when
+    /// `execute` is set. With a `cwd` or `env` the command runs in a subshell that `cd`s
+    /// into `cwd` and exports `env`, so the pane's shell keeps its directory and
+    /// variables and every command of a pipeline or list sees them.
+    pub fn terminal_input(&self, command: &str, workspace_root: &Path) -> String {
+        let mut setup = Vec::new();
+        if self.cwd.as_deref().is_some_and(|dir| !dir.trim().is_empty()) {
+            let dir = self.working_dir(workspace_root);
+            setup.push(format!("cd {}", shell_quote(&dir.to_string_lossy())));
+        }
+        if !self.env.is_empty() {
+            let assignments: Vec<String> =
+                self.env.iter().map(|(name, value)| format!("{name}={}", shell_quote(value))).collect();
+            setup.push(format!("export {}", assignments.join(" ")));
+        }
+        let mut input = if setup.is_empty() {
+            command.to_string()
+        } else {
+            format!("({} && {command})", setup.join(" && "))
+        };
+        if self.execute {
+            input.push('\r');
+        }
+        input
+    }
+
+    /// Whether the launcher starts a shell of its own, with `profile`.
+    pub fn opens_shell(&self) -> bool {
+        matches!(self.run_in, LauncherRunIn::NewSplit | LauncherRunIn::NewTab)
+    }
+
+    /// Profile that runs a `background` launcher's (already expanded) `command` in a
+    /// login shell and exits with it: `$SHELL -l -c command` in `cwd`, with `env`.
+    pub fn background_profile(&self, command: &str, workspace_root: &Path) -> ShellProfile {
+        ShellProfile {
+            name: self.name.clone(),
+            args: vec!["-c".to_string(), command.to_string()],
+            env: self.env.clone(),
+            cwd: Some(self.working_dir(workspace_root).to_string_lossy().into_owned()),
+            ..ShellProfile::default()
+        }
+    }
+
+    /// Problems with this launcher, given the launchers listed before it: a command
+    /// template that does not parse, and (fatal) an empty or duplicate name or an
+    /// invalid environment variable name.
+    pub fn problems(&self, earlier: &[Launcher]) -> Vec<LauncherProblem> {
+        let mut problems = Vec::new();
+        let mut add = |field, message: String, fatal| problems.push(LauncherProblem { field, message, fatal });
+        if let Err(err) = CommandTemplate::parse(&self.command) {
+            add("command", err.to_string(), false);
+        }
+        if self.name.trim().is_empty() {
+            add("name", "the name is empty".to_string(), true);
+        } else if earlier.iter().any(|other| other.name == self.name) {
+            add("name", format!("another launcher is already named \"{}\"", self.name), true);
+        }
+        if let Some(name) = self.env.keys().find(|name| !is_env_name(name)) {
+            add("env", format!("\"{name}\" is not a valid environment variable name"), true);
+        }
+        problems
+    }
+}
+
+/// A `profile` named by a launcher that opens a shell, with the problem to report if
+/// the settings have no such profile. Checked against the settings when they change,
+/// without reading the file again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileUse {
+    pub name: String,
+    pub unknown: Diagnostic,
+}
+
+/// A problem with one field of a launcher, found when loading or editing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LauncherProblem {
+    /// Field the problem is in (`name`, `command`, `env`)
+    pub field: &'static str,
+    pub message: String,
+    /// The launcher cannot be used and is skipped when the file is loaded
+    pub fatal: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LauncherConfig {
     pub version: u32,
     pub launchers: Vec<Launcher>,
@@ -25,42 +227,734 @@ pub struct LauncherConfig {
 impl Default for LauncherConfig {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: LAUNCHER_CONFIG_VERSION,
             launchers: vec![
-                Launcher {
-                    name: "Claude".to_string(),
-                    command: "claude".to_string(),
-                    run_in: LauncherRunIn::Current,
-                },
-                Launcher {
-                    name: "Gemini".to_string(),
-                    command: "gemini".to_string(),
-                    run_in: LauncherRunIn::Current,
-                },
+                Launcher::new("Claude", "claude", LauncherRunIn::Current),
+                Launcher::new("Gemini", "gemini", LauncherRunIn::Current),
             ],
         }
     }
 }
 
-pub fn load_launchers() -> Vec<Launcher> {
-    let path = launchers_file_path();
-    if !path.exists() {
-        // Return default launchers if file doesn't exist, 
-        // effectively providing a template for the user
-        return LauncherConfig::default().launchers;
+/// Fields of a launcher entry; others are reported as unknown.
+const LAUNCHER_FIELDS: &[&str] = &[
+    "name", "command", "run_in", "execute", "env", "cwd", "profile", "confirm", "icon", "group",
+];
+
+/// Launchers read from one file, with the problems found in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LauncherFile {
+    pub path: PathBuf,
+    pub config: LauncherConfig,
+    /// Entries that were skipped or repaired, and why
+    pub diagnostics: Vec<Diagnostic>,
+    /// The file could not be read or parsed at all, so it has no launchers of its own
+    pub failed: bool,
+    pub profile_uses: Vec<ProfileUse>,
+}
+
+impl LauncherFile {
+    fn read(path: PathBuf) -> Option<Self> {
+        let (config, diagnostics, profile_uses, failed) = match fs::read_to_string(&path) {
+            Ok(content) => match parse_launcher_file(&content) {
+                Ok((config, diagnostics, profile_uses)) => (config, diagnostics, profile_uses, false),
+                Err(diagnostic) => (empty_config(), vec![diagnostic], Vec::new(), true),
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => (
+                empty_config(),
+                vec![Diagnostic { line: 1, column: 1, message: format!("cannot read the file: {err}") }],
+                Vec::new(),
+                true,
+            ),
+        };
+        for diagnostic in &diagnostics {
+            crate::logging::log_line("WARN", &format!("{}:{diagnostic}", path.display()));
+        }
+        Some(Self { path, config, diagnostics, failed, profile_uses })
+    }
+
+    /// Launchers naming a profile that is not in `profiles`; they start the default shell.
+    pub fn unknown_profiles(&self, profiles: &[ShellProfile]) -> Vec<Diagnostic> {
+        self.profile_uses
+            .iter()
+            .filter(|used| used.name != DEFAULT_PROFILE_NAME && !profiles.iter().any(|p| p.name == used.name))
+            .map(|used| used.unknown.clone())
+            .collect()
     }
 
-    match fs::read_to_string(&path) {
-        Ok(content) => match serde_json::from_str::<LauncherConfig>(&content) {
-            Ok(config) => config.launchers,
-            Err(err) => {
-                eprintln!("Failed to parse launchers file: {}", err);
-                LauncherConfig::default().launchers
+    /// This file, or the launchers of `previous` when this one failed to parse, so a
+    /// half-saved edit does not take every launcher away. The new problems are kept.
+    pub fn or_previous(self, previous: &LauncherFile) -> LauncherFile {
+        if !self.failed || previous.path != self.path {
+            return self;
+        }
+        LauncherFile { config: previous.config.clone(), profile_uses: previous.profile_uses.clone(), ..self }
+    }
+}
+
+fn empty_config() -> LauncherConfig {
+    LauncherConfig { version: LAUNCHER_CONFIG_VERSION, launchers: Vec::new() }
+}
+
+#[derive(Deserialize)]
+struct RawLauncherConfig<'a> {
+    #[serde(default)]
+    version: Option<Value>,
+    #[serde(borrow)]
+    launchers: Vec<&'a RawValue>,
+}
+
+/// Parse `launchers.json`, upgrading older formats to `LAUNCHER_CONFIG_VERSION`. Bad
+/// entries are skipped and reported instead of failing the whole file; an unknown
+/// `run_in` runs in the current pane. A file that is not JSON at all has no launchers.
+pub fn parse_launcher_config(content: &str) -> (LauncherConfig, Vec<Diagnostic>) {
+    match parse_launcher_file(content) {
+        Ok((config, diagnostics, _)) => (config, diagnostics),
+        Err(diagnostic) => (empty_config(), vec![diagnostic]),
+    }
+}
+
+/// Like `parse_launcher_config`, but a file that cannot be read as a whole is an error.
+/// Also returns the profiles the launchers name.
+fn parse_launcher_file(content: &str) -> Result<(LauncherConfig, Vec<Diagnostic>, Vec<ProfileUse>), Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut profile_uses = Vec::new();
+    let mut config = empty_config();
+
+    let root: &RawValue =
+        serde_json::from_str(content).map_err(|err| Diagnostic::from_serde(content, content, &err))?;
+    let entries: Vec<&RawValue> = if root.get().starts_with('[') {
+        // Early files were a bare list of launchers
+        serde_json::from_str(root.get()).unwrap_or_default()
+    } else {
+        match serde_json::from_str::<RawLauncherConfig>(root.get()) {
+            Ok(raw) => {
+                check_version(content, root, raw.version.as_ref(), &mut diagnostics);
+                raw.launchers
             }
-        },
+            Err(err) => return Err(Diagnostic::from_serde(content, root.get(), &err)),
+        }
+    };
+
+    for entry in entries {
+        if let Some(launcher) = parse_entry(content, entry, &config.launchers, &mut diagnostics) {
+            if let Some(name) = launcher.profile.clone().filter(|_| launcher.opens_shell()) {
+                let at = offset_in(content, entry.get()) + key_offset(entry, "profile").unwrap_or(0);
+                let message = format!("no shell profile \"{name}\" in settings.json; the default shell is used");
+                profile_uses.push(ProfileUse { name, unknown: Diagnostic::at(content, at, message) });
+            }
+            config.launchers.push(launcher);
+        }
+    }
+    Ok((config, diagnostics, profile_uses))
+}
+
+fn check_version(content: &str, root: &RawValue, version: Option<&Value>, diagnostics: &mut Vec<Diagnostic>) {
+    let at = offset_in(content, root.get()) + key_offset(root, "version").unwrap_or(0);
+    let message = match version.map(|version| (version, version.as_u64())) {
+        // Version 1 entries lack the optional fields, which serde fills with their defaults
+        Some((_, Some(version))) if (1..=LAUNCHER_CONFIG_VERSION as u64).contains(&version) => return,
+        Some((_, Some(version))) if version > LAUNCHER_CONFIG_VERSION as u64 => format!(
+            "version {version} is newer than this Tide reads ({LAUNCHER_CONFIG_VERSION}); read as version {LAUNCHER_CONFIG_VERSION}"
+        ),
+        Some((version, _)) => format!("unknown version {version}; read as version {LAUNCHER_CONFIG_VERSION}"),
+        None => format!("no \"version\"; read as version {LAUNCHER_CONFIG_VERSION}"),
+    };
+    diagnostics.push(Diagnostic::at(content, at, message));
+}
+
+/// One entry of the `launchers` list, or `None` (with a diagnostic) when it is skipped.
+fn parse_entry(
+    content: &str,
+    entry: &RawValue,
+    earlier: &[Launcher],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Launcher> {
+    let start = offset_in(content, entry.get());
+    let field_at = |key: &str| start + key_offset(entry, key).unwrap_or(0);
+
+    let mut launcher: Launcher = match serde_json::from_str(entry.get()) {
+        Ok(launcher) => launcher,
         Err(err) => {
-            eprintln!("Failed to read launchers file: {}", err);
-            LauncherConfig::default().launchers
+            let mut diagnostic = Diagnostic::from_serde(content, entry.get(), &err);
+            if diagnostic.message.starts_with("missing field") {
+                // serde points past the entry's end; point at its start instead
+                diagnostic = Diagnostic::at(content, start, diagnostic.message);
+            }
+            diagnostic.message.push_str("; launcher skipped");
+            diagnostics.push(diagnostic);
+            return None;
+        }
+    };
+
+    // Fields serde accepted but that are probably mistakes
+    let fields: BTreeMap<String, Value> = serde_json::from_str(entry.get()).unwrap_or_default();
+    for (key, value) in fields.iter().filter(|(key, _)| !LAUNCHER_FIELDS.contains(&key.as_str())) {
+        diagnostics.push(Diagnostic::at(content, field_at(key), format!("unknown field \"{key}\" is ignored")));
+        launcher.unknown_fields.insert(key.clone(), value.clone());
+    }
+    if let Some(Value::String(run_in)) = fields.get("run_in") {
+        if LauncherRunIn::from_name(run_in).is_none() {
+            diagnostics.push(Diagnostic::at(
+                content,
+                field_at("run_in"),
+                format!("unknown run_in \"{run_in}\"; running in the current pane"),
+            ));
+        }
+    }
+    if launcher.profile.is_some() && !launcher.opens_shell() {
+        diagnostics.push(Diagnostic::at(
+            content,
+            field_at("profile"),
+            "\"profile\" is only used by new_split and new_tab launchers; ignored",
+        ));
+    }
+    let mut skip = None;
+    for problem in launcher.problems(earlier) {
+        let at = field_at(problem.field);
+        if !problem.fatal {
+            diagnostics.push(Diagnostic::at(
+                content,
+                at,
+                format!("{}; the launcher will fail when clicked", problem.message),
+            ));
+        } else if skip.is_none() {
+            skip = Some((at, problem.message));
+        }
+    }
+    match skip {
+        Some((at, reason)) => {
+            diagnostics.push(Diagnostic::at(content, at, format!("{reason}; launcher skipped")));
+            None
+        }
+        None => Some(launcher),
+    }
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The global launchers file; the built-in launchers when it does not exist.
+pub fn load_launchers() -> LauncherFile {
+    let path = launchers_file_path();
+    LauncherFile::read(path.clone()).unwrap_or_else(|| {
+        // Return default launchers if file doesn't exist,
+        // effectively providing a template for the user
+        LauncherFile {
+            path,
+            config: LauncherConfig::default(),
+            diagnostics: Vec::new(),
+            failed: false,
+            profile_uses: Vec::new(),
+        }
+    })
+}
+
+/// Launchers in `<workspace_root>/.tide/launchers.json`; none when the file is missing.
+pub fn load_project_launchers(workspace_root: &Path) -> LauncherFile {
+    let path = project_launchers_file_path(workspace_root);
+    let mut file = LauncherFile::read(path.clone()).unwrap_or_else(|| LauncherFile {
+        path,
+        config: empty_config(),
+        diagnostics: Vec::new(),
+        failed: false,
+        profile_uses: Vec::new(),
+    });
+    for launcher in &mut file.config.launchers {
+        launcher.from_project = true;
+    }
+    file
+}
+
+/// `launchers.json` as `save_launchers` writes it. Other top-level fields of the file
+/// are kept after the launchers.
+#[derive(Serialize)]
+struct SavedLauncherConfig<'a> {
+    version: u32,
+    launchers: &'a [Launcher],
+    #[serde(flatten)]
+    other: serde_json::Map<String, Value>,
+}
+
+/// The file at `path` as JSON, and its version: 1 for a bare list, `None` when it is
+/// missing, unreadable or has no usable `version`.
+fn existing_launcher_file(path: &Path) -> (Option<Value>, Option<u32>) {
+    let root = fs::read_to_string(path).ok().and_then(|content| serde_json::from_str::<Value>(&content).ok());
+    let version = match &root {
+        Some(Value::Array(_)) => Some(1),
+        Some(root) => root.get("version").and_then(Value::as_u64).and_then(|version| u32::try_from(version).ok()),
+        None => None,
+    };
+    (root, version)
+}
+
+/// The version the file at `path` has and the one saving `launchers` to it would
+/// write, when saving would upgrade the file.
+pub fn launchers_upgrade(path: &Path, launchers: &[Launcher]) -> Option<(u32, u32)> {
+    let (_, existing) = existing_launcher_file(path);
+    let existing = existing?;
+    let needed = launchers.iter().map(Launcher::format_version).max().unwrap_or(1);
+    (needed > existing).then_some((existing, needed))
+}
+
+/// Write `launchers` to `path` as pretty JSON. The file keeps its `version` unless the
+/// launchers use fields of a newer one (see `launchers_upgrade`); new files get
+/// `LAUNCHER_CONFIG_VERSION`. Unknown fields of the file and of its launchers are kept.
+pub fn save_launchers(path: &Path, launchers: &[Launcher]) -> std::io::Result<()> {
+    let (root, existing) = existing_launcher_file(path);
+    let needed = launchers.iter().map(Launcher::format_version).max().unwrap_or(1);
+    let mut other = match root {
+        Some(Value::Object(root)) => root,
+        _ => serde_json::Map::new(),
+    };
+    other.remove("version");
+    other.remove("launchers");
+    let config = SavedLauncherConfig {
+        version: existing.unwrap_or(LAUNCHER_CONFIG_VERSION).max(needed),
+        launchers,
+        other,
+    };
+    let content = serde_json::to_string_pretty(&config)?;
+    fs::write(path, content)
+}
+
+/// The global launchers with the project's merged in: a project launcher takes the
+/// place of the global one with the same name, the others follow the global list.
+/// A cloned repository should not run its commands on a click the user meant for their
+/// own launcher, so project launchers that replace a global one or press Enter
+/// themselves ask first.
+pub fn merge_launchers(global: Vec<Launcher>, project: Vec<Launcher>) -> Vec<Launcher> {
+    let mut merged = global;
+    for mut launcher in project {
+        let existing = merged.iter().position(|existing| existing.name == launcher.name);
+        launcher.confirm |= existing.is_some() || launcher.execute;
+        match existing {
+            Some(index) => merged[index] = launcher,
+            None => merged.push(launcher),
+        }
+    }
+    merged
+}
+
+/// What changed between two launcher lists, by name, for the log: `added Build`,
+/// `removed Claude`, `changed Gemini`, or `reordered`. Empty when the lists hold the same launchers.
+pub fn launcher_changes(old: &[Launcher], new: &[Launcher]) -> Vec<String> {
+    let find = |list: &'_ [Launcher], name: &str| list.iter().position(|launcher| launcher.name == name);
+    let mut changes = Vec::new();
+    for launcher in new {
+        match find(old, &launcher.name) {
+            None => changes.push(format!("added {}", launcher.name)),
+            Some(at) if old[at] != *launcher => changes.push(format!("changed {}", launcher.name)),
+            Some(_) => {}
+        }
+    }
+    for launcher in old {
+        if find(new, &launcher.name).is_none() {
+            changes.push(format!("removed {}", launcher.name));
         }
     }
+    if changes.is_empty() && old != new {
+        changes.push("reordered".to_string());
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse `content` that should have no problems.
+    fn parse_clean(content: &str) -> LauncherConfig {
+        let (config, diagnostics) = parse_launcher_config(content);
+        assert_eq!(diagnostics, Vec::new());
+        config
+    }
+
+    fn names(config: &LauncherConfig) -> Vec<&str> {
+        config.launchers.iter().map(|launcher| launcher.name.as_str()).collect()
+    }
+
+    /// `(line, column, start of message)` of each diagnostic.
+    fn check(diagnostics: &[Diagnostic], expected: &[(usize, usize, &str)]) {
+        assert_eq!(diagnostics.len(), expected.len(), "{diagnostics:?}");
+        for (diagnostic, (line, column, message)) in diagnostics.iter().zip(expected) {
+            assert_eq!((diagnostic.line, diagnostic.column), (*line, *column), "{diagnostic}");
+            assert!(diagnostic.message.starts_with(message), "{diagnostic}");
+        }
+    }
+
+    #[test]
+    fn version_1_files_still_load() {
+        let config = parse_clean(
+            r#"{ "version": 1, "launchers": [{ "name": "Claude", "command": "claude", "run_in": "current" }] }"#,
+        );
+        assert_eq!(config.version, LAUNCHER_CONFIG_VERSION);
+        assert_eq!(config.launchers, vec![Launcher::new("Claude", "claude", LauncherRunIn::Current)]);
+
+        let bare = parse_clean(r#"[{ "name": "Top", "command": "top", "run_in": "new_split" }]"#);
+        assert_eq!(bare.launchers, vec![Launcher::new("Top", "top", LauncherRunIn::NewSplit)]);
+        assert!(parse_clean("[]").launchers.is_empty());
+    }
+
+    #[test]
+    fn version_2_fields_round_trip() {
+        let content = r#"{
+            "version": 2,
+            "launchers": [{
+                "name": "Serve", "command": "npm run dev", "run_in": "new_tab",
+                "execute": true, "env": { "PORT": "3000" }, "cwd": "web",
+                "profile": "nix develop", "confirm": true, "icon": "🚀", "group": "Web"
+            }]
+        }"#;
+        let config = parse_clean(content);
+        let launcher = &config.launchers[0];
+        assert_eq!(launcher.run_in, LauncherRunIn::NewTab);
+        assert!(launcher.execute && launcher.confirm);
+        assert_eq!(launcher.label(), "🚀 Serve");
+        assert_eq!(launcher.working_dir(Path::new("/src/app")), PathBuf::from("/src/app/web"));
+
+        let written = serde_json::to_string(&config).unwrap();
+        assert_eq!(parse_clean(&written), config);
+        // Unset fields are left out
+        let plain = serde_json::to_value(Launcher::new("Claude", "claude", LauncherRunIn::Current)).unwrap();
+        assert_eq!(plain, serde_json::json!({ "name": "Claude", "command": "claude", "run_in": "current" }));
+    }
+
+    #[test]
+    fn missing_file_uses_the_built_in_launchers() {
+        let path = std::env::temp_dir().join(format!("tide-launchers-missing-{}.json", std::process::id()));
+        assert_eq!(LauncherFile::read(path), None);
+        assert_eq!(names(&LauncherConfig::default()), vec!["Claude", "Gemini"]);
+    }
+
+    #[test]
+    fn unknown_run_in_runs_in_the_current_pane() {
+        let (config, diagnostics) = parse_launcher_config(
+            r#"{
+  "version": 2,
+  "launchers": [
+    { "name": "Logs", "command": "tail -f log", "run_in": "floating_window" }
+  ]
+}"#,
+        );
+        assert_eq!(config.launchers[0].run_in, LauncherRunIn::Current);
+        check(&diagnostics, &[(4, 49, "unknown run_in \"floating_window\"; running in the current pane")]);
+    }
+
+    #[test]
+    fn profiles_are_checked() {
+        let content = r#"{
+  "version": 2,
+  "launchers": [
+    { "name": "Here", "command": "ls", "run_in": "current", "profile": "zsh" },
+    { "name": "Split", "command": "ls", "run_in": "new_split", "profile": "nix develop" },
+    { "name": "Tab", "command": "ls", "run_in": "new_tab", "profile": "Default" }
+  ]
+}"#;
+        let (config, diagnostics) = parse_launcher_config(content);
+        assert_eq!(config.launchers.len(), 3);
+        check(&diagnostics, &[(4, 61, "\"profile\" is only used by new_split and new_tab launchers; ignored")]);
+
+        let (_, _, profile_uses) = parse_launcher_file(content).unwrap();
+        let file = LauncherFile {
+            path: PathBuf::from("launchers.json"),
+            config,
+            diagnostics,
+            failed: false,
+            profile_uses,
+        };
+        check(
+            &file.unknown_profiles(&[]),
+            &[(5, 64, "no shell profile \"nix develop\" in settings.json; the default shell is used")],
+        );
+        let nix = ShellProfile { name: "nix develop".to_string(), ..ShellProfile::default() };
+        assert_eq!(file.unknown_profiles(&[nix]), Vec::new());
+    }
+
+    #[test]
+    fn bad_entries_are_skipped_and_the_rest_kept() {
+        let (config, diagnostics) = parse_launcher_config(
+            r#"{
+  "version": 2,
+  "launchers": [
+    { "name": "Claude", "command": "claude", "run_in": "current" },
+    { "name": "No command", "run_in": "current" },
+    { "name": "Typed", "command": 42, "run_in": "current" },
+    { "name": "Claude", "command": "claude again", "run_in": "current" },
+    { "name": " ", "command": "ls", "run_in": "current" },
+    { "name": "Env", "command": "env", "run_in": "current", "env": { "NOT-A-NAME": "x" } },
+    { "name": "Typo", "command": "make", "run_in": "current", "exeucte": true },
+    { "name": "Grep", "command": "rg ${nope}", "run_in": "current" },
+    "not an object"
+  ]
+}"#,
+        );
+        assert_eq!(names(&config), vec!["Claude", "Typo", "Grep"]);
+        check(
+            &diagnostics,
+            &[
+                (5, 5, "missing field `command`; launcher skipped"),
+                (6, 36, "invalid type: integer `42`, expected a string; launcher skipped"),
+                (7, 7, "another launcher is already named \"Claude\"; launcher skipped"),
+                (8, 7, "the name is empty; launcher skipped"),
+                (9, 61, "\"NOT-A-NAME\" is not a valid environment variable name; launcher skipped"),
+                (10, 63, "unknown field \"exeucte\" is ignored"),
+                (11, 23, "unknown variable '${nope}'; the launcher will fail when clicked"),
+                (12, 19, "invalid type: string \"not an object\""),
+            ],
+        );
+    }
+
+    #[test]
+    fn unknown_versions_are_read_as_the_current_one() {
+        let (config, diagnostics) = parse_launcher_config(
+            r#"{ "version": 7, "launchers": [{ "name": "Claude", "command": "claude", "run_in": "current" }] }"#,
+        );
+        assert_eq!(names(&config), vec!["Claude"]);
+        check(&diagnostics, &[(1, 3, "version 7 is newer than this Tide reads")]);
+
+        let (config, diagnostics) = parse_launcher_config(r#"{ "version": "two", "launchers": [] }"#);
+        assert!(config.launchers.is_empty());
+        check(&diagnostics, &[(1, 3, "unknown version \"two\"")]);
+
+        // Not readable in any version: no launchers
+        let (config, diagnostics) = parse_launcher_config(r#"{ "version": 9, "items": [] }"#);
+        assert!(config.launchers.is_empty());
+        check(&diagnostics, &[(1, 29, "missing field `launchers`")]);
+    }
+
+    #[test]
+    fn broken_json_has_no_launchers() {
+        let (config, diagnostics) = parse_launcher_config("{\n  \"version\": 2,\n  \"launchers\": [\n    { \"name\": \"x\" \n");
+        assert!(config.launchers.is_empty());
+        check(&diagnostics, &[(5, 1, "EOF while parsing an object")]);
+    }
+
+    #[test]
+    fn project_launchers_override_by_name() {
+        let root = std::env::temp_dir().join(format!("tide-launchers-project-{}", std::process::id()));
+        fs::create_dir_all(root.join(".tide")).unwrap();
+        fs::write(
+            project_launchers_file_path(&root),
+            r#"{ "version": 2, "launchers": [
+                { "name": "Storybook", "command": "npm run storybook", "run_in": "new_tab" },
+                { "name": "Claude", "command": "claude --model opus", "run_in": "current" },
+                { "name": "Test", "command": "cargo test", "run_in": "current", "execute": true }
+            ] }"#,
+        )
+        .unwrap();
+
+        let project = load_project_launchers(&root).config.launchers;
+        assert!(project.iter().all(|launcher| launcher.from_project));
+        let merged = merge_launchers(LauncherConfig::default().launchers, project);
+        let summary: Vec<_> = merged
+            .iter()
+            .map(|launcher| (launcher.name.as_str(), launcher.command.as_str(), launcher.from_project))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Claude", "claude --model opus", true),
+                ("Gemini", "gemini", false),
+                ("Storybook", "npm run storybook", true),
+                ("Test", "cargo test", true),
+            ]
+        );
+        // Replacing a global launcher or pressing Enter asks first
+        let confirm: Vec<_> = merged.iter().map(|launcher| launcher.confirm).collect();
+        assert_eq!(confirm, [true, false, false, true]);
+
+        // A broken project file adds nothing, and says why
+        fs::write(project_launchers_file_path(&root), "{ not json").unwrap();
+        let broken = load_project_launchers(&root);
+        assert!(broken.config.launchers.is_empty());
+        check(&broken.diagnostics, &[(1, 3, "key must be a string")]);
+        assert_eq!(load_project_launchers(&root.join("missing")).diagnostics, Vec::new());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn a_failed_reload_keeps_the_previous_launchers() {
+        let root = std::env::temp_dir().join(format!("tide-launchers-reload-{}", std::process::id()));
+        fs::create_dir_all(root.join(".tide")).unwrap();
+        let path = project_launchers_file_path(&root);
+        fs::write(
+            &path,
+            r#"{ "version": 2, "launchers": [{ "name": "Test", "command": "cargo test", "run_in": "current" }] }"#,
+        )
+        .unwrap();
+        let good = load_project_launchers(&root);
+        assert!(!good.failed);
+        assert_eq!(good.diagnostics, Vec::new());
+        assert_eq!(names(&good.config), ["Test"]);
+
+        fs::write(&path, r#"{ "version": 2, "launchers": [{ "name": "Test", "#).unwrap();
+        let reloaded = load_project_launchers(&root).or_previous(&good);
+        assert!(reloaded.failed);
+        assert_eq!(names(&reloaded.config), ["Test"]);
+        assert_eq!(reloaded.config.launchers[0].command, "cargo test");
+        assert_eq!(reloaded.diagnostics.len(), 1);
+
+        // Skipped entries are not a failure: the rest of the file is the new config
+        fs::write(&path, r#"{ "version": 2, "launchers": [{ "name": "Test" }] }"#).unwrap();
+        let reloaded = load_project_launchers(&root).or_previous(&good);
+        assert!(!reloaded.failed);
+        assert!(reloaded.config.launchers.is_empty());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn launcher_changes_are_listed_by_name() {
+        let old = LauncherConfig::default().launchers;
+        assert_eq!(launcher_changes(&old, &old), Vec::<String>::new());
+
+        let mut new = old.clone();
+        new[1].command = "gemini --yolo".to_string();
+        new.remove(0);
+        new.push(Launcher::new("Build", "cargo build", LauncherRunIn::Background));
+        assert_eq!(launcher_changes(&old, &new), ["changed Gemini", "added Build", "removed Claude"]);
+
+        let reversed: Vec<_> = old.iter().rev().cloned().collect();
+        assert_eq!(launcher_changes(&old, &reversed), ["reordered"]);
+    }
+
+    #[test]
+    fn saved_launchers_read_back_and_keep_their_version() {
+        let path = std::env::temp_dir().join(format!("tide-launchers-save-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut launchers = LauncherConfig::default().launchers;
+
+        // New files are written as the current version, old ones keep theirs
+        save_launchers(&path, &launchers).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("{\n  \"version\": 2,\n  \"launchers\": [\n"), "{content}");
+        fs::write(&path, r#"{ "version": 1, "launchers": [] }"#).unwrap();
+        assert_eq!(launchers_upgrade(&path, &launchers), None);
+        save_launchers(&path, &launchers).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("{\n  \"version\": 1,\n  \"launchers\": [\n"), "{content}");
+        assert_eq!(parse_clean(&content).launchers, launchers);
+
+        // Version 2 fields need a version 2 file
+        launchers[0].env.insert("TERM".to_string(), "xterm".to_string());
+        launchers[1].group = Some("AI".to_string());
+        assert_eq!(launchers_upgrade(&path, &launchers), Some((1, 2)));
+        save_launchers(&path, &launchers).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("{\n  \"version\": 2,\n  \"launchers\": [\n"), "{content}");
+        assert_eq!(parse_clean(&content).launchers, launchers);
+        assert_eq!(launchers_upgrade(&path, &launchers), None);
+
+        fs::write(&path, r#"{ "version": 5, "launchers": [] }"#).unwrap();
+        save_launchers(&path, &launchers).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("\"version\": 5"), "{content}");
+        assert_eq!(parse_launcher_config(&content).0.launchers, launchers);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn load_and_save_keep_the_version_and_unknown_fields() {
+        let path = std::env::temp_dir().join(format!("tide-launchers-round-trip-{}.json", std::process::id()));
+        let content = r#"{
+  "version": 1,
+  "launchers": [
+    {
+      "name": "Claude",
+      "command": "claude",
+      "run_in": "current",
+      "shortcut": "cmd-1"
+    }
+  ],
+  "theme": {
+    "compact": true
+  }
+}"#;
+        fs::write(&path, content).unwrap();
+        let file = LauncherFile::read(path.clone()).unwrap();
+        check(&file.diagnostics, &[(8, 7, "unknown field \"shortcut\" is ignored")]);
+        assert_eq!(file.config.launchers[0].unknown_fields["shortcut"], "cmd-1");
+
+        save_launchers(&path, &file.config.launchers).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        // Edited launchers keep their unknown fields too
+        let mut launchers = file.config.launchers;
+        launchers[0].command = "claude --continue".to_string();
+        save_launchers(&path, &launchers).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        assert_eq!(saved, content.replace("\"claude\",", "\"claude --continue\","));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn problems_are_reported_by_field() {
+        let claude = Launcher::new("Claude", "claude ${nope}", LauncherRunIn::Current);
+        let problems = claude.problems(&[]);
+        assert_eq!(problems.len(), 1);
+        assert_eq!((problems[0].field, problems[0].fatal), ("command", false));
+
+        let mut copy = Launcher::new("Claude", "claude", LauncherRunIn::Current);
+        copy.env.insert("1PORT".to_string(), "3000".to_string());
+        let fields: Vec<_> = copy.problems(&[claude]).into_iter().map(|problem| (problem.field, problem.fatal)).collect();
+        assert_eq!(fields, vec![("name", true), ("env", true)]);
+        assert!(Launcher::new(" ", "x", LauncherRunIn::Current).problems(&[])[0].message.contains("empty"));
+    }
+
+    #[test]
+    fn terminal_input_adds_cwd_env_and_enter() {
+        let root = Path::new("/src/my app");
+        let mut launcher = Launcher::new("Test", "cargo test", LauncherRunIn::Current);
+        assert_eq!(launcher.terminal_input("cargo test", root), "cargo test");
+
+        launcher.execute = true;
+        launcher.cwd = Some("crates/core".to_string());
+        launcher.env.insert("RUST_LOG".to_string(), "debug,hyper=info".to_string());
+        launcher.env.insert("GREETING".to_string(), "hello world".to_string());
+        assert_eq!(
+            launcher.terminal_input("cargo test", root),
+            "(cd '/src/my app/crates/core' && export GREETING='hello world' RUST_LOG=debug,hyper=info && cargo test)\r"
+        );
+
+        launcher.cwd = None;
+        launcher.env.remove("GREETING");
+        assert_eq!(
+            launcher.terminal_input("cargo build | tee log", root),
+            "(export RUST_LOG=debug,hyper=info && cargo build | tee log)\r"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminal_input_leaves_the_shell_as_it_was() {
+        let root = std::env::temp_dir().canonicalize().unwrap();
+        let mut launcher = Launcher::new("Env", "", LauncherRunIn::Current);
+        launcher.cwd = Some(".".to_string());
+        launcher.env.insert("TIDE_GREETING".to_string(), "hello world".to_string());
+
+        let input = launcher.terminal_input("echo \"$TIDE_GREETING\" | cat; pwd", &root);
+        let script = format!("{input}; pwd; echo \"${{TIDE_GREETING:-unset}}\"");
+        let output = std::process::Command::new("sh").arg("-c").arg(script).current_dir("/").output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), format!("hello world\n{}\n/\nunset\n", root.display()));
+    }
+
+    #[test]
+    fn background_profile_runs_the_command_in_a_login_shell() {
+        let root = Path::new("/src/app");
+        let mut launcher = Launcher::new("Watch", "npm run watch", LauncherRunIn::Background);
+        launcher.cwd = Some("web".to_string());
+        launcher.env.insert("NODE_ENV".to_string(), "development".to_string());
+
+        let profile = launcher.background_profile("npm run watch", root);
+        assert!(!profile.is_default_shell());
+        assert!(profile.is_login());
+        assert_eq!(profile.resolved_program(), None);
+        assert_eq!(profile.args, ["-c", "npm run watch"]);
+        assert_eq!(profile.startup_dir(root), Path::new("/src/app/web"));
+        assert_eq!(profile.env.get("NODE_ENV").map(String::as_str), Some("development"));
+    }
 }
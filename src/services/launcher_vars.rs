@@ -0,0 +1,279 @@
+//! Placeholders in launcher commands, resolved when the launcher runs.
+//!
+//! `${workspaceRoot}`, `${paneCwd}`, `${file}`, `${relativeFile}`, `${selectedText}`,
+//! `${gitBranch}`, `${env:NAME}` and `${input:Prompt}` are replaced by their value,
+//! shell-quoted, so `rg ${selectedText} ${workspaceRoot}` works for any text and path.
+//! `$${` writes a literal `${`. A placeholder without a value stops the launcher with an
+//! error instead of typing half a command.
+
+use std::fmt;
+
+/// A `${…}` placeholder.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Placeholder {
+    WorkspaceRoot,
+    PaneCwd,
+    File,
+    RelativeFile,
+    SelectedText,
+    GitBranch,
+    Env(String),
+    /// Asked for in a prompt showing the text
+    Input(String),
+}
+
+impl Placeholder {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "workspaceRoot" => Placeholder::WorkspaceRoot,
+            "paneCwd" => Placeholder::PaneCwd,
+            "file" => Placeholder::File,
+            "relativeFile" => Placeholder::RelativeFile,
+            "selectedText" => Placeholder::SelectedText,
+            "gitBranch" => Placeholder::GitBranch,
+            _ => {
+                if let Some(var) = name.strip_prefix("env:").filter(|var| !var.is_empty()) {
+                    Placeholder::Env(var.to_string())
+                } else if let Some(prompt) = name.strip_prefix("input:") {
+                    Placeholder::Input(prompt.trim().to_string())
+                } else {
+                    return None;
+                }
+            }
+        })
+    }
+
+    /// Why the placeholder has no value, for error messages.
+    fn missing_reason(&self) -> String {
+        match self {
+            Placeholder::WorkspaceRoot => "the workspace has no root folder".to_string(),
+            Placeholder::PaneCwd => "the pane's working directory is unknown".to_string(),
+            Placeholder::File => "no file is open in the editor".to_string(),
+            Placeholder::RelativeFile => {
+                "no file from this workspace is open in the editor".to_string()
+            }
+            Placeholder::SelectedText => "no text is selected in the terminal".to_string(),
+            Placeholder::GitBranch => "the workspace is not on a git branch".to_string(),
+            Placeholder::Env(var) => format!("environment variable {var} is not set"),
+            Placeholder::Input(_) => "no value was entered".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placeholder::WorkspaceRoot => write!(f, "${{workspaceRoot}}"),
+            Placeholder::PaneCwd => write!(f, "${{paneCwd}}"),
+            Placeholder::File => write!(f, "${{file}}"),
+            Placeholder::RelativeFile => write!(f, "${{relativeFile}}"),
+            Placeholder::SelectedText => write!(f, "${{selectedText}}"),
+            Placeholder::GitBranch => write!(f, "${{gitBranch}}"),
+            Placeholder::Env(var) => write!(f, "${{env:{var}}}"),
+            Placeholder::Input(prompt) => write!(f, "${{input:{prompt}}}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VarError {
+    /// `${` without a closing `}`; the byte offset of the `$`
+    Unterminated(usize),
+    Unknown(String),
+    Missing(Placeholder),
+}
+
+impl fmt::Display for VarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarError::Unterminated(at) => write!(f, "unclosed '${{' at character {}", at + 1),
+            VarError::Unknown(name) => write!(f, "unknown variable '${{{name}}}'"),
+            VarError::Missing(placeholder) => {
+                write!(f, "{placeholder} has no value: {}", placeholder.missing_reason())
+            }
+        }
+    }
+}
+
+impl std::error::Error for VarError {}
+
+/// A launcher command split into text and placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTemplate {
+    segments: Vec<Segment>,
+}
+
+impl CommandTemplate {
+    pub fn parse(command: &str) -> Result<Self, VarError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = command;
+
+        while let Some(start) = rest.find('$') {
+            text.push_str(&rest[..start]);
+            let after = &rest[start..];
+            if let Some(literal) = after.strip_prefix("$${") {
+                text.push_str("${");
+                rest = literal;
+            } else if let Some(body) = after.strip_prefix("${") {
+                let at = command.len() - after.len();
+                let end = body.find('}').ok_or(VarError::Unterminated(at))?;
+                let name = &body[..end];
+                let placeholder =
+                    Placeholder::parse(name).ok_or_else(|| VarError::Unknown(name.to_string()))?;
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Placeholder(placeholder));
+                rest = &body[end + 1..];
+            } else {
+                text.push('$');
+                rest = &after[1..];
+            }
+        }
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Prompts of the `${input:…}` placeholders, each once, in order.
+    pub fn prompts(&self) -> Vec<String> {
+        let mut prompts: Vec<String> = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Placeholder(Placeholder::Input(prompt)) = segment {
+                if !prompts.contains(prompt) {
+                    prompts.push(prompt.clone());
+                }
+            }
+        }
+        prompts
+    }
+
+    /// The command with every placeholder replaced by its shell-quoted value. `resolve`
+    /// is called once per distinct placeholder; `None` (or an empty input) is an error.
+    pub fn expand(
+        &self,
+        mut resolve: impl FnMut(&Placeholder) -> Option<String>,
+    ) -> Result<String, VarError> {
+        let mut values: Vec<(&Placeholder, String)> = Vec::new();
+        let mut command = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => command.push_str(text),
+                Segment::Placeholder(placeholder) => {
+                    let known = values.iter().find(|(p, _)| *p == placeholder);
+                    let value = match known {
+                        Some((_, value)) => value.clone(),
+                        None => {
+                            let value = resolve(placeholder)
+                                .filter(|value| !(value.is_empty() && matches!(placeholder, Placeholder::Input(_))))
+                                .ok_or_else(|| VarError::Missing(placeholder.clone()))?;
+                            values.push((placeholder, value.clone()));
+                            value
+                        }
+                    };
+                    command.push_str(&shell_quote(&value));
+                }
+            }
+        }
+        Ok(command)
+    }
+}
+
+/// Quote `value` as one shell word (POSIX shells and fish). Plain words are left alone.
+pub fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"_-./:@%+=,".contains(&byte));
+    if plain {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(command: &str, resolve: impl FnMut(&Placeholder) -> Option<String>) -> Result<String, VarError> {
+        CommandTemplate::parse(command)?.expand(resolve)
+    }
+
+    #[test]
+    fn placeholders_are_parsed() {
+        let template = CommandTemplate::parse("rg ${selectedText} ${env:HOME}/x $${literal} $PATH").unwrap();
+        assert_eq!(
+            template.segments,
+            vec![
+                Segment::Text("rg ".to_string()),
+                Segment::Placeholder(Placeholder::SelectedText),
+                Segment::Text(" ".to_string()),
+                Segment::Placeholder(Placeholder::Env("HOME".to_string())),
+                Segment::Text("/x ${literal} $PATH".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_placeholders_are_reported() {
+        assert_eq!(CommandTemplate::parse("echo ${file"), Err(VarError::Unterminated(5)));
+        assert_eq!(CommandTemplate::parse("echo ${nope}"), Err(VarError::Unknown("nope".to_string())));
+        assert_eq!(CommandTemplate::parse("echo ${env:}"), Err(VarError::Unknown("env:".to_string())));
+        assert_eq!(
+            VarError::Missing(Placeholder::File).to_string(),
+            "${file} has no value: no file is open in the editor"
+        );
+    }
+
+    #[test]
+    fn values_are_shell_quoted() {
+        assert_eq!(shell_quote("src/main.rs"), "src/main.rs");
+        assert_eq!(shell_quote("my file.txt"), "'my file.txt'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+        assert_eq!(shell_quote(""), "''");
+
+        let command = expand("cat ${file}", |_| Some("/tmp/a b/c.txt".to_string())).unwrap();
+        assert_eq!(command, "cat '/tmp/a b/c.txt'");
+    }
+
+    #[test]
+    fn missing_values_stop_the_expansion() {
+        let result = expand("git log ${gitBranch}", |_| None);
+        assert_eq!(result, Err(VarError::Missing(Placeholder::GitBranch)));
+        // An empty answer to a prompt is missing too; other empty values are kept
+        assert_eq!(
+            expand("grep ${input:Pattern}", |_| Some(String::new())),
+            Err(VarError::Missing(Placeholder::Input("Pattern".to_string())))
+        );
+        assert_eq!(expand("echo ${selectedText}", |_| Some(String::new())).unwrap(), "echo ''");
+    }
+
+    #[test]
+    fn prompts_are_asked_once() {
+        let template = CommandTemplate::parse("git commit -m ${input:Message} && echo ${input:Message} ${input: Tag }").unwrap();
+        assert_eq!(template.prompts(), vec!["Message".to_string(), "Tag".to_string()]);
+
+        let mut calls = 0;
+        let command = template
+            .expand(|placeholder| {
+                calls += 1;
+                match placeholder {
+                    Placeholder::Input(prompt) if prompt == "Message" => Some("fix: it's done".to_string()),
+                    _ => Some("v1".to_string()),
+                }
+            })
+            .unwrap();
+        assert_eq!(command, r"git commit -m 'fix: it'\''s done' && echo 'fix: it'\''s done' v1");
+        assert_eq!(calls, 2);
+    }
+}
@@ -2,13 +2,37 @@ mod clipboard;
 mod fs;
 mod git;
 mod terminal;
+pub mod background;
 pub mod config;
+pub mod config_watch;
+pub mod diagnostics;
 pub mod state;
 pub mod launcher;
+pub mod launcher_vars;
+pub mod settings;
+pub mod color_scheme;
+pub mod hints;
+pub mod inline_image;
+pub mod process_title;
+pub mod remote_control;
+pub mod shell_profile;
+pub mod scrollback;
+pub mod terminal_notification;
+pub mod text_width;
+pub mod tasks;
+pub mod tmux;
 
+pub use background::{format_duration, TaskStatus};
 pub use clipboard::{get_clipboard_string, set_clipboard_string};
 pub use fs::{build_tree_entries, list_dir_entries, read_file_preview};
-pub use git::git_status_entries;
-pub use terminal::TerminalSession;
+pub use git::{git_branch, git_status_entries};
+pub use terminal::{SessionOptions, TerminalSession};
 pub use state::{load_state, save_state, AppState};
-pub use launcher::{load_launchers, Launcher, LauncherRunIn};
\ No newline at end of file
+pub use launcher::{
+    launcher_changes, launchers_upgrade, load_launchers, load_project_launchers, merge_launchers, save_launchers,
+    Launcher, LauncherConfig, LauncherFile, LauncherRunIn,
+};
+pub use scrollback::take_workspace_scrollback;
+pub use shell_profile::ShellProfile;
+pub use settings::{load_settings, read_settings, save_settings, CursorSettings, FontSettings, Settings};
+pub use terminal_notification::TerminalNotification;
\ No newline at end of file
@@ -0,0 +1,170 @@
+//! Pane titles derived from the terminal's foreground process.
+//!
+//! When the shell does not send OSC 0/2 titles, the pane shows the command running in
+//! the foreground (e.g. `cargo test`, `vim main.rs`). Priority is: title pinned by the
+//! user, then the OSC title, then the foreground process, then "Terminal".
+
+/// Title shown before anything more specific is known.
+pub const DEFAULT_PANE_TITLE: &str = "Terminal";
+
+/// Longest derived title, in characters; longer command lines are cut with an ellipsis.
+const MAX_TITLE_CHARS: usize = 48;
+
+/// Pick the title to display from the available sources.
+pub fn resolve_pane_title(pinned: Option<&str>, osc: Option<&str>, process: Option<&str>) -> String {
+    [pinned, osc, process]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|title| !title.is_empty())
+        .unwrap_or(DEFAULT_PANE_TITLE)
+        .to_string()
+}
+
+/// Title for a command line: the program name followed by its arguments, with
+/// paths shortened to their file name (`/usr/bin/vim src/main.rs` -> `vim main.rs`).
+pub fn title_from_args(args: &[String]) -> Option<String> {
+    let (program, rest) = args.split_first()?;
+    let program = file_name(program).trim_start_matches('-');
+    if program.is_empty() {
+        return None;
+    }
+
+    let mut title = program.to_string();
+    for arg in rest.iter().filter(|arg| !arg.is_empty()) {
+        title.push(' ');
+        if arg.starts_with('-') {
+            title.push_str(arg);
+        } else {
+            title.push_str(file_name(arg));
+        }
+    }
+
+    Some(truncate(&title))
+}
+
+fn file_name(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    match trimmed.rsplit_once('/') {
+        Some((_, name)) if !name.is_empty() => name,
+        _ => trimmed,
+    }
+}
+
+fn truncate(title: &str) -> String {
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title.to_string();
+    }
+    let mut cut: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// Command line of a running process.
+#[cfg(target_os = "linux")]
+pub fn process_args(pid: i32) -> Option<Vec<String>> {
+    if let Ok(cmdline) = std::fs::read(format!("/proc/{pid}/cmdline")) {
+        let args: Vec<String> = cmdline
+            .split(|byte| *byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        if !args.is_empty() {
+            return Some(args);
+        }
+    }
+
+    // Kernel threads and zombies have no cmdline; fall back to the short name
+    let comm = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    let comm = comm.trim();
+    (!comm.is_empty()).then(|| vec![comm.to_string()])
+}
+
+/// Command line of a running process (via `ps`; arguments are split on whitespace).
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn process_args(pid: i32) -> Option<Vec<String>> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let args: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// Working directory of a running process.
+#[cfg(target_os = "linux")]
+pub fn process_cwd(pid: i32) -> Option<std::path::PathBuf> {
+    std::fs::read_link(format!("/proc/{pid}/cwd")).ok()
+}
+
+/// Working directory of a running process (via `lsof`).
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn process_cwd(pid: i32) -> Option<std::path::PathBuf> {
+    let output = std::process::Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Field output: `p<pid>`, `f<fd>`, then `n<path>`
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix('n'))
+        .map(std::path::PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn title_uses_program_name_and_shortened_paths() {
+        assert_eq!(
+            title_from_args(&args(&["/usr/bin/vim", "src/main.rs"])).as_deref(),
+            Some("vim main.rs")
+        );
+        assert_eq!(
+            title_from_args(&args(&["cargo", "test", "--workspace"])).as_deref(),
+            Some("cargo test --workspace")
+        );
+        // Login shells are started as `-zsh`
+        assert_eq!(title_from_args(&args(&["-zsh"])).as_deref(), Some("zsh"));
+        assert_eq!(title_from_args(&[]), None);
+    }
+
+    #[test]
+    fn long_titles_are_truncated() {
+        let long = args(&["grep", &"x".repeat(100)]);
+        let title = title_from_args(&long).unwrap();
+        assert_eq!(title.chars().count(), MAX_TITLE_CHARS);
+        assert!(title.ends_with('…'));
+    }
+
+    #[test]
+    fn pinned_title_beats_osc_which_beats_process() {
+        assert_eq!(resolve_pane_title(Some("api"), Some("osc"), Some("vim")), "api");
+        assert_eq!(resolve_pane_title(None, Some("osc"), Some("vim")), "osc");
+        assert_eq!(resolve_pane_title(None, Some("  "), Some("vim")), "vim");
+        assert_eq!(resolve_pane_title(None, None, None), DEFAULT_PANE_TITLE);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cwd_of_this_process_is_found() {
+        let cwd = process_cwd(std::process::id() as i32).unwrap();
+        assert_eq!(cwd.canonicalize().unwrap(), std::env::current_dir().unwrap().canonicalize().unwrap());
+    }
+}
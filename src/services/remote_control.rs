@@ -0,0 +1,941 @@
+//! Remote control of a running Tide instance over a Unix socket.
+//!
+//! Each instance listens on `~/.config/tide/sockets/tide-<pid>.sock` and speaks JSON-RPC 2.0,
+//! one request or response per line. Shells started by Tide get the socket path in
+//! `TIDE_SOCKET` and their own pane in `TIDE_PANE_ID` (`<workspace>:<pane>`), so a script
+//! running in a pane can talk back to the window it lives in:
+//!
+//! ```text
+//! $ echo '{"jsonrpc":"2.0","id":1,"method":"pane.read_text"}' | nc -U "$TIDE_SOCKET"
+//! ```
+//!
+//! Requests are parsed on the connection thread and handed to the handler registered by
+//! the UI, which runs them on the UI thread and answers through a channel.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::services::shell_profile::ShellProfile;
+
+/// Environment variable holding the socket path of the instance a pane belongs to.
+pub const SOCKET_ENV: &str = "TIDE_SOCKET";
+
+/// Environment variable holding the address of the pane a shell runs in.
+pub const PANE_ENV: &str = "TIDE_PANE_ID";
+
+/// How long a connection waits for the UI thread to answer a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A terminal pane: the id of its workspace tab and its id within the tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaneAddress {
+    pub workspace: usize,
+    pub pane: usize,
+}
+
+impl PaneAddress {
+    pub fn new(workspace: usize, pane: usize) -> Self {
+        Self { workspace, pane }
+    }
+}
+
+impl fmt::Display for PaneAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.workspace, self.pane)
+    }
+}
+
+impl FromStr for PaneAddress {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid pane id {text:?} (expected <workspace>:<pane>)");
+        let (workspace, pane) = text.trim().split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            workspace: workspace.parse().map_err(|_| invalid())?,
+            pane: pane.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for PaneAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(Self::INTERNAL_ERROR, message)
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+/// A request to the UI. A missing `pane` means the focused pane of the active workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteCommand {
+    /// `workspace.open {path}`: open a workspace tab and make it active
+    OpenWorkspace { path: PathBuf },
+    /// `editor.open {path, line?, column?}`: show a file in the editor of the workspace
+    /// containing it (1-based position)
+    OpenFile { path: PathBuf, line: Option<usize>, column: Option<usize> },
+    /// `window.focus`: bring the window in front of other apps
+    FocusWindow,
+    /// `pane.list`: every pane with its workspace, title and working directory
+    ListPanes,
+    /// `pane.split {pane?, profile?}`: new pane to the right, started with `profile`
+    SplitPane { pane: Option<PaneAddress>, profile: Option<String> },
+    /// `pane.send_text {pane?, text}`: type `text` into the pane
+    SendText { pane: Option<PaneAddress>, text: String },
+    /// `pane.read_text {pane?}`: the text visible in the pane
+    ReadText { pane: Option<PaneAddress> },
+    /// `pane.last_output {pane?}`: output of the last command entered in the pane
+    LastOutput { pane: Option<PaneAddress> },
+    /// `launcher.run {name, pane?, inputs?}`: run a launcher from the workspace of `pane`;
+    /// `inputs` answers its `${input:…}` prompts
+    RunLauncher {
+        name: String,
+        pane: Option<PaneAddress>,
+        inputs: HashMap<String, String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PathParams {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileParams {
+    path: PathBuf,
+    #[serde(default)]
+    line: Option<usize>,
+    #[serde(default)]
+    column: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PaneParams {
+    #[serde(default)]
+    pane: Option<PaneAddress>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SplitParams {
+    #[serde(default)]
+    pane: Option<PaneAddress>,
+    #[serde(default)]
+    profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextParams {
+    #[serde(default)]
+    pane: Option<PaneAddress>,
+    text: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LauncherParams {
+    name: String,
+    #[serde(default)]
+    pane: Option<PaneAddress>,
+    #[serde(default)]
+    inputs: HashMap<String, String>,
+}
+
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Omitted params are the same as an empty object
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|err| RpcError::invalid_params(err.to_string()))
+}
+
+/// Build the command for a request's method and params.
+pub fn parse_command(method: &str, raw: Value) -> Result<RemoteCommand, RpcError> {
+    Ok(match method {
+        "workspace.open" => {
+            let PathParams { path } = params(raw)?;
+            RemoteCommand::OpenWorkspace { path }
+        }
+        "editor.open" => {
+            let FileParams { path, line, column } = params(raw)?;
+            RemoteCommand::OpenFile { path, line, column }
+        }
+        "window.focus" => {
+            let NoParams {} = params(raw)?;
+            RemoteCommand::FocusWindow
+        }
+        "pane.list" => {
+            let NoParams {} = params(raw)?;
+            RemoteCommand::ListPanes
+        }
+        "pane.split" => {
+            let SplitParams { pane, profile } = params(raw)?;
+            RemoteCommand::SplitPane { pane, profile }
+        }
+        "pane.send_text" => {
+            let TextParams { pane, text } = params(raw)?;
+            RemoteCommand::SendText { pane, text }
+        }
+        "pane.read_text" => {
+            let PaneParams { pane } = params(raw)?;
+            RemoteCommand::ReadText { pane }
+        }
+        "pane.last_output" => {
+            let PaneParams { pane } = params(raw)?;
+            RemoteCommand::LastOutput { pane }
+        }
+        "launcher.run" => {
+            let LauncherParams { name, pane, inputs } = params(raw)?;
+            RemoteCommand::RunLauncher { name, pane, inputs }
+        }
+        _ => return Err(RpcError::new(RpcError::METHOD_NOT_FOUND, format!("unknown method {method:?}"))),
+    })
+}
+
+/// Channel the UI answers a request on.
+pub type Reply = Sender<Result<Value, RpcError>>;
+
+type RequestHandler = Arc<dyn Fn(RemoteCommand, Reply) + Send + Sync>;
+
+static REQUEST_HANDLER: OnceLock<Mutex<Option<RequestHandler>>> = OnceLock::new();
+
+/// Register the handler running requests (called from connection threads).
+pub fn set_request_handler(handler: RequestHandler) {
+    let mutex = REQUEST_HANDLER.get_or_init(|| Mutex::new(None));
+    if let Ok(mut guard) = mutex.lock() {
+        *guard = Some(handler);
+    }
+}
+
+/// Run a command through the registered handler and wait for its answer.
+fn dispatch(command: RemoteCommand) -> Result<Value, RpcError> {
+    let handler = REQUEST_HANDLER
+        .get()
+        .and_then(|mutex| mutex.lock().ok().and_then(|guard| guard.clone()))
+        .ok_or_else(|| RpcError::internal("Tide is not ready for requests yet"))?;
+    let (reply, answer) = mpsc::channel();
+    handler(command, reply);
+    answer
+        .recv_timeout(REPLY_TIMEOUT)
+        .map_err(|_| RpcError::internal("Tide did not answer in time"))?
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let body = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": err.code, "message": err.message },
+        }),
+    };
+    body.to_string()
+}
+
+/// Handle one request line; returns the response line (`None` for notifications).
+pub fn handle_line(line: &str) -> Option<String> {
+    handle_line_with(line, dispatch)
+}
+
+fn handle_line_with(
+    line: &str,
+    run: impl FnOnce(RemoteCommand) -> Result<Value, RpcError>,
+) -> Option<String> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => {
+            let err = RpcError::new(RpcError::PARSE_ERROR, format!("parse error: {err}"));
+            return Some(response(Value::Null, Err(err)));
+        }
+    };
+
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+    let version_ok = request.get("jsonrpc").and_then(Value::as_str) == Some("2.0");
+    let Some(method) = method.filter(|_| version_ok && request.is_object()) else {
+        let err = RpcError::new(RpcError::INVALID_REQUEST, "expected a JSON-RPC 2.0 request");
+        return Some(response(id.unwrap_or(Value::Null), Err(err)));
+    };
+
+    let raw = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = parse_command(method, raw).and_then(run);
+    if let Err(err) = &result {
+        crate::logging::log_line("WARN", &format!("Remote control {method} failed: {err}"));
+    }
+    // Requests without an id are notifications and get no response
+    id.map(|id| response(id, result))
+}
+
+static SOCKET_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Socket of this instance, once the server is running.
+pub fn socket_path() -> Option<&'static Path> {
+    SOCKET_PATH.get().map(PathBuf::as_path)
+}
+
+/// `profile` with the remote control variables for the shell of `pane` added.
+pub fn with_pane_env(profile: &ShellProfile, pane: PaneAddress) -> ShellProfile {
+    let mut profile = profile.clone();
+    if let Some(socket) = socket_path() {
+        profile
+            .env
+            .insert(SOCKET_ENV.to_string(), socket.to_string_lossy().into_owned());
+    }
+    profile.env.insert(PANE_ENV.to_string(), pane.to_string());
+    profile
+}
+
+#[cfg(unix)]
+pub use self::client::{call, running_instances};
+#[cfg(unix)]
+pub use self::server::start_server;
+
+#[cfg(unix)]
+mod server {
+    use super::{handle_line, SOCKET_PATH};
+    use crate::logging;
+    use crate::services::config::sockets_dir;
+    use std::fs;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::thread;
+
+    /// Start listening on this instance's socket; returns its path.
+    pub fn start_server() -> io::Result<PathBuf> {
+        let dir = sockets_dir();
+        // Anyone who can connect can type into the shells: only this user gets into the
+        // directory, so no one can reach a socket between its bind and its chmod
+        fs::DirBuilder::new().recursive(true).mode(0o700).create(&dir)?;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+        remove_stale_sockets(&dir);
+        let path = dir.join(format!("tide-{}.sock", std::process::id()));
+        serve(&path)?;
+        let _ = SOCKET_PATH.set(path.clone());
+        Ok(path)
+    }
+
+    pub(super) fn serve(path: &Path) -> io::Result<()> {
+        let _ = fs::remove_file(path);
+        let listener = UnixListener::bind(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        logging::log_line("INFO", &format!("Remote control listening on {}", path.display()));
+
+        thread::Builder::new()
+            .name("tide-remote-control".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let spawned = thread::Builder::new()
+                                .name("tide-remote-client".to_string())
+                                .spawn(move || serve_connection(stream));
+                            if let Err(err) = spawned {
+                                logging::log_line("ERROR", &format!("Remote control client thread failed: {err}"));
+                            }
+                        }
+                        Err(err) => {
+                            logging::log_line("WARN", &format!("Remote control accept failed: {err}"))
+                        }
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    fn serve_connection(stream: UnixStream) {
+        let reader = match stream.try_clone() {
+            Ok(reader) => BufReader::new(reader),
+            Err(err) => {
+                logging::log_line("WARN", &format!("Remote control connection failed: {err}"));
+                return;
+            }
+        };
+        let mut writer = stream;
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = handle_line(&line) {
+                if writeln!(writer, "{response}").and_then(|_| writer.flush()).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Remove sockets left behind by instances that are no longer running.
+    fn remove_stale_sockets(dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_socket = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("tide-") && name.ends_with(".sock"));
+            if is_socket && UnixStream::connect(&path).is_err() {
+                logging::log_line("INFO", &format!("Removing stale socket {}", path.display()));
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+mod client {
+    use super::RpcError;
+    use crate::services::config::sockets_dir;
+    use serde_json::{json, Value};
+    use std::fs;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    const CALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+    /// Sockets of running instances, most recently started first.
+    pub fn running_instances() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(sockets_dir()) else { return Vec::new() };
+        let mut sockets: Vec<(SystemTime, PathBuf)> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "sock"))
+            .filter(|path| UnixStream::connect(path).is_ok())
+            .map(|path| {
+                let modified = fs::metadata(&path)
+                    .and_then(|meta| meta.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                (modified, path)
+            })
+            .collect();
+        sockets.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        sockets.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// Send one request to the instance listening on `socket` and wait for the answer.
+    pub fn call(socket: &Path, method: &str, params: Value) -> io::Result<Result<Value, RpcError>> {
+        let mut stream = UnixStream::connect(socket)?;
+        stream.set_read_timeout(Some(CALL_TIMEOUT))?;
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        writeln!(stream, "{request}")?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let response: Value = serde_json::from_str(&line)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(match response.get("error") {
+            Some(error) => Err(RpcError::new(
+                error["code"].as_i64().unwrap_or(RpcError::INTERNAL_ERROR),
+                error["message"].as_str().unwrap_or_default(),
+            )),
+            None => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        })
+    }
+}
+
+/// Lines commands were entered on, kept beside the grid for `pane.last_output`. Lines
+/// are counted from the top of the first screen, so a mark stays with its line as output
+/// scrolls it into the history; a resize that rewraps lines, or an inline image placed at
+/// the bottom of the screen, can still move it off.
+#[derive(Debug, Default)]
+pub struct CommandMarks {
+    /// Lines scrolled off the top of the screen since the session started
+    scrolled: usize,
+    /// Marked lines, oldest first
+    lines: VecDeque<usize>,
+}
+
+impl CommandMarks {
+    /// Marks kept; commands older than that are gone from the scrollback anyway.
+    const LIMIT: usize = 1000;
+
+    /// Mark `line` of the screen (0 is its top line).
+    pub fn mark(&mut self, line: usize) {
+        let line = self.scrolled + line;
+        if self.lines.back() == Some(&line) {
+            return;
+        }
+        if self.lines.len() == Self::LIMIT {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    /// Record `count` lines scrolled off the top of the screen.
+    pub fn scroll(&mut self, count: usize) {
+        self.scrolled += count;
+    }
+
+    /// Grid lines of the marks, latest first; negative lines are in the history.
+    pub fn grid_lines(&self) -> impl Iterator<Item = i32> + '_ {
+        self.lines
+            .iter()
+            .rev()
+            .map(|&line| (line as i64 - self.scrolled as i64).max(i32::MIN as i64) as i32)
+    }
+}
+
+/// Pane text for `pane.read_text` / `pane.last_output`, and the command marks they use.
+#[cfg(target_os = "macos")]
+pub use self::platform::{last_command_output, mark_command_line, visible_text, CommandMarkHandler};
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::CommandMarks;
+    use crate::services::text_width::{delegate_handler, WidthPolicyHandler};
+    use alacritty_terminal::event::EventListener;
+    use alacritty_terminal::grid::Dimensions;
+    use alacritty_terminal::index::{Column, Line, Point};
+    use alacritty_terminal::term::{Term, TermMode};
+    use alacritty_terminal::vte::ansi::{
+        Attr, CharsetIndex, ClearMode, CursorShape, CursorStyle, Handler, Hyperlink, KeyboardModes,
+        KeyboardModesApplyBehavior, LineClearMode, Mode, ModifyOtherKeys, PrivateMode, Rgb,
+        ScpCharPath, ScpUpdateMode, StandardCharset, TabulationClearMode,
+    };
+
+    /// Handler counting the lines output scrolls off the top of the normal screen, for
+    /// `CommandMarks::scroll`; the output itself goes on to the width-policy handler.
+    /// The scroll region is taken to be the whole screen, as it is for shells.
+    pub struct CommandMarkHandler<'a, T: EventListener> {
+        inner: WidthPolicyHandler<'a, T>,
+        lines_scrolled: usize,
+    }
+
+    impl<'a, T: EventListener> CommandMarkHandler<'a, T> {
+        pub fn new(inner: WidthPolicyHandler<'a, T>) -> Self {
+            Self { inner, lines_scrolled: 0 }
+        }
+
+        /// Lines scrolled into the history by the output so far.
+        pub fn lines_scrolled(&self) -> usize {
+            self.lines_scrolled
+        }
+
+        fn count_scroll(&mut self, lines: usize) {
+            if !self.inner.term().mode().contains(TermMode::ALT_SCREEN) {
+                self.lines_scrolled += lines;
+            }
+        }
+
+        /// Run `f`, which moves the cursor down a line; if the cursor stays on its line,
+        /// the screen scrolled instead.
+        fn line_down(&mut self, f: impl FnOnce(&mut WidthPolicyHandler<'a, T>)) {
+            let line = self.inner.term().grid().cursor.point.line;
+            f(&mut self.inner);
+            if self.inner.term().grid().cursor.point.line == line {
+                self.count_scroll(1);
+            }
+        }
+
+        /// Screen lines down to the last one with text: those clearing the screen moves
+        /// into the history.
+        fn used_lines(&self) -> usize {
+            let grid = self.inner.term().grid();
+            (0..grid.screen_lines())
+                .rev()
+                .find(|&line| !grid[Line(line as i32)].is_clear())
+                .map_or(0, |line| line + 1)
+        }
+    }
+
+    impl<T: EventListener> Handler for CommandMarkHandler<'_, T> {
+        fn input(&mut self, c: char) {
+            let before = self.inner.term().grid().cursor.point;
+            self.inner.input(c);
+            // Wrapping on the bottom line scrolls
+            let after = self.inner.term().grid().cursor.point;
+            if after.column < before.column && after.line == before.line {
+                self.count_scroll(1);
+            }
+        }
+
+        fn linefeed(&mut self) {
+            self.line_down(|inner| inner.linefeed());
+        }
+
+        fn newline(&mut self) {
+            self.line_down(|inner| inner.newline());
+        }
+
+        fn scroll_up(&mut self, count: usize) {
+            self.inner.scroll_up(count);
+            self.count_scroll(count);
+        }
+
+        fn clear_screen(&mut self, mode: ClearMode) {
+            let scrolled = if matches!(mode, ClearMode::All) { self.used_lines() } else { 0 };
+            self.inner.clear_screen(mode);
+            self.count_scroll(scrolled);
+        }
+
+        delegate_handler! { inner:
+            set_title(title: Option<String>);
+            set_cursor_style(style: Option<CursorStyle>);
+            set_cursor_shape(shape: CursorShape);
+            goto(line: i32, col: usize);
+            goto_line(line: i32);
+            goto_col(col: usize);
+            insert_blank(count: usize);
+            move_up(count: usize);
+            move_down(count: usize);
+            identify_terminal(intermediate: Option<char>);
+            device_status(arg: usize);
+            move_forward(col: usize);
+            move_backward(col: usize);
+            move_down_and_cr(row: usize);
+            move_up_and_cr(row: usize);
+            put_tab(count: u16);
+            backspace();
+            carriage_return();
+            bell();
+            substitute();
+            set_horizontal_tabstop();
+            scroll_down(count: usize);
+            insert_blank_lines(count: usize);
+            delete_lines(count: usize);
+            erase_chars(count: usize);
+            delete_chars(count: usize);
+            move_backward_tabs(count: u16);
+            move_forward_tabs(count: u16);
+            save_cursor_position();
+            restore_cursor_position();
+            clear_line(mode: LineClearMode);
+            clear_tabs(mode: TabulationClearMode);
+            set_tabs(interval: u16);
+            reset_state();
+            reverse_index();
+            terminal_attribute(attr: Attr);
+            set_mode(mode: Mode);
+            unset_mode(mode: Mode);
+            report_mode(mode: Mode);
+            set_private_mode(mode: PrivateMode);
+            unset_private_mode(mode: PrivateMode);
+            report_private_mode(mode: PrivateMode);
+            set_scrolling_region(top: usize, bottom: Option<usize>);
+            set_keypad_application_mode();
+            unset_keypad_application_mode();
+            set_active_charset(index: CharsetIndex);
+            configure_charset(index: CharsetIndex, charset: StandardCharset);
+            set_color(index: usize, color: Rgb);
+            dynamic_color_sequence(prefix: String, index: usize, terminator: &str);
+            reset_color(index: usize);
+            clipboard_store(clipboard: u8, data: &[u8]);
+            clipboard_load(clipboard: u8, terminator: &str);
+            decaln();
+            push_title();
+            pop_title();
+            text_area_size_pixels();
+            text_area_size_chars();
+            set_hyperlink(hyperlink: Option<Hyperlink>);
+            report_keyboard_mode();
+            push_keyboard_mode(mode: KeyboardModes);
+            pop_keyboard_modes(to_pop: u16);
+            set_keyboard_mode(mode: KeyboardModes, behavior: KeyboardModesApplyBehavior);
+            set_modify_other_keys(mode: ModifyOtherKeys);
+            report_modify_other_keys();
+            set_scp(char_path: ScpCharPath, update_mode: ScpUpdateMode);
+        }
+    }
+
+    fn trim_lines(text: String) -> String {
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        lines.join("\n").trim_end_matches('\n').to_string()
+    }
+
+    /// Text in the viewport, trailing blanks removed.
+    pub fn visible_text<T: EventListener>(term: &Term<T>) -> String {
+        let top = -(term.grid().display_offset() as i32);
+        let bottom = top + term.screen_lines() as i32 - 1;
+        let start = Point::new(Line(top), Column(0));
+        let end = Point::new(Line(bottom), term.last_column());
+        trim_lines(term.bounds_to_string(start, end))
+    }
+
+    /// Mark the cursor line as the line a command was entered on (called when Enter is
+    /// sent to the shell). Full-screen programs are not marked.
+    pub fn mark_command_line<T: EventListener>(term: &Term<T>, marks: &mut CommandMarks) {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+        marks.mark(term.grid().cursor.point.line.0.max(0) as usize);
+    }
+
+    /// Output between the last marked command line and the cursor line (the next prompt).
+    /// `None` when no command line is left in the scrollback.
+    pub fn last_command_output<T: EventListener>(term: &Term<T>, marks: &CommandMarks) -> Option<String> {
+        let cursor = term.grid().cursor.point.line.0;
+        // The prompt line itself is marked while a command is still running
+        let mark = marks.grid_lines().filter(|&line| line <= cursor).max()?;
+        if mark < term.topmost_line().0 {
+            return None;
+        }
+        let (first, last) = (mark + 1, cursor - 1);
+        if first > last {
+            return Some(String::new());
+        }
+        let start = Point::new(Line(first), Column(0));
+        let end = Point::new(Line(last), term.last_column());
+        Some(trim_lines(term.bounds_to_string(start, end)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(line: &str) -> Value {
+        let response = handle_line_with(line, |command| Ok(json!(format!("{command:?}")))).unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn pane_addresses_round_trip() {
+        let address: PaneAddress = "3:12".parse().unwrap();
+        assert_eq!(address, PaneAddress::new(3, 12));
+        assert_eq!(address.to_string(), "3:12");
+        assert!("3".parse::<PaneAddress>().is_err());
+        assert!("a:1".parse::<PaneAddress>().is_err());
+    }
+
+    #[test]
+    fn methods_parse_into_commands() {
+        assert_eq!(
+            parse_command("workspace.open", json!({ "path": "/tmp/project" })),
+            Ok(RemoteCommand::OpenWorkspace { path: PathBuf::from("/tmp/project") })
+        );
+        assert_eq!(parse_command("pane.list", Value::Null), Ok(RemoteCommand::ListPanes));
+        assert_eq!(parse_command("window.focus", json!({})), Ok(RemoteCommand::FocusWindow));
+        assert_eq!(
+            parse_command("editor.open", json!({ "path": "/tmp/a.rs", "line": 3, "column": null })),
+            Ok(RemoteCommand::OpenFile { path: PathBuf::from("/tmp/a.rs"), line: Some(3), column: None })
+        );
+        assert_eq!(
+            parse_command("pane.send_text", json!({ "pane": "1:2", "text": "ls\r" })),
+            Ok(RemoteCommand::SendText {
+                pane: Some(PaneAddress::new(1, 2)),
+                text: "ls\r".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_command("pane.split", json!({ "profile": "zsh" })),
+            Ok(RemoteCommand::SplitPane { pane: None, profile: Some("zsh".to_string()) })
+        );
+        assert_eq!(
+            parse_command("launcher.run", json!({ "name": "Claude" })),
+            Ok(RemoteCommand::RunLauncher { name: "Claude".to_string(), pane: None, inputs: HashMap::new() })
+        );
+        assert_eq!(
+            parse_command("launcher.run", json!({ "name": "grep", "inputs": { "Pattern": "TODO" } })),
+            Ok(RemoteCommand::RunLauncher {
+                name: "grep".to_string(),
+                pane: None,
+                inputs: HashMap::from([("Pattern".to_string(), "TODO".to_string())]),
+            })
+        );
+    }
+
+    #[test]
+    fn bad_params_and_methods_are_reported() {
+        let code = |result: Result<RemoteCommand, RpcError>| result.unwrap_err().code;
+        assert_eq!(code(parse_command("pane.close", Value::Null)), RpcError::METHOD_NOT_FOUND);
+        assert_eq!(code(parse_command("pane.send_text", json!({}))), RpcError::INVALID_PARAMS);
+        assert_eq!(code(parse_command("pane.read_text", json!({ "pane": "x" }))), RpcError::INVALID_PARAMS);
+        assert_eq!(code(parse_command("pane.list", json!({ "extra": 1 }))), RpcError::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn responses_follow_json_rpc() {
+        let ok = reply(r#"{"jsonrpc":"2.0","id":7,"method":"pane.list"}"#);
+        assert_eq!(ok["id"], 7);
+        assert_eq!(ok["result"], "ListPanes");
+
+        let parse = reply("{not json");
+        assert_eq!(parse["error"]["code"], RpcError::PARSE_ERROR);
+        assert_eq!(parse["id"], Value::Null);
+
+        let invalid = reply(r#"{"id":"a","method":"pane.list"}"#);
+        assert_eq!(invalid["error"]["code"], RpcError::INVALID_REQUEST);
+        assert_eq!(invalid["id"], "a");
+
+        let unknown = reply(r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#);
+        assert_eq!(unknown["error"]["code"], RpcError::METHOD_NOT_FOUND);
+
+        let notification = handle_line_with(r#"{"jsonrpc":"2.0","method":"pane.list"}"#, |_| Ok(Value::Null));
+        assert_eq!(notification, None);
+    }
+
+    #[test]
+    fn pane_env_carries_the_pane_id() {
+        let profile = with_pane_env(&ShellProfile::default(), PaneAddress::new(2, 5));
+        assert_eq!(profile.env.get(PANE_ENV).map(String::as_str), Some("2:5"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn socket_answers_requests() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::os::unix::net::UnixStream;
+
+        set_request_handler(Arc::new(|command, reply: Reply| {
+            let _ = reply.send(match command {
+                RemoteCommand::ListPanes => Ok(json!([{ "pane": "0:0" }])),
+                _ => Err(RpcError::internal("unexpected")),
+            });
+        }));
+        let path = std::env::temp_dir().join(format!("tide-test-{}.sock", std::process::id()));
+        server::serve(&path).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        writeln!(stream, r#"{{"jsonrpc":"2.0","id":1,"method":"pane.list"}}"#).unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["result"][0]["pane"], "0:0");
+
+        let failed = call(&path, "pane.read_text", Value::Null).unwrap();
+        assert_eq!(failed, Err(RpcError::internal("unexpected")));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn command_marks_follow_scrolled_lines() {
+        let mut marks = CommandMarks::default();
+        marks.mark(3);
+        marks.mark(3);
+        marks.scroll(2);
+        marks.mark(5);
+        assert_eq!(marks.grid_lines().collect::<Vec<_>>(), [5, 1]);
+        marks.scroll(4);
+        assert_eq!(marks.grid_lines().collect::<Vec<_>>(), [1, -3]);
+    }
+
+    #[cfg(target_os = "macos")]
+    mod grid {
+        use super::super::{last_command_output, mark_command_line, visible_text, CommandMarkHandler, CommandMarks};
+        use crate::services::text_width::{AmbiguousWidth, WidthPolicyHandler};
+        use alacritty_terminal::event::VoidListener;
+        use alacritty_terminal::term::test::TermSize;
+        use alacritty_terminal::term::{Config, Term};
+        use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+
+        struct Pane {
+            term: Term<VoidListener>,
+            parser: Processor<StdSyncHandler>,
+            marks: CommandMarks,
+        }
+
+        impl Pane {
+            fn new(lines: usize) -> Self {
+                Self {
+                    term: Term::new(Config::default(), &TermSize::new(20, lines), VoidListener),
+                    parser: Processor::new(),
+                    marks: CommandMarks::default(),
+                }
+            }
+
+            fn print(&mut self, output: &[u8]) {
+                let mut handler = CommandMarkHandler::new(WidthPolicyHandler::new(&mut self.term, AmbiguousWidth::Narrow));
+                self.parser.advance(&mut handler, output);
+                let scrolled = handler.lines_scrolled();
+                self.marks.scroll(scrolled);
+            }
+
+            fn enter(&mut self) {
+                mark_command_line(&self.term, &mut self.marks);
+            }
+
+            fn last_output(&self) -> Option<String> {
+                last_command_output(&self.term, &self.marks)
+            }
+        }
+
+        #[test]
+        fn last_output_is_between_the_command_and_the_prompt() {
+            let mut pane = Pane::new(6);
+            pane.print(b"$ ls");
+            assert_eq!(pane.last_output(), None);
+
+            pane.enter();
+            pane.print(b"\r\na.txt\r\nb.txt\r\n$ ");
+            assert_eq!(pane.last_output().as_deref(), Some("a.txt\nb.txt"));
+            assert_eq!(visible_text(&pane.term), "$ ls\na.txt\nb.txt\n$");
+
+            // Nothing printed yet for a command that is still running
+            pane.enter();
+            pane.print(b"\r\n");
+            assert_eq!(pane.last_output().as_deref(), Some(""));
+        }
+
+        #[test]
+        fn marks_stay_on_lines_scrolled_into_the_history() {
+            let mut pane = Pane::new(3);
+            pane.print(b"$ seq 5");
+            pane.enter();
+            pane.print(b"\r\n1\r\n2\r\n3\r\n4\r\n5\r\n$ ");
+            assert_eq!(pane.last_output().as_deref(), Some("1\n2\n3\n4\n5"));
+
+            // Clearing the screen moves its three lines into the history too
+            pane.print(b"\x1b[H\x1b[2J");
+            assert_eq!(pane.marks.grid_lines().next(), Some(-7));
+
+            // So do lines that wrap at the bottom of the screen
+            pane.print(b"$ ");
+            pane.enter();
+            pane.print(b"\r\n");
+            pane.print(&[b'x'; 50]);
+            pane.print(b"\r\n$ ");
+            assert_eq!(pane.last_output(), Some("x".repeat(50)));
+        }
+    }
+}
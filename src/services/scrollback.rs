@@ -0,0 +1,436 @@
+//! Pane scrollback persistence - screen and recent history restored after a restart.
+//!
+//! On shutdown every pane's grid is serialized as lines of text with SGR escapes (so
+//! colors and attributes survive) into `~/.config/tide/scrollback/<workspace>-<index>.json`.
+//! On startup the files are taken (read and deleted) and replayed into the new sessions
+//! above the fresh prompt, followed by a "restored" separator.
+
+use crate::services::config::scrollback_dir;
+use crate::services::state::saving_enabled;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(target_os = "macos")]
+use alacritty_terminal::{
+    event::EventListener,
+    grid::Dimensions,
+    index::{Column, Line},
+    term::{cell::Flags, Term, TermMode},
+    vte::ansi::{Color, NamedColor},
+};
+
+/// Format version written to every file; files with another version are discarded.
+pub const SCROLLBACK_FORMAT_VERSION: u32 = 1;
+
+/// Most lines kept per pane (newest win).
+const MAX_SAVED_LINES: usize = 5000;
+
+/// Most bytes of line data kept per pane (newest lines win).
+const MAX_SAVED_BYTES: usize = 2 * 1024 * 1024;
+
+/// Saved contents of one pane.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedScrollback {
+    pub version: u32,
+    /// Seconds since the Unix epoch when the pane was saved
+    pub saved_at: u64,
+    /// Logical lines (soft-wrapped rows joined) with SGR escapes for attributes
+    pub lines: Vec<String>,
+}
+
+impl SavedScrollback {
+    /// Wrap `lines`, keeping only the newest lines that fit the size caps.
+    pub fn new(mut lines: Vec<String>) -> Self {
+        if lines.len() > MAX_SAVED_LINES {
+            lines.drain(..lines.len() - MAX_SAVED_LINES);
+        }
+
+        let mut total = 0;
+        let keep_from = lines
+            .iter()
+            .rposition(|line| {
+                total += line.len() + 2;
+                total > MAX_SAVED_BYTES
+            })
+            .map_or(0, |index| index + 1);
+        lines.drain(..keep_from);
+
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            version: SCROLLBACK_FORMAT_VERSION,
+            saved_at,
+            lines,
+        }
+    }
+
+    /// Bytes fed to a new terminal before the shell output: the saved lines followed
+    /// by a dimmed separator. Empty when nothing was saved.
+    pub fn replay_bytes(&self) -> Vec<u8> {
+        if self.lines.is_empty() {
+            return Vec::new();
+        }
+
+        let mut out = String::new();
+        for line in &self.lines {
+            out.push_str("\x1b[0m");
+            out.push_str(line);
+            out.push_str("\x1b[0m\r\n");
+        }
+        out.push_str("\x1b[2m── restored ──\x1b[0m\r\n");
+        out.into_bytes()
+    }
+}
+
+/// Save the panes of the workspace rooted at `root`, in pane order.
+/// Files left over from panes that no longer exist are removed.
+pub fn save_workspace_scrollback(root: &Path, panes: &[SavedScrollback]) {
+    if !saving_enabled() {
+        return;
+    }
+    let dir = scrollback_dir();
+    if let Err(err) = create_private_dir(&dir) {
+        eprintln!("Failed to create scrollback dir: {}", err);
+        return;
+    }
+
+    remove_workspace_files(root);
+    for (index, saved) in panes.iter().enumerate() {
+        let path = pane_file(root, index);
+        match serde_json::to_string(saved) {
+            Ok(content) => {
+                if let Err(err) = write_private_file(&path, &content) {
+                    eprintln!("Failed to write scrollback file {}: {}", path.display(), err);
+                }
+            }
+            Err(err) => {
+                eprintln!("Failed to serialize scrollback: {}", err);
+            }
+        }
+    }
+}
+
+/// Create `dir` for this user only: saved panes can hold tokens and passwords that were
+/// on screen. An existing directory is made private too.
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(dir)?;
+    #[cfg(unix)]
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    Ok(())
+}
+
+/// Write `content` to a file only this user can read.
+fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    // A file left from before keeps its mode when opened
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+/// Take (read and delete) the saved panes of the workspace rooted at `root`, in pane order.
+pub fn take_workspace_scrollback(root: &Path) -> Vec<SavedScrollback> {
+    let mut panes = Vec::new();
+    for index in 0.. {
+        let path = pane_file(root, index);
+        if !path.exists() {
+            break;
+        }
+
+        let saved = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<SavedScrollback>(&content).map_err(|err| err.to_string())
+            });
+        match saved {
+            Ok(saved) if saved.version == SCROLLBACK_FORMAT_VERSION => panes.push(saved),
+            Ok(saved) => {
+                eprintln!(
+                    "Ignoring scrollback file {} with version {}",
+                    path.display(),
+                    saved.version
+                );
+                panes.push(SavedScrollback::new(Vec::new()));
+            }
+            Err(err) => {
+                eprintln!("Failed to read scrollback file {}: {}", path.display(), err);
+                panes.push(SavedScrollback::new(Vec::new()));
+            }
+        }
+    }
+
+    remove_workspace_files(root);
+    panes
+}
+
+fn remove_workspace_files(root: &Path) {
+    let prefix = workspace_key(root);
+    let Ok(entries) = fs::read_dir(scrollback_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.strip_prefix(&prefix).is_some_and(|rest| rest.starts_with('-')) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn pane_file(root: &Path, index: usize) -> PathBuf {
+    scrollback_dir().join(format!("{}-{index}.json", workspace_key(root)))
+}
+
+/// File name prefix for a workspace: a readable directory name plus a stable hash of
+/// the full path (FNV-1a, so it does not change between builds).
+fn workspace_key(root: &Path) -> String {
+    let path = root.to_string_lossy();
+    let hash = path.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    let name: String = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .take(32)
+        .collect();
+    format!("{name}_{hash:016x}")
+}
+
+/// Serialize the primary screen and recent history of `term`.
+/// Returns `None` while a full-screen program owns the alternate screen.
+#[cfg(target_os = "macos")]
+pub fn capture_scrollback<T: EventListener>(term: &Term<T>) -> Option<SavedScrollback> {
+    if term.mode().contains(TermMode::ALT_SCREEN) {
+        return None;
+    }
+
+    let grid = term.grid();
+    let columns = grid.columns();
+    let last_line = grid.cursor.point.line;
+    let first_line = Line((last_line.0 - MAX_SAVED_LINES as i32).max(grid.topmost_line().0));
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for line in first_line.0..=last_line.0 {
+        let row = &grid[Line(line)];
+        let wraps = columns > 0 && row[Column(columns - 1)].flags.contains(Flags::WRAPLINE);
+
+        // Trailing blank cells are dropped unless the row continues on the next one
+        let end = if wraps {
+            columns
+        } else {
+            (0..columns)
+                .rposition(|col| {
+                    let cell = &row[Column(col)];
+                    cell.c != ' '
+                        || cell.bg != Color::Named(NamedColor::Background)
+                        || cell.flags.intersects(Flags::INVERSE | Flags::ALL_UNDERLINES)
+                })
+                .map_or(0, |col| col + 1)
+        };
+
+        let mut style = String::new();
+        for col in 0..end {
+            let cell = &row[Column(col)];
+            if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+                continue;
+            }
+            let cell_style = sgr(&cell.fg, &cell.bg, cell.underline_color(), cell.flags);
+            if cell_style != style {
+                current.push_str(if cell_style.is_empty() { "\x1b[0m" } else { &cell_style });
+                style = cell_style;
+            }
+            current.push(cell.c);
+            if let Some(extra) = cell.zerowidth() {
+                current.extend(extra.iter());
+            }
+        }
+        if !style.is_empty() {
+            current.push_str("\x1b[0m");
+        }
+
+        if !wraps {
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    // Drop the trailing prompt line; the new shell prints its own
+    if lines.last().is_some_and(|line| !line.is_empty()) {
+        lines.pop();
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    Some(SavedScrollback::new(lines))
+}
+
+/// SGR sequence selecting a cell's colors and attributes (empty for the default style).
+#[cfg(target_os = "macos")]
+fn sgr(fg: &Color, bg: &Color, underline: Option<Color>, flags: Flags) -> String {
+    let mut params: Vec<String> = Vec::new();
+
+    for (flag, param) in [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::UNDERLINE, "4"),
+        (Flags::DOUBLE_UNDERLINE, "4:2"),
+        (Flags::UNDERCURL, "4:3"),
+        (Flags::DOTTED_UNDERLINE, "4:4"),
+        (Flags::DASHED_UNDERLINE, "4:5"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ] {
+        if flags.contains(flag) {
+            params.push(param.to_string());
+        }
+    }
+
+    params.extend(color_param(fg, 30, 90, 38));
+    params.extend(color_param(bg, 40, 100, 48));
+    if let Some(color) = underline {
+        params.extend(color_param(&color, 0, 0, 58).filter(|_| !matches!(color, Color::Named(_))));
+    }
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[0;{}m", params.join(";"))
+    }
+}
+
+/// SGR parameter for a color; `None` for the default foreground/background.
+#[cfg(target_os = "macos")]
+fn color_param(color: &Color, normal: u8, bright: u8, extended: u8) -> Option<String> {
+    match *color {
+        Color::Spec(rgb) => Some(format!("{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b)),
+        Color::Indexed(index) => Some(format!("{extended};5;{index}")),
+        Color::Named(named) => {
+            let index = named as usize;
+            let dim_black = NamedColor::DimBlack as usize;
+            if index < 8 {
+                Some((normal as usize + index).to_string())
+            } else if index < 16 {
+                Some((bright as usize + index - 8).to_string())
+            } else if (dim_black..dim_black + 8).contains(&index) {
+                // Dim colors come back through the DIM flag
+                Some((normal as usize + index - dim_black).to_string())
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "macos"))]
+mod tests {
+    use super::*;
+    use alacritty_terminal::event::VoidListener;
+    use alacritty_terminal::term::test::TermSize;
+    use alacritty_terminal::term::Config;
+    use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+
+    fn term_with(input: &str) -> Term<VoidListener> {
+        let mut term = Term::new(Config::default(), &TermSize::new(20, 6), VoidListener);
+        let mut parser = Processor::<StdSyncHandler>::new();
+        parser.advance(&mut term, input.as_bytes());
+        term
+    }
+
+    fn row_text(term: &Term<VoidListener>, line: i32) -> String {
+        let row = &term.grid()[Line(line)];
+        (0..term.columns()).map(|col| row[Column(col)].c).collect::<String>().trim_end().to_string()
+    }
+
+    #[test]
+    fn attributes_survive_a_round_trip() {
+        let term = term_with("\x1b[1;31mred\x1b[0m plain\r\n\x1b[4:3;38;2;1;2;3mcurl\x1b[0m\r\n$ ");
+        let saved = capture_scrollback(&term).unwrap();
+        assert_eq!(saved.lines.len(), 2, "prompt line is dropped: {:?}", saved.lines);
+
+        let restored = term_with(&String::from_utf8(saved.replay_bytes()).unwrap());
+        assert_eq!(row_text(&restored, 0), "red plain");
+        assert_eq!(row_text(&restored, 1), "curl");
+        assert_eq!(row_text(&restored, 2), "── restored ──");
+
+        let red = &restored.grid()[Line(0)][Column(0)];
+        assert!(red.flags.contains(Flags::BOLD));
+        assert_eq!(red.fg, Color::Named(NamedColor::Red));
+        let plain = &restored.grid()[Line(0)][Column(4)];
+        assert!(plain.flags.is_empty());
+        let curl = &restored.grid()[Line(1)][Column(0)];
+        assert!(curl.flags.contains(Flags::UNDERCURL));
+        assert_eq!(curl.fg, Color::Spec(alacritty_terminal::vte::ansi::Rgb { r: 1, g: 2, b: 3 }));
+    }
+
+    #[test]
+    fn soft_wrapped_rows_are_saved_as_one_line() {
+        let long = "x".repeat(30);
+        let term = term_with(&format!("{long}\r\n$ "));
+        let saved = capture_scrollback(&term).unwrap();
+        assert_eq!(saved.lines, vec![long]);
+    }
+
+    #[test]
+    fn alternate_screen_is_not_saved() {
+        let term = term_with("\x1b[?1049hfullscreen");
+        assert!(capture_scrollback(&term).is_none());
+    }
+
+    #[test]
+    fn saved_files_are_private() {
+        let dir = std::env::temp_dir().join(format!("tide-scrollback-private-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        create_private_dir(&dir.join("scrollback")).unwrap();
+        create_private_dir(&dir).unwrap();
+
+        let path = dir.join("scrollback").join("pane.json");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        write_private_file(&path, "{}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}");
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(&dir.join("scrollback")), 0o700);
+        assert_eq!(mode(&path), 0o600);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn size_caps_keep_newest_lines() {
+        let lines: Vec<String> = (0..MAX_SAVED_LINES + 10).map(|i| i.to_string()).collect();
+        let saved = SavedScrollback::new(lines);
+        assert_eq!(saved.lines.len(), MAX_SAVED_LINES);
+        assert_eq!(saved.lines.last().unwrap(), &(MAX_SAVED_LINES + 9).to_string());
+
+        let big = "y".repeat(MAX_SAVED_BYTES / 2);
+        let saved = SavedScrollback::new(vec![big.clone(), big.clone(), "tail".to_string()]);
+        assert_eq!(saved.lines, vec![big, "tail".to_string()]);
+    }
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::services::config::settings_file_path;
+use crate::services::diagnostics::Diagnostic;
+use crate::services::hints::HintSettings;
+use crate::services::shell_profile::ShellProfile;
+use crate::services::text_width::AmbiguousWidth;
+
+/// Per-workspace settings, keyed by workspace root in `Settings::workspaces`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceSettings {
+    /// Color scheme file name in `~/.config/tide/themes` (`None` = built-in palette)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_scheme: Option<String>,
+    /// Shell profile new panes start with (`None` = the built-in default profile)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Show notifications from programs in this workspace (`None` = the global setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<bool>,
+}
+
+/// Terminal font configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FontSettings {
+    /// Primary font family
+    pub family: String,
+    /// Families tried in order for glyphs the primary font lacks (CJK, emoji)
+    pub fallback: Vec<String>,
+    /// Font size in points
+    pub size: f32,
+    /// Cell height as a multiple of the font's line height
+    pub line_height: f64,
+    /// Extra horizontal space added to every cell, in pixels
+    pub letter_spacing: f64,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        Self {
+            family: "Menlo".to_string(),
+            fallback: [
+                "SF Mono",
+                "DejaVu Sans Mono",
+                "Noto Sans Mono",
+                "PingFang SC",
+                "Noto Sans CJK SC",
+                "Apple Color Emoji",
+                "Noto Color Emoji",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            size: 13.0,
+            line_height: 1.25,
+            letter_spacing: 0.0,
+        }
+    }
+}
+
+/// Default cursor shape, used until a program requests another one (DECSCUSR).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CursorShape {
+    #[default]
+    Block,
+    Beam,
+    Underline,
+}
+
+/// Terminal cursor configuration.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct CursorSettings {
+    pub shape: CursorShape,
+    /// Blink by default; programs can still turn blinking on or off
+    pub blinking: bool,
+}
+
+/// Notifications posted by terminal programs (OSC 9 / OSC 777).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Default for workspaces without their own setting
+    pub enabled: bool,
+    /// Most notifications a single pane may post per minute; extras are dropped
+    pub max_per_minute: u32,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_per_minute: 6,
+        }
+    }
+}
+
+/// Inline images drawn by programs (kitty graphics, iTerm2 `OSC 1337 ; File=`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ImageSettings {
+    pub enabled: bool,
+    /// Memory decoded images may take in each pane; the least recently shown are evicted
+    pub memory_limit_mb: usize,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            memory_limit_mb: 256,
+        }
+    }
+}
+
+impl ImageSettings {
+    /// The memory limit in bytes.
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+/// User settings stored in `~/.config/tide/settings.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Settings {
+    pub version: u32,
+    #[serde(default)]
+    pub font: FontSettings,
+    #[serde(default)]
+    pub cursor: CursorSettings,
+    /// Columns for East Asian ambiguous-width characters; match the shell's locale
+    #[serde(default)]
+    pub ambiguous_width: AmbiguousWidth,
+    #[serde(default)]
+    pub profiles: Vec<ShellProfile>,
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+    #[serde(default)]
+    pub images: ImageSettings,
+    #[serde(default)]
+    pub hints: HintSettings,
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, WorkspaceSettings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            font: FontSettings::default(),
+            cursor: CursorSettings::default(),
+            ambiguous_width: AmbiguousWidth::default(),
+            profiles: Vec::new(),
+            notifications: NotificationSettings::default(),
+            images: ImageSettings::default(),
+            hints: HintSettings::default(),
+            workspaces: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Settings for the workspace rooted at `root` (defaults if none are stored).
+    pub fn workspace(&self, root: &Path) -> WorkspaceSettings {
+        self.workspaces
+            .get(&workspace_key(root))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Profile named `name`; the built-in default when it is unknown or `None`.
+    pub fn profile(&self, name: Option<&str>) -> ShellProfile {
+        name.and_then(|name| self.profiles.iter().find(|profile| profile.name == name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Profile new panes in the workspace rooted at `root` start with.
+    pub fn workspace_profile(&self, root: &Path) -> ShellProfile {
+        self.profile(self.workspace(root).default_profile.as_deref())
+    }
+
+    /// Whether program notifications are shown for the workspace rooted at `root`.
+    pub fn notifications_enabled(&self, root: &Path) -> bool {
+        self.workspace(root)
+            .notifications
+            .unwrap_or(self.notifications.enabled)
+    }
+
+    /// Mutable settings for the workspace rooted at `root`, created on demand.
+    pub fn workspace_mut(&mut self, root: &Path) -> &mut WorkspaceSettings {
+        self.workspaces.entry(workspace_key(root)).or_default()
+    }
+
+    /// Top-level keys whose values differ in `other` (`font`, `profiles`, ...).
+    pub fn changed_fields(&self, other: &Settings) -> Vec<String> {
+        let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(other))
+        else {
+            return Vec::new();
+        };
+        new.iter()
+            .filter(|(key, value)| old.get(*key) != Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
+
+fn workspace_key(root: &Path) -> String {
+    root.to_string_lossy().into_owned()
+}
+
+/// The settings, or the defaults with the reason when the file cannot be used.
+pub fn load_settings() -> (Settings, Vec<(PathBuf, Diagnostic)>) {
+    match read_settings() {
+        Ok(settings) => (settings, Vec::new()),
+        Err(diagnostic) => {
+            let path = settings_file_path();
+            crate::logging::log_line("WARN", &format!("{}:{diagnostic}; using the default settings", path.display()));
+            (Settings::default(), vec![(path, diagnostic)])
+        }
+    }
+}
+
+/// The settings file; the defaults when it does not exist, where it went wrong when it
+/// cannot be read or parsed.
+pub fn read_settings() -> Result<Settings, Diagnostic> {
+    let path = settings_file_path();
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|err| Diagnostic { line: 1, column: 1, message: format!("cannot read the file: {err}") })?;
+    serde_json::from_str(&content).map_err(|err| Diagnostic::from_serde(&content, &content, &err))
+}
+
+pub fn save_settings(settings: &Settings) {
+    let path = settings_file_path();
+    match serde_json::to_string_pretty(settings) {
+        Ok(content) => {
+            if let Err(err) = fs::write(path, content) {
+                eprintln!("Failed to write settings file: {}", err);
+            }
+        }
+        Err(err) => {
+            eprintln!("Failed to serialize settings: {}", err);
+        }
+    }
+}
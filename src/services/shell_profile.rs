@@ -0,0 +1,152 @@
+//! Shell profiles - the program, arguments and environment a terminal pane starts with.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name shown for the built-in profile (the user's login shell).
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// A named way to start a terminal, stored in `settings.json` under `profiles`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ShellProfile {
+    pub name: String,
+    /// Program to run (`None` = the user's shell from `$SHELL`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Variables added to (or overriding) the inherited environment
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Variables removed from the inherited environment
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub env_remove: Vec<String>,
+    /// Start the program as a login shell (`-l`); by default only the user's shell is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub login: Option<bool>,
+    /// Startup directory: absolute, `~/`-relative, or relative to the workspace root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+impl Default for ShellProfile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE_NAME.to_string(),
+            program: None,
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            env_remove: Vec::new(),
+            login: None,
+            cwd: None,
+        }
+    }
+}
+
+impl ShellProfile {
+    /// Whether this profile starts the user's shell exactly like the built-in default.
+    pub fn is_default_shell(&self) -> bool {
+        self.program.is_none() && self.args.is_empty() && self.is_login()
+    }
+
+    /// Whether `-l` is passed: as set, otherwise only when no `program` is given, since
+    /// other programs (`nix develop`, `ssh host`) do not take it.
+    pub fn is_login(&self) -> bool {
+        self.login.unwrap_or(self.program.is_none())
+    }
+
+    /// Program and arguments a non-default profile runs; `default_shell` stands in for
+    /// a missing `program`.
+    pub fn command_line(&self, default_shell: &str) -> Vec<String> {
+        let program = self.resolved_program().unwrap_or_else(|| default_shell.to_string());
+        let login = self.is_login().then(|| "-l".to_string());
+        std::iter::once(program)
+            .chain(login)
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+
+    /// Program with a leading `~/` expanded.
+    pub fn resolved_program(&self) -> Option<String> {
+        self.program
+            .as_deref()
+            .map(|program| expand_home(program).to_string_lossy().into_owned())
+    }
+
+    /// Directory the shell starts in.
+    pub fn startup_dir(&self, workspace_root: &Path) -> PathBuf {
+        match self.cwd.as_deref().map(str::trim) {
+            None | Some("") => workspace_root.to_path_buf(),
+            Some(dir) => {
+                let path = expand_home(dir);
+                if path.is_absolute() {
+                    path
+                } else {
+                    workspace_root.join(path)
+                }
+            }
+        }
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let home = || dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    if path == "~" {
+        home()
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        home().join(rest)
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn startup_dir_resolves_relative_to_workspace() {
+        let root = Path::new("/work/project");
+        let mut profile = ShellProfile::default();
+        assert_eq!(profile.startup_dir(root), root);
+
+        profile.cwd = Some("frontend".to_string());
+        assert_eq!(profile.startup_dir(root), root.join("frontend"));
+
+        profile.cwd = Some("/tmp".to_string());
+        assert_eq!(profile.startup_dir(root), PathBuf::from("/tmp"));
+
+        profile.cwd = Some("~/src".to_string());
+        assert!(profile.startup_dir(root).ends_with("src"));
+        assert!(profile.startup_dir(root).is_absolute());
+    }
+
+    #[test]
+    fn partial_profile_uses_defaults() {
+        let profile: ShellProfile =
+            serde_json::from_str(r#"{"name": "bash --norc", "program": "bash", "args": ["--norc"], "login": false}"#)
+                .unwrap();
+        assert_eq!(profile.program.as_deref(), Some("bash"));
+        assert!(profile.env.is_empty());
+        assert!(profile.cwd.is_none());
+        assert!(!profile.is_default_shell());
+        assert!(ShellProfile::default().is_default_shell());
+    }
+
+    #[test]
+    fn only_the_users_shell_is_a_login_shell_by_default() {
+        let nix: ShellProfile =
+            serde_json::from_str(r#"{"name": "nix develop", "program": "nix", "args": ["develop"]}"#).unwrap();
+        assert_eq!(nix.command_line("/bin/zsh"), ["nix", "develop"]);
+
+        let zsh: ShellProfile =
+            serde_json::from_str(r#"{"name": "zsh", "program": "/bin/zsh", "login": true}"#).unwrap();
+        assert_eq!(zsh.command_line("/bin/bash"), ["/bin/zsh", "-l"]);
+
+        let clean: ShellProfile = serde_json::from_str(r#"{"name": "clean", "env": {"LANG": "C"}}"#).unwrap();
+        assert!(clean.is_login());
+        assert_eq!(clean.command_line("/bin/zsh"), ["/bin/zsh", "-l"]);
+    }
+}
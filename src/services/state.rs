@@ -1,9 +1,18 @@
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use crate::logging;
 use crate::services::config::state_file_path;
+use crate::services::diagnostics::{key_offset, offset_in, Diagnostic};
 use dirs::home_dir;
 
+/// Format of `state.json` written by this version.
+const STATE_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
     pub version: u32,
@@ -15,42 +24,152 @@ impl Default for AppState {
     fn default() -> Self {
         let root = std::env::current_dir().unwrap_or_else(|_| home_dir().expect("Could not find home directory"));
         Self {
-            version: 1,
+            version: STATE_VERSION,
             workspaces: vec![root],
             active_workspace_index: 0,
         }
     }
 }
 
-pub fn load_state() -> AppState {
-    let path = state_file_path();
+/// State file given on the command line (`--state`), used instead of the default one.
+static STATE_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Load and save state in `path` for the rest of the run.
+pub fn set_state_file(path: PathBuf) {
+    let _ = STATE_FILE.set(path);
+}
+
+/// Cleared by `--no-restore`: the run leaves the saved session as it was.
+static SAVING: AtomicBool = AtomicBool::new(true);
+
+/// Stop saving the state and the scrollback for the rest of the run.
+pub fn disable_saving() {
+    SAVING.store(false, Ordering::Relaxed);
+}
+
+pub fn saving_enabled() -> bool {
+    SAVING.load(Ordering::Relaxed)
+}
+
+fn state_path() -> PathBuf {
+    STATE_FILE.get().cloned().unwrap_or_else(state_file_path)
+}
+
+/// The saved session, with the problems found in its file for the control center.
+pub fn load_state() -> (AppState, Vec<(PathBuf, Diagnostic)>) {
+    let path = state_path();
     if !path.exists() {
-        return AppState::default();
+        return (AppState::default(), Vec::new());
     }
 
     match fs::read_to_string(&path) {
-        Ok(content) => match serde_json::from_str(&content) {
-            Ok(state) => state,
-            Err(err) => {
-                eprintln!("Failed to parse state file: {}", err);
-                AppState::default()
+        Ok(content) => {
+            let (state, diagnostics) = parse_state(&content);
+            for diagnostic in &diagnostics {
+                logging::log_line("WARN", &format!("{}:{diagnostic}", path.display()));
             }
-        },
+            let state = state.unwrap_or_else(|| {
+                logging::log_line("ERROR", &format!("{}: no workspaces to restore; starting fresh", path.display()));
+                AppState::default()
+            });
+            (state, diagnostics.into_iter().map(|diagnostic| (path.clone(), diagnostic)).collect())
+        }
         Err(err) => {
-            eprintln!("Failed to read state file: {}", err);
-            AppState::default()
+            logging::log_line("ERROR", &format!("Failed to read state file {}: {err}", path.display()));
+            let diagnostic = Diagnostic { line: 1, column: 1, message: format!("cannot read the file: {err}") };
+            (AppState::default(), vec![(path, diagnostic)])
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawState<'a> {
+    #[serde(default)]
+    version: Option<Value>,
+    #[serde(borrow)]
+    workspaces: Vec<&'a RawValue>,
+    #[serde(default)]
+    active_workspace_index: Option<Value>,
+}
+
+/// Parse `state.json`, skipping workspace entries that are not paths. `None` when
+/// nothing can be restored (broken JSON, or no workspaces left).
+pub fn parse_state(content: &str) -> (Option<AppState>, Vec<Diagnostic>) {
+    let mut diagnostics = Vec::new();
+    let root: &RawValue = match serde_json::from_str(content) {
+        Ok(root) => root,
+        Err(err) => {
+            diagnostics.push(Diagnostic::from_serde(content, content, &err));
+            return (None, diagnostics);
+        }
+    };
+    let raw: RawState = match serde_json::from_str(root.get()) {
+        Ok(raw) => raw,
+        Err(err) => {
+            diagnostics.push(Diagnostic::from_serde(content, root.get(), &err));
+            return (None, diagnostics);
+        }
+    };
+    let field_at = |key: &str| offset_in(content, root.get()) + key_offset(root, key).unwrap_or(0);
+
+    if raw.version.as_ref().and_then(Value::as_u64) != Some(STATE_VERSION as u64) {
+        let version = raw.version.map_or("none".to_string(), |version| version.to_string());
+        diagnostics.push(Diagnostic::at(
+            content,
+            field_at("version"),
+            format!("unknown version {version}; read as version {STATE_VERSION}"),
+        ));
+    }
+
+    let mut workspaces = Vec::new();
+    for entry in raw.workspaces {
+        // Missing folders are kept; the user decides whether to close them
+        match serde_json::from_str::<PathBuf>(entry.get()) {
+            Ok(path) => workspaces.push(path),
+            Err(err) => {
+                let mut diagnostic = Diagnostic::from_serde(content, entry.get(), &err);
+                diagnostic.message.push_str("; workspace skipped");
+                diagnostics.push(diagnostic);
+            }
         }
     }
+    if workspaces.is_empty() {
+        return (None, diagnostics);
+    }
+
+    let index = raw.active_workspace_index.as_ref().and_then(Value::as_u64);
+    let active_workspace_index = match index.map(|index| index as usize) {
+        Some(index) if index < workspaces.len() => index,
+        _ => {
+            let index = raw.active_workspace_index.map_or("none".to_string(), |index| index.to_string());
+            diagnostics.push(Diagnostic::at(
+                content,
+                field_at("active_workspace_index"),
+                format!("active_workspace_index {index} is not a workspace; using the first"),
+            ));
+            0
+        }
+    };
+
+    let state = AppState {
+        version: STATE_VERSION,
+        workspaces,
+        active_workspace_index,
+    };
+    (Some(state), diagnostics)
 }
 
 pub fn save_state(workspaces: &[PathBuf], active_index: usize) {
+    if !saving_enabled() {
+        return;
+    }
     let state = AppState {
-        version: 1,
+        version: STATE_VERSION,
         workspaces: workspaces.to_vec(),
         active_workspace_index: active_index,
     };
 
-    let path = state_file_path();
+    let path = state_path();
     match serde_json::to_string_pretty(&state) {
         Ok(content) => {
             if let Err(err) = fs::write(path, content) {
@@ -62,3 +181,52 @@ pub fn save_state(workspaces: &[PathBuf], active_index: usize) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(diagnostics: &[Diagnostic]) -> Vec<(usize, usize)> {
+        diagnostics.iter().map(|diagnostic| (diagnostic.line, diagnostic.column)).collect()
+    }
+
+    #[test]
+    fn bad_workspace_entries_are_skipped() {
+        let content = r#"{
+  "version": 1,
+  "workspaces": ["/src/api", 42, "/gone/but/kept", null],
+  "active_workspace_index": 1
+}"#;
+        let (state, diagnostics) = parse_state(content);
+        let state = state.unwrap();
+        assert_eq!(state.workspaces, vec![PathBuf::from("/src/api"), PathBuf::from("/gone/but/kept")]);
+        assert_eq!(state.active_workspace_index, 1);
+        assert_eq!(positions(&diagnostics), vec![(3, 31), (3, 55)]);
+        assert!(diagnostics[0].message.ends_with("; workspace skipped"), "{}", diagnostics[0]);
+    }
+
+    #[test]
+    fn active_index_out_of_range_is_the_first() {
+        let (state, diagnostics) =
+            parse_state(r#"{ "version": 1, "workspaces": ["/a", "/b"], "active_workspace_index": 5 }"#);
+        assert_eq!(state.unwrap().active_workspace_index, 0);
+        assert_eq!(positions(&diagnostics), vec![(1, 45)]);
+    }
+
+    #[test]
+    fn nothing_to_restore_is_none() {
+        let (state, diagnostics) = parse_state(r#"{ "version": 1, "workspaces": [], "active_workspace_index": 0 }"#);
+        assert_eq!((state.is_none(), diagnostics.len()), (true, 0));
+
+        let (state, diagnostics) = parse_state("{ \"version\": 1,\n  \"workspaces\": [\"/a\",, ] }");
+        assert!(state.is_none());
+        assert_eq!(positions(&diagnostics), vec![(2, 23)]);
+    }
+
+    #[test]
+    fn unknown_versions_are_still_read() {
+        let (state, diagnostics) = parse_state(r#"{ "version": 3, "workspaces": ["/a"], "active_workspace_index": 0 }"#);
+        assert_eq!(state.unwrap().workspaces, vec![PathBuf::from("/a")]);
+        assert_eq!(diagnostics[0].message, "unknown version 3; read as version 1");
+    }
+}
@@ -0,0 +1,440 @@
+//! Tasks a workspace already declares — `package.json` scripts, Makefile targets,
+//! justfile recipes, Cargo binaries and examples, pyproject scripts — offered as
+//! launchers in the control center's "Tasks" group.
+//!
+//! Each task becomes a launcher named after its command (`npm run dev`, `make test`),
+//! which runs in a new split. Files that fail to parse are logged and skipped.
+
+use crate::logging;
+use crate::services::launcher::{Launcher, LauncherRunIn};
+use crate::services::launcher_vars::shell_quote;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Group the discovered launchers are listed under.
+pub const TASKS_GROUP: &str = "Tasks";
+
+const MAKEFILES: &[&str] = &["GNUmakefile", "makefile", "Makefile"];
+const JUSTFILES: &[&str] = &["justfile", "Justfile", ".justfile"];
+
+/// Files tasks are read from, whether or not they exist, for watching.
+pub fn task_files(workspace_root: &Path) -> Vec<PathBuf> {
+    ["package.json", "Cargo.toml", "pyproject.toml"]
+        .iter()
+        .chain(MAKEFILES)
+        .chain(JUSTFILES)
+        .map(|name| workspace_root.join(name))
+        .collect()
+}
+
+/// Tasks declared in the files at `workspace_root`.
+pub fn discover_tasks(workspace_root: &Path) -> Vec<Launcher> {
+    let mut tasks = Vec::new();
+    let mut read = |name: &str, parse: &dyn Fn(&str) -> Result<Vec<Launcher>, String>| {
+        let path = workspace_root.join(name);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return false;
+        };
+        match parse(&content) {
+            Ok(found) => tasks.extend(found),
+            Err(err) => logging::log_line("WARN", &format!("{}: {err}; its tasks are skipped", path.display())),
+        }
+        true
+    };
+    read("package.json", &|content| npm_tasks(workspace_root, content));
+    // make and just read the first of their file names that exists
+    let _ = MAKEFILES.iter().any(|name| read(name, &|content| Ok(make_tasks(content))));
+    let _ = JUSTFILES.iter().any(|name| read(name, &|content| Ok(just_tasks(content))));
+    read("Cargo.toml", &|content| cargo_tasks(workspace_root, content));
+    read("pyproject.toml", &|content| python_tasks(workspace_root, content));
+    tasks
+}
+
+/// `launchers` followed by the `tasks` whose names are not taken by a launcher.
+pub fn with_tasks(mut launchers: Vec<Launcher>, tasks: Vec<Launcher>) -> Vec<Launcher> {
+    for task in tasks {
+        if !launchers.iter().any(|launcher| launcher.name == task.name) {
+            launchers.push(task);
+        }
+    }
+    launchers
+}
+
+/// A launcher running `command` in a new split; `execute` is off for tasks that need
+/// arguments typed after them.
+fn task(command: String, execute: bool) -> Launcher {
+    Launcher {
+        execute,
+        group: Some(TASKS_GROUP.to_string()),
+        // Task names are not placeholders
+        ..Launcher::new(&command, &command.replace("${", "$${"), LauncherRunIn::NewSplit)
+    }
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    scripts: BTreeMap<String, serde_json::Value>,
+    #[serde(default, rename = "packageManager")]
+    package_manager: Option<String>,
+}
+
+fn npm_tasks(root: &Path, content: &str) -> Result<Vec<Launcher>, String> {
+    let package: PackageJson = serde_json::from_str(content).map_err(|err| err.to_string())?;
+    // `"packageManager": "pnpm@9.1.0"`, or the lock file in the root
+    let manager = package
+        .package_manager
+        .as_deref()
+        .and_then(|spec| spec.split('@').next())
+        .filter(|name| ["npm", "pnpm", "yarn", "bun"].contains(name))
+        .or_else(|| {
+            [("pnpm-lock.yaml", "pnpm"), ("yarn.lock", "yarn"), ("bun.lockb", "bun"), ("bun.lock", "bun")]
+                .into_iter()
+                .find(|(lock, _)| root.join(lock).exists())
+                .map(|(_, manager)| manager)
+        })
+        .unwrap_or("npm");
+
+    let scripts = &package.scripts;
+    Ok(scripts
+        .keys()
+        // `prebuild` and `postbuild` run along with `build`
+        .filter(|name| {
+            let hook_of = name.strip_prefix("pre").or_else(|| name.strip_prefix("post"));
+            !hook_of.is_some_and(|script| scripts.contains_key(script))
+        })
+        .map(|name| task(format!("{manager} run {}", shell_quote(name)), true))
+        .collect())
+}
+
+/// Targets of rules, without special (`.PHONY`), pattern (`%.o`), file (`main.o`) and
+/// variable targets.
+fn make_tasks(content: &str) -> Vec<Launcher> {
+    let mut targets: Vec<&str> = Vec::new();
+    let mut continued = false;
+    let mut in_define = false;
+    for line in content.lines() {
+        let was_continued = continued;
+        continued = line.ends_with('\\');
+        let word = line.split_whitespace().next().unwrap_or_default();
+        if in_define {
+            in_define = word != "endef";
+            continue;
+        }
+        if word == "define" {
+            in_define = true;
+            continue;
+        }
+        // Recipe lines, continuations and comments
+        if was_continued || line.starts_with(['\t', ' ', '#']) {
+            continue;
+        }
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `NAME := value`, `NAME ::= value`, `NAME = a:b`
+        if rest.starts_with('=') || rest.starts_with(":=") || head.contains('=') {
+            continue;
+        }
+        for target in head.split_whitespace() {
+            let plain = target.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-');
+            if plain && !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    targets.into_iter().map(|target| task(format!("make {target}"), true)).collect()
+}
+
+const JUST_KEYWORDS: &[&str] = &["alias", "export", "import", "mod", "set", "unexport"];
+
+/// Public recipes. Recipes with required parameters are typed without Enter, for the
+/// arguments to be added.
+fn just_tasks(content: &str) -> Vec<Launcher> {
+    let mut tasks = Vec::new();
+    let mut private = false;
+    for line in content.lines() {
+        if line.starts_with([' ', '\t']) || line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // Attributes like `[private]` or `[group('ci')]` apply to the next recipe
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        let private_recipe = std::mem::take(&mut private);
+        let header = line.strip_prefix('@').unwrap_or(line);
+        let name_end = header
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(header.len());
+        let (name, rest) = header.split_at(name_end);
+        if name.is_empty() || (JUST_KEYWORDS.contains(&name) && rest.starts_with(' ')) {
+            continue;
+        }
+        let Some(colon) = unquoted_colon(rest) else {
+            continue;
+        };
+        // `name := value` is a variable
+        if rest[colon..].starts_with(":=") || private_recipe || name.starts_with('_') {
+            continue;
+        }
+        let needs_arguments = rest[..colon]
+            .split_whitespace()
+            .any(|parameter| !parameter.contains('=') && !parameter.starts_with('*'));
+        tasks.push(task(format!("just {name}"), !needs_arguments));
+    }
+    tasks
+}
+
+/// Byte offset of the first `:` outside quotes (parameter defaults may contain one).
+fn unquoted_colon(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (at, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, ':') => return Some(at),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Deserialize, Default)]
+struct CargoManifest {
+    package: Option<CargoPackage>,
+    workspace: Option<CargoWorkspace>,
+    #[serde(default)]
+    bin: Vec<CargoTarget>,
+    #[serde(default)]
+    example: Vec<CargoTarget>,
+}
+
+#[derive(Deserialize)]
+struct CargoPackage {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CargoWorkspace {
+    #[serde(default)]
+    members: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CargoTarget {
+    name: Option<String>,
+}
+
+/// `cargo build` and `cargo test`, a build of each workspace member, and a run of each
+/// binary and example.
+fn cargo_tasks(root: &Path, content: &str) -> Result<Vec<Launcher>, String> {
+    let manifest: CargoManifest = toml::from_str(content).map_err(|err| err.message().to_string())?;
+    let mut tasks = vec![task("cargo build".to_string(), true), task("cargo test".to_string(), true)];
+    if let Some(package) = &manifest.package {
+        tasks.extend(cargo_target_tasks(root, &manifest, &package.name, ""));
+    }
+
+    let members = manifest.workspace.iter().flat_map(|workspace| &workspace.members);
+    for dir in members.flat_map(|member| member_dirs(root, member)) {
+        let member_manifest = fs::read_to_string(dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| toml::from_str::<CargoManifest>(&content).ok());
+        let Some((member, package)) = member_manifest.and_then(|member| {
+            let name = member.package.as_ref()?.name.clone();
+            Some((member, name))
+        }) else {
+            continue;
+        };
+        tasks.push(task(format!("cargo build -p {}", shell_quote(&package)), true));
+        let selector = format!("-p {} ", shell_quote(&package));
+        tasks.extend(cargo_target_tasks(&dir, &member, &package, &selector));
+    }
+    Ok(tasks)
+}
+
+/// Directories of a `workspace.members` entry; a trailing `/*` lists subdirectories.
+fn member_dirs(root: &Path, member: &str) -> Vec<PathBuf> {
+    let Some(parent) = member.strip_suffix("/*") else {
+        return vec![root.join(member)];
+    };
+    let mut dirs: Vec<PathBuf> = fs::read_dir(root.join(parent))
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect())
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+/// `cargo run` tasks for the binaries and examples of the package at `dir`, declared in
+/// the manifest or found where Cargo looks for them (`src/main.rs`, `src/bin`, `examples`).
+fn cargo_target_tasks(dir: &Path, manifest: &CargoManifest, package: &str, selector: &str) -> Vec<Launcher> {
+    let declared = |targets: &[CargoTarget]| targets.iter().filter_map(|target| target.name.clone()).collect::<Vec<_>>();
+    let mut bins = declared(&manifest.bin);
+    if dir.join("src/main.rs").is_file() {
+        bins.push(package.to_string());
+    }
+    bins.extend(rust_sources(&dir.join("src/bin")));
+    let mut examples = declared(&manifest.example);
+    examples.extend(rust_sources(&dir.join("examples")));
+
+    let mut tasks = Vec::new();
+    for (kind, mut names) in [("bin", bins), ("example", examples)] {
+        names.sort();
+        names.dedup();
+        tasks.extend(
+            names
+                .into_iter()
+                .map(|name| task(format!("cargo run {selector}--{kind} {}", shell_quote(&name)), true)),
+        );
+    }
+    tasks
+}
+
+/// Names of `name.rs` files and `name/main.rs` directories in `dir`.
+fn rust_sources(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            if !(path.extension().is_some_and(|ext| ext == "rs") || path.join("main.rs").is_file()) {
+                return None;
+            }
+            path.file_stem()?.to_str().map(String::from)
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Default)]
+struct PyProject {
+    #[serde(default)]
+    project: PyScripts,
+    #[serde(default)]
+    tool: PyTools,
+}
+
+#[derive(Deserialize, Default)]
+struct PyTools {
+    poetry: Option<PyScripts>,
+}
+
+#[derive(Deserialize, Default)]
+struct PyScripts {
+    #[serde(default)]
+    scripts: BTreeMap<String, toml::Value>,
+}
+
+/// `[project.scripts]` and `[tool.poetry.scripts]`, run through the project's tool when
+/// its lock file is there (`uv run serve`), otherwise by name from the active environment.
+fn python_tasks(root: &Path, content: &str) -> Result<Vec<Launcher>, String> {
+    let pyproject: PyProject = toml::from_str(content).map_err(|err| err.message().to_string())?;
+    let poetry = pyproject.tool.poetry.is_some();
+    let runner = [("uv.lock", "uv run "), ("poetry.lock", "poetry run "), ("pdm.lock", "pdm run ")]
+        .into_iter()
+        .find(|(lock, _)| root.join(lock).exists())
+        .map(|(_, runner)| runner)
+        .unwrap_or(if poetry { "poetry run " } else { "" });
+
+    let mut names: Vec<&String> = pyproject.project.scripts.keys().collect();
+    names.extend(pyproject.tool.poetry.iter().flat_map(|poetry| poetry.scripts.keys()));
+    names.sort();
+    names.dedup();
+    Ok(names
+        .into_iter()
+        .map(|name| task(format!("{runner}{}", shell_quote(name)), true))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tasks").join(name)
+    }
+
+    fn commands(tasks: &[Launcher]) -> Vec<&str> {
+        tasks.iter().map(|task| task.command.as_str()).collect()
+    }
+
+    #[test]
+    fn node_scripts_make_targets_and_just_recipes() {
+        let tasks = discover_tasks(&fixture("node-app"));
+        assert_eq!(
+            commands(&tasks),
+            vec![
+                "pnpm run build",
+                "pnpm run dev",
+                "pnpm run 'lint fix'",
+                "pnpm run preview",
+                "pnpm run test:unit",
+                "make all",
+                "make test",
+                "make clean",
+                "make install",
+                "make release",
+                "just default",
+                "just fmt",
+                "just serve",
+                "just deploy",
+            ]
+        );
+        assert!(tasks.iter().all(|task| task.group.as_deref() == Some(TASKS_GROUP)));
+        assert!(tasks.iter().all(|task| task.name == task.command && task.run_in == LauncherRunIn::NewSplit));
+        // `deploy env` needs an argument typed after it
+        let execute: Vec<_> = tasks.iter().filter(|task| !task.execute).map(|task| task.name.as_str()).collect();
+        assert_eq!(execute, vec!["just deploy"]);
+    }
+
+    #[test]
+    fn cargo_workspace_members_bins_and_examples() {
+        let tasks = discover_tasks(&fixture("rust-workspace"));
+        assert_eq!(
+            commands(&tasks),
+            vec![
+                "cargo build",
+                "cargo test",
+                "cargo run --bin tool",
+                "cargo run --bin tool-admin",
+                "cargo run --example demo",
+                "cargo build -p core-lib",
+                "cargo run -p core-lib --example bench",
+                "cargo build -p app",
+                "cargo run -p app --bin app",
+                "cargo run -p app --bin worker",
+            ]
+        );
+    }
+
+    #[test]
+    fn pyproject_scripts_run_through_the_locked_tool() {
+        let tasks = discover_tasks(&fixture("python-app"));
+        assert_eq!(commands(&tasks), vec!["uv run cli", "uv run serve"]);
+
+        let tasks = python_tasks(Path::new("/nonexistent"), "[tool.poetry.scripts]\nserve = \"app:main\"\n").unwrap();
+        assert_eq!(commands(&tasks), vec!["poetry run serve"]);
+    }
+
+    #[test]
+    fn broken_files_are_skipped() {
+        assert!(npm_tasks(Path::new("/nonexistent"), "{ \"scripts\": ").is_err());
+        assert!(cargo_tasks(Path::new("/nonexistent"), "[package\nname = 1").is_err());
+        assert!(discover_tasks(&fixture("missing")).is_empty());
+        assert_eq!(task_files(&fixture("node-app")).len(), 9);
+    }
+
+    #[test]
+    fn launchers_hide_tasks_with_the_same_name() {
+        let mut build = Launcher::new("make test", "make test TESTS=unit", LauncherRunIn::Current);
+        build.group = Some("Mine".to_string());
+        let tasks = make_tasks("test:\n\tcargo test\nlint:\n\tcargo clippy\n");
+        let merged = with_tasks(vec![build.clone()], tasks);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0], build);
+        assert_eq!(merged[1].name, "make lint");
+    }
+}
@@ -1,5 +1,19 @@
 use crate::logging;
 
+/// How a new session starts, besides its shell profile.
+#[derive(Default)]
+pub struct SessionOptions<'a> {
+    /// Output from a previous session (see `services::scrollback`), shown above the new
+    /// shell's first prompt
+    pub replay: &'a [u8],
+    /// Palette color queries are answered with until `set_color_palette` (black when
+    /// `None`)
+    #[cfg(target_os = "macos")]
+    pub palette: Option<[alacritty_terminal::vte::ansi::Rgb; alacritty_terminal::term::color::COUNT]>,
+    /// Lines of scrollback kept (a default when `None`)
+    pub scrollback: Option<usize>,
+}
+
 /// Platform-specific terminal session implementation.
 ///
 /// On macOS this is backed by `alacritty_terminal` and `portable-pty`. On
@@ -8,34 +22,92 @@ use crate::logging;
 #[cfg(target_os = "macos")]
 mod platform {
     use super::logging;
+    use super::SessionOptions;
+    use crate::services::inline_image::{
+        handle_sequence, visible_placements, ImageScanner, ImageStore, ScanItem, VisiblePlacement,
+    };
+    use crate::services::process_title::{process_args, title_from_args};
+    use crate::services::remote_control::{last_command_output, mark_command_line, CommandMarkHandler, CommandMarks};
+    use crate::services::settings::ImageSettings;
+    use crate::services::terminal_notification::{NotificationScanner, TerminalNotification};
+    use crate::services::text_width::{AmbiguousWidth, WidthPolicyHandler};
+    use crate::services::tmux::{
+        capture_pane_command, cursor_position_command, dispatch_tmux_event, parse_window_line,
+        resize_commands, send_keys_commands, ControlEvent, ControlModeScanner, ControlParser, TmuxEvent,
+        TmuxWindow, LIST_WINDOWS_COMMAND,
+    };
+    use crate::services::ShellProfile;
     use crate::services::{get_clipboard_string, set_clipboard_string};
     use alacritty_terminal::event::{Event, EventListener};
     use alacritty_terminal::grid::{Dimensions, Scroll};
     use alacritty_terminal::sync::FairMutex;
+    use alacritty_terminal::term::color::COUNT;
     use alacritty_terminal::term::{Config, Term};
-    use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+    use alacritty_terminal::vte::ansi::{CursorStyle, Processor, Rgb, StdSyncHandler};
     use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+    use std::collections::{HashMap, VecDeque};
     use std::io::{self, Read, Write};
-    use std::path::Path;
-    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-    use std::sync::{Arc, Mutex};
+    use std::os::fd::{AsRawFd, BorrowedFd};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, OnceLock};
     use std::thread::{self, JoinHandle};
-    use std::time::Instant;
+    use std::time::{Duration, Instant};
+
+    /// How often the foreground process group is checked for pane titles.
+    const FOREGROUND_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Formatter turning a color into the reply escape sequence for a color query.
+    pub(crate) type ColorFormatter = Arc<dyn Fn(Rgb) -> String + Send + Sync>;
+
+    /// Colors that color queries (OSC 4/10/11/12 `?`) are answered with.
+    ///
+    /// The listener answers each query as the parser reaches it, in order with the other
+    /// replies, but cannot lock the `Term` the parser holds. So the parser copies the
+    /// color the program has set for the queried index (if any) to `overrides` first
+    /// (see `WidthPolicyHandler::with_color_overrides`); anything else, including colors
+    /// reset through OSC 104/110/111, is answered from the pane palette.
+    #[derive(Clone)]
+    pub(crate) struct QueryColors {
+        pub palette: Arc<Mutex<[Rgb; COUNT]>>,
+        pub overrides: Arc<Mutex<[Option<Rgb>; COUNT]>>,
+    }
+
+    impl QueryColors {
+        pub fn new(palette: [Rgb; COUNT]) -> Self {
+            Self {
+                palette: Arc::new(Mutex::new(palette)),
+                overrides: Arc::new(Mutex::new([None; COUNT])),
+            }
+        }
+
+        /// Reply to a query for color `index`.
+        fn answer(&self, index: usize, formatter: &ColorFormatter) -> Option<String> {
+            if index >= COUNT {
+                return None;
+            }
+            let set = self.overrides.lock().ok()?[index];
+            let color = set.or_else(|| Some(self.palette.lock().ok()?[index]))?;
+            Some(formatter(color))
+        }
+    }
 
     #[derive(Clone)]
     pub struct TideEventListener {
         pty_writer: Arc<Mutex<Box<dyn Write + Send>>>,
         alive: Arc<AtomicBool>,
         on_title_change: Arc<dyn Fn(String) + Send + Sync>,
+        colors: QueryColors,
     }
 
     impl TideEventListener {
-        pub fn new(
+        pub(crate) fn new(
             pty_writer: Arc<Mutex<Box<dyn Write + Send>>>,
             alive: Arc<AtomicBool>,
             on_title_change: Arc<dyn Fn(String) + Send + Sync>,
+            colors: QueryColors,
         ) -> Self {
-            Self { pty_writer, alive, on_title_change }
+            Self { pty_writer, alive, on_title_change, colors }
         }
 
         fn write_to_pty(&self, text: &str) {
@@ -61,6 +133,9 @@ mod platform {
                     logging::log_line("DEBUG", &format!("Terminal title changed: {title}"));
                     (self.on_title_change)(title);
                 }
+                Event::ResetTitle => {
+                    (self.on_title_change)(String::new());
+                }
                 Event::Exit => {
                     logging::log_line("INFO", "Terminal requested exit");
                     self.alive.store(false, Ordering::SeqCst);
@@ -84,11 +159,44 @@ mod platform {
                         self.write_to_pty(&seq);
                     }
                 }
+                Event::ColorRequest(index, formatter) => {
+                    if let Some(reply) = self.colors.answer(index, &formatter) {
+                        self.write_to_pty(&reply);
+                    }
+                }
                 _ => {}
             }
         }
     }
 
+    /// Build the command for a shell profile.
+    fn shell_command(profile: &ShellProfile, workspace_root: &Path) -> CommandBuilder {
+        let mut cmd = if profile.is_default_shell() {
+            // Runs $SHELL as a login shell (argv[0] prefixed with `-`)
+            CommandBuilder::new_default_prog()
+        } else {
+            let default_shell = CommandBuilder::new_default_prog().get_shell();
+            CommandBuilder::from_argv(
+                profile
+                    .command_line(&default_shell)
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            )
+        };
+
+        cmd.cwd(profile.startup_dir(workspace_root));
+        cmd.env("TERM", "xterm-256color");
+        cmd.env("COLORTERM", "truecolor");
+        for key in &profile.env_remove {
+            cmd.env_remove(key);
+        }
+        for (key, value) in &profile.env {
+            cmd.env(key, value);
+        }
+        cmd
+    }
+
     /// Simple terminal dimensions used for `Term::new` and resize.
     #[derive(Clone, Copy)]
     struct TermDimensions {
@@ -119,6 +227,268 @@ mod platform {
         }
     }
 
+    /// Shown in a pane when tmux switches it to control mode, and when it leaves.
+    const TMUX_STARTED_MESSAGE: &[u8] =
+        b"\r\n\x1b[2m[tmux control mode: windows open as tabs; press Esc to detach]\x1b[0m\r\n";
+    const TMUX_ENDED_MESSAGE: &[u8] = b"\r\n\x1b[2m[tmux detached]\x1b[0m\r\n";
+
+    /// Command sent on a tmux control channel, kept until its reply arrives.
+    #[derive(Clone, Copy)]
+    enum TmuxCommand {
+        ListWindows,
+        Capture(u32),
+        Cursor(u32),
+        Other,
+    }
+
+    /// Sending side of a tmux control channel: the PTY of the pane running `tmux -CC`
+    /// and the commands waiting for a reply, in the order they were sent.
+    #[derive(Clone)]
+    struct TmuxChannel {
+        writer: Arc<Mutex<Box<dyn Write + Send>>>,
+        pending: Arc<Mutex<VecDeque<TmuxCommand>>>,
+    }
+
+    impl TmuxChannel {
+        /// Send one or more newline-terminated commands; `kind` is the last one.
+        fn send(&self, kind: TmuxCommand, commands: &str) -> io::Result<()> {
+            let mut pending = self.pending.lock().expect("tmux pending mutex poisoned");
+            let mut writer = self.writer.lock().expect("pty_writer mutex poisoned");
+            writer.write_all(commands.as_bytes())?;
+            let count = commands.lines().count();
+            for index in 0..count {
+                pending.push_back(if index + 1 == count { kind } else { TmuxCommand::Other });
+            }
+            Ok(())
+        }
+
+        fn reply_received(&self) -> TmuxCommand {
+            self.pending
+                .lock()
+                .ok()
+                .and_then(|mut pending| pending.pop_front())
+                .unwrap_or(TmuxCommand::Other)
+        }
+    }
+
+    /// A tmux pane shown in a Tide pane.
+    struct TmuxPaneTarget {
+        term: Arc<FairMutex<Term<TideEventListener>>>,
+        notify: Arc<dyn Fn() + Send + Sync>,
+        alive: Arc<AtomicBool>,
+        ambiguous_width: Arc<Mutex<AmbiguousWidth>>,
+        color_overrides: Arc<Mutex<[Option<Rgb>; COUNT]>>,
+        /// Set once the captured screen has been drawn; output before that is dropped
+        /// (the capture already contains it)
+        ready: bool,
+        size: Option<(u16, u16)>,
+    }
+
+    /// A pane in control mode and the tmux windows behind it.
+    struct TmuxGateway {
+        channel: TmuxChannel,
+        windows: Vec<TmuxWindow>,
+        panes: HashMap<u32, TmuxPaneTarget>,
+    }
+
+    static TMUX_GATEWAYS: OnceLock<Mutex<HashMap<usize, TmuxGateway>>> = OnceLock::new();
+    static NEXT_TMUX_GATEWAY: AtomicUsize = AtomicUsize::new(0);
+
+    /// Run `f` on gateway `gateway`, if it is still in control mode.
+    fn with_tmux_gateway<R>(gateway: usize, f: impl FnOnce(&mut TmuxGateway) -> R) -> Option<R> {
+        let mutex = TMUX_GATEWAYS.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut gateways = mutex.lock().ok()?;
+        gateways.get_mut(&gateway).map(f)
+    }
+
+    /// Input of a tmux pane: written bytes become `send-keys` commands.
+    struct TmuxPaneWriter {
+        channel: TmuxChannel,
+        pane: u32,
+    }
+
+    impl Write for TmuxPaneWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.channel.send(TmuxCommand::Other, &send_keys_commands(self.pane, buf))?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Control mode of a pane running `tmux -CC`, owned by the pane's IO thread.
+    struct TmuxControl {
+        gateway: usize,
+        root: PathBuf,
+        channel: TmuxChannel,
+        parser: ControlParser,
+        pane_parsers: HashMap<u32, Processor<StdSyncHandler>>,
+    }
+
+    impl TmuxControl {
+        fn start(root: PathBuf, writer: Arc<Mutex<Box<dyn Write + Send>>>) -> Self {
+            let gateway = NEXT_TMUX_GATEWAY.fetch_add(1, Ordering::SeqCst);
+            let channel = TmuxChannel {
+                writer,
+                pending: Arc::new(Mutex::new(VecDeque::new())),
+            };
+            if let Ok(mut gateways) = TMUX_GATEWAYS.get_or_init(|| Mutex::new(HashMap::new())).lock() {
+                gateways.insert(
+                    gateway,
+                    TmuxGateway {
+                        channel: channel.clone(),
+                        windows: Vec::new(),
+                        panes: HashMap::new(),
+                    },
+                );
+            }
+            logging::log_line("INFO", &format!("tmux control mode started (gateway {gateway})"));
+
+            let control = Self {
+                gateway,
+                root,
+                channel,
+                parser: ControlParser::new(),
+                pane_parsers: HashMap::new(),
+            };
+            control.send(TmuxCommand::ListWindows, LIST_WINDOWS_COMMAND);
+            control
+        }
+
+        fn send(&self, kind: TmuxCommand, commands: &str) {
+            if let Err(err) = self.channel.send(kind, commands) {
+                logging::log_line("ERROR", &format!("Failed to send tmux command: {err}"));
+            }
+        }
+
+        /// Handle control channel output. Returns the offset where control mode ended.
+        fn feed(&mut self, bytes: &[u8]) -> Option<usize> {
+            let (events, end) = self.parser.feed(bytes);
+            for event in events {
+                self.handle(event);
+            }
+            end
+        }
+
+        fn handle(&mut self, event: ControlEvent) {
+            match event {
+                ControlEvent::Output { pane, data } => self.feed_pane(pane, &data, false),
+                ControlEvent::WindowAdd { .. }
+                | ControlEvent::WindowClose { .. }
+                | ControlEvent::WindowRenamed { .. }
+                | ControlEvent::LayoutChange { .. } => {
+                    self.send(TmuxCommand::ListWindows, LIST_WINDOWS_COMMAND);
+                }
+                ControlEvent::Reply { ok, lines } => match self.channel.reply_received() {
+                    _ if !ok => {
+                        logging::log_line("WARN", &format!("tmux command failed: {}", lines.join(" ")));
+                    }
+                    TmuxCommand::ListWindows => {
+                        self.update_windows(lines.iter().filter_map(|line| parse_window_line(line)).collect());
+                    }
+                    TmuxCommand::Capture(pane) => {
+                        let mut screen = b"\x1b[H\x1b[2J".to_vec();
+                        screen.extend_from_slice(lines.join("\r\n").as_bytes());
+                        self.feed_pane(pane, &screen, true);
+                    }
+                    TmuxCommand::Cursor(pane) => {
+                        let position = lines.first().and_then(|line| {
+                            let (x, y) = line.split_once(' ')?;
+                            Some((x.parse::<u32>().ok()?, y.parse::<u32>().ok()?))
+                        });
+                        if let Some((x, y)) = position {
+                            self.feed_pane(pane, format!("\x1b[{};{}H", y + 1, x + 1).as_bytes(), true);
+                        }
+                    }
+                    TmuxCommand::Other => {}
+                },
+                ControlEvent::Exit { reason } => {
+                    logging::log_line("INFO", &format!("tmux exited: {}", reason.unwrap_or_default()));
+                }
+            }
+        }
+
+        /// Feed output into the pane showing tmux pane `pane`. Before the captured
+        /// screen has been drawn, only `captured` output is accepted.
+        fn feed_pane(&mut self, pane: u32, bytes: &[u8], captured: bool) {
+            let target = with_tmux_gateway(self.gateway, |gateway| {
+                let target = gateway.panes.get_mut(&pane)?;
+                if !target.ready && !captured {
+                    return None;
+                }
+                target.ready = true;
+                let ambiguous = *target.ambiguous_width.lock().expect("ambiguous_width mutex poisoned");
+                Some((
+                    Arc::clone(&target.term),
+                    Arc::clone(&target.notify),
+                    ambiguous,
+                    Arc::clone(&target.color_overrides),
+                ))
+            })
+            .flatten();
+            // The gateway lock is released first: the pane's listener writes replies
+            // back through the channel.
+            if let Some((term, notify, ambiguous, color_overrides)) = target {
+                let parser = self.pane_parsers.entry(pane).or_default();
+                parser.advance(
+                    &mut WidthPolicyHandler::new(&mut term.lock(), ambiguous).with_color_overrides(&color_overrides),
+                    bytes,
+                );
+                notify();
+            }
+        }
+
+        fn update_windows(&mut self, windows: Vec<TmuxWindow>) {
+            let shape = |windows: &[TmuxWindow]| -> Vec<(u32, String, Vec<u32>)> {
+                windows
+                    .iter()
+                    .map(|window| {
+                        (window.id, window.name.clone(), window.panes.iter().map(|pane| pane.pane).collect())
+                    })
+                    .collect()
+            };
+            let changed = with_tmux_gateway(self.gateway, |gateway| {
+                let changed = shape(&gateway.windows) != shape(&windows);
+                gateway.windows = windows.clone();
+                changed
+            })
+            .unwrap_or(false);
+            if changed {
+                logging::log_line("INFO", &format!("tmux windows: {:?}", shape(&windows)));
+                dispatch_tmux_event(TmuxEvent::Windows {
+                    gateway: self.gateway,
+                    root: self.root.clone(),
+                    windows,
+                });
+            }
+        }
+
+        /// Leave control mode: panes of this gateway stop, and the UI closes their tabs.
+        fn finish(self, reason: Option<String>) {
+            if let Ok(mut gateways) = TMUX_GATEWAYS.get_or_init(|| Mutex::new(HashMap::new())).lock() {
+                if let Some(gateway) = gateways.remove(&self.gateway) {
+                    for target in gateway.panes.values() {
+                        target.alive.store(false, Ordering::SeqCst);
+                    }
+                }
+            }
+            logging::log_line("INFO", &format!("tmux control mode ended (gateway {})", self.gateway));
+            dispatch_tmux_event(TmuxEvent::Exited {
+                gateway: self.gateway,
+                reason,
+            });
+        }
+    }
+
+    /// The tmux pane a session shows.
+    struct TmuxPaneLink {
+        gateway: usize,
+        pane: u32,
+        channel: TmuxChannel,
+    }
+
     /// Core PTY-backed terminal session for macOS.
     ///
     /// This owns the `alacritty_terminal::Term`, PTY handles, scrollback
@@ -126,14 +496,29 @@ mod platform {
     /// terminal state.
     pub struct TerminalSession {
         pub(crate) term: Arc<FairMutex<Term<TideEventListener>>>,
-        pty_master: Box<dyn MasterPty + Send>,
+        /// `None` for panes of a tmux server, which have no PTY of their own
+        pty_master: Option<Box<dyn MasterPty + Send>>,
         pty_writer: Arc<Mutex<Box<dyn Write + Send>>>,
+        /// Set while the pane runs `tmux -CC`: the id of its gateway
+        control_mode: Arc<Mutex<Option<usize>>>,
+        /// Set for panes of a tmux server
+        tmux: Option<TmuxPaneLink>,
         scrollback: usize,
         alive: Arc<AtomicBool>,
+        /// Exit code of the shell once it has exited
+        exit_code: Arc<Mutex<Option<u32>>>,
         bytes_read: Arc<AtomicU64>,
         bytes_written: AtomicU64,
         notify: Arc<dyn Fn() + Send + Sync>,
         io_thread: Option<JoinHandle<()>>,
+        /// Colors color queries are answered with
+        query_colors: QueryColors,
+        /// Width the parser gives East Asian ambiguous-width characters
+        ambiguous_width: Arc<Mutex<AmbiguousWidth>>,
+        /// Inline images; locked after `term` when both are needed
+        images: Arc<Mutex<ImageStore>>,
+        /// Lines commands were entered on; locked after `term` when both are needed
+        command_marks: Arc<Mutex<CommandMarks>>,
     }
 
     impl TerminalSession {
@@ -141,17 +526,26 @@ mod platform {
         ///
         /// This uses a fixed 80x24 cell grid for now; Floem-driven sizing
         /// will be hooked up in a later step.
+        ///
+        /// `on_title_change` receives OSC titles (empty when the program resets it);
+        /// `on_process_title` receives the foreground command whenever it changes
+        /// (`None` while the shell itself is in the foreground);
+        /// `on_notification` receives OSC 9 / OSC 777 notifications from programs.
         pub fn new(
             workspace_root: &Path,
+            profile: &ShellProfile,
+            options: SessionOptions<'_>,
             notify: Arc<dyn Fn() + Send + Sync>,
             on_title_change: Arc<dyn Fn(String) + Send + Sync>,
+            on_process_title: Arc<dyn Fn(Option<String>) + Send + Sync>,
+            on_notification: Arc<dyn Fn(TerminalNotification) + Send + Sync>,
         ) -> io::Result<Arc<Self>> {
             const DEFAULT_COLS: u16 = 80;
             const DEFAULT_ROWS: u16 = 24;
             const MIN_SCROLLBACK: usize = 500;
             const DEFAULT_SCROLLBACK: usize = 2000;
 
-            let scrollback = DEFAULT_SCROLLBACK.max(MIN_SCROLLBACK);
+            let scrollback = options.scrollback.unwrap_or(DEFAULT_SCROLLBACK).max(MIN_SCROLLBACK);
             let dims = TermDimensions::new(DEFAULT_COLS, DEFAULT_ROWS);
             let term_config = Config {
                 scrolling_history: scrollback,
@@ -161,11 +555,12 @@ mod platform {
             logging::log_line(
                 "INFO",
                 &format!(
-                    "Starting TerminalSession at {} ({}x{}, scrollback={})",
+                    "Starting TerminalSession at {} ({}x{}, scrollback={}, profile={})",
                     workspace_root.display(),
                     DEFAULT_COLS,
                     DEFAULT_ROWS,
                     scrollback,
+                    profile.name,
                 ),
             );
 
@@ -182,13 +577,10 @@ mod platform {
                 .openpty(pty_size)
                 .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
 
-            // Spawn default shell in the workspace root.
-            let mut cmd = CommandBuilder::new_default_prog();
-            cmd.cwd(workspace_root);
-            cmd.env("TERM", "xterm-256color");
-            cmd.env("COLORTERM", "truecolor");
+            // Spawn the profile's shell (by default the login shell in the workspace root).
+            let cmd = shell_command(profile, workspace_root);
 
-            let _child = pair
+            let mut child = pair
                 .slave
                 .spawn_command(cmd)
                 .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
@@ -202,13 +594,26 @@ mod platform {
 
             let alive = Arc::new(AtomicBool::new(true));
             let bytes_read = Arc::new(AtomicU64::new(0));
+            let query_colors = QueryColors::new(options.palette.unwrap_or([Rgb::default(); COUNT]));
+            let ambiguous_width = Arc::new(Mutex::new(AmbiguousWidth::default()));
+            let images = Arc::new(Mutex::new(ImageStore::new(ImageSettings::default())));
+            let command_marks = Arc::new(Mutex::new(CommandMarks::default()));
 
             // Create terminal state with configured scrollback and event listener.
-            let term = Term::new(
+            let mut term = Term::new(
                 term_config,
                 &dims,
-                TideEventListener::new(Arc::clone(&pty_writer), Arc::clone(&alive), on_title_change),
+                TideEventListener::new(
+                    Arc::clone(&pty_writer),
+                    Arc::clone(&alive),
+                    on_title_change,
+                    query_colors.clone(),
+                ),
             );
+            // Restored output goes in before the shell starts writing its prompt.
+            if !options.replay.is_empty() {
+                Processor::<StdSyncHandler>::new().advance(&mut term, options.replay);
+            }
             let term = Arc::new(FairMutex::new(term));
 
             // Clone a reader for the IO thread.
@@ -221,15 +626,81 @@ mod platform {
             let alive_for_thread = Arc::clone(&alive);
             let bytes_read_for_thread = Arc::clone(&bytes_read);
             let notify_for_thread = Arc::clone(&notify);
+            let writer_for_thread = Arc::clone(&pty_writer);
+            let color_overrides_for_thread = Arc::clone(&query_colors.overrides);
+            let ambiguous_width_for_thread = Arc::clone(&ambiguous_width);
+            let images_for_thread = Arc::clone(&images);
+            let command_marks_for_thread = Arc::clone(&command_marks);
+            let control_mode = Arc::new(Mutex::new(None));
+            let control_mode_for_thread = Arc::clone(&control_mode);
+            let root_for_thread = workspace_root.to_path_buf();
 
             let io_thread = thread::Builder::new()
                 .name("tide-terminal-io".to_string())
                 .spawn(move || {
                     logging::log_line("INFO", "Terminal IO thread started");
                     let mut parser = Processor::<StdSyncHandler>::new();
+                    let mut notification_scanner = NotificationScanner::new();
+                    let mut image_scanner = ImageScanner::new();
+                    let mut tmux_control: Option<TmuxControl> = None;
+                    let mut control_mode_scanner = ControlModeScanner::default();
                     let mut buf = [0u8; 4096];
                     let mut total_bytes: u64 = 0;
 
+                    let mut advance = |chunk: &[u8]| {
+                        let image_replies = {
+                            let ambiguous = *ambiguous_width_for_thread
+                                .lock()
+                                .expect("ambiguous_width mutex poisoned");
+                            let mut term = term_for_thread.lock();
+                            // Image sequences are placed at the cursor as it is between
+                            // the surrounding output
+                            let mut image_replies = Vec::new();
+                            let mut lines_scrolled = 0;
+                            for item in image_scanner.feed(chunk) {
+                                match item {
+                                    ScanItem::Output(output) => {
+                                        let mut handler = CommandMarkHandler::new(
+                                            WidthPolicyHandler::new(&mut term, ambiguous)
+                                                .with_color_overrides(&color_overrides_for_thread),
+                                        );
+                                        parser.advance(&mut handler, &output);
+                                        lines_scrolled += handler.lines_scrolled();
+                                    }
+                                    sequence => {
+                                        let mut images = images_for_thread
+                                            .lock()
+                                            .expect("images mutex poisoned");
+                                        image_replies.extend(handle_sequence(&mut term, &mut images, sequence));
+                                    }
+                                }
+                            }
+                            if lines_scrolled > 0 {
+                                command_marks_for_thread
+                                    .lock()
+                                    .expect("command_marks mutex poisoned")
+                                    .scroll(lines_scrolled);
+                            }
+                            image_replies
+                        };
+                        if !image_replies.is_empty() {
+                            let mut writer = writer_for_thread
+                                .lock()
+                                .expect("pty_writer mutex poisoned");
+                            for reply in image_replies {
+                                if let Err(err) = writer.write_all(reply.as_bytes()) {
+                                    logging::log_line(
+                                        "ERROR",
+                                        &format!("Failed to answer image command: {err}"),
+                                    );
+                                }
+                            }
+                        }
+                        for notification in notification_scanner.feed(chunk) {
+                            on_notification(notification);
+                        }
+                    };
+
                     while alive_for_thread.load(Ordering::SeqCst) {
                         match reader.read(&mut buf) {
                             Ok(0) => {
@@ -240,11 +711,42 @@ mod platform {
                                 break;
                             }
                             Ok(n) => {
-                                let chunk = &buf[..n];
                                 let parse_start = Instant::now();
-                                {
-                                    let mut term = term_for_thread.lock();
-                                    parser.advance(&mut *term, chunk);
+                                // Output alternates between plain terminal output and the
+                                // tmux control channel (between DCS 1000p and ST)
+                                let mut rest = buf[..n].to_vec();
+                                while !rest.is_empty() {
+                                    if let Some(control) = tmux_control.as_mut() {
+                                        match control.feed(&rest) {
+                                            Some(end) => {
+                                                if let Some(control) = tmux_control.take() {
+                                                    control.finish(None);
+                                                }
+                                                *control_mode_for_thread.lock().expect("control_mode mutex poisoned") = None;
+                                                advance(TMUX_ENDED_MESSAGE);
+                                                rest.drain(..end);
+                                            }
+                                            None => rest.clear(),
+                                        }
+                                        continue;
+                                    }
+
+                                    let (output, channel) = control_mode_scanner.feed(&rest);
+                                    advance(&output);
+                                    match channel {
+                                        Some(channel) => {
+                                            advance(TMUX_STARTED_MESSAGE);
+                                            let control = TmuxControl::start(
+                                                root_for_thread.clone(),
+                                                Arc::clone(&writer_for_thread),
+                                            );
+                                            *control_mode_for_thread.lock().expect("control_mode mutex poisoned") =
+                                                Some(control.gateway);
+                                            tmux_control = Some(control);
+                                            rest = channel;
+                                        }
+                                        None => rest.clear(),
+                                    }
                                 }
                                 notify_for_thread();
                                 logging::log_slow_op(
@@ -268,6 +770,9 @@ mod platform {
                         }
                     }
 
+                    if let Some(control) = tmux_control.take() {
+                        control.finish(Some("terminal closed".to_string()));
+                    }
                     alive_for_thread.store(false, Ordering::SeqCst);
                     logging::log_line(
                         "INFO",
@@ -277,28 +782,213 @@ mod platform {
                     );
                 })?;
 
+            // Poll the foreground process group for pane titles. The thread owns a
+            // duplicate of the master fd, so the descriptor it polls stays open (and
+            // cannot be reused for another file) however long it outlives the session;
+            // it stops within a poll interval once `alive` is cleared.
+            let shell_pid = child.process_id().map(|pid| pid as libc::pid_t);
+            let poll_fd = pair.master.as_raw_fd().and_then(|fd| {
+                // SAFETY: `fd` belongs to `pair.master`, which keeps it open for the
+                // whole borrow; it is only used to duplicate it.
+                unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned().ok()
+            });
+            if let Some(master_fd) = poll_fd {
+                let alive_for_poll = Arc::clone(&alive);
+                thread::Builder::new()
+                    .name("tide-terminal-title".to_string())
+                    .spawn(move || {
+                        let mut last_pgrp = None;
+                        loop {
+                            thread::sleep(FOREGROUND_POLL_INTERVAL);
+                            if !alive_for_poll.load(Ordering::SeqCst) {
+                                break;
+                            }
+
+                            // SAFETY: `master_fd` is an open descriptor owned by this
+                            // thread; `tcgetpgrp` only reads it.
+                            let pgrp = unsafe { libc::tcgetpgrp(master_fd.as_raw_fd()) };
+                            if pgrp <= 0 || last_pgrp == Some(pgrp) {
+                                continue;
+                            }
+                            last_pgrp = Some(pgrp);
+
+                            let title = if Some(pgrp) == shell_pid {
+                                None
+                            } else {
+                                process_args(pgrp).and_then(|args| title_from_args(&args))
+                            };
+                            logging::log_line(
+                                "DEBUG",
+                                &format!("Terminal foreground process {pgrp}: {title:?}"),
+                            );
+                            on_process_title(title);
+                        }
+                    })?;
+            }
+
+            // Reap the shell and keep its exit code; `notify` lets the UI pick it up.
+            let exit_code = Arc::new(Mutex::new(None));
+            let exit_code_for_wait = Arc::clone(&exit_code);
+            let notify_for_wait = Arc::clone(&notify);
+            thread::Builder::new()
+                .name("tide-terminal-wait".to_string())
+                .spawn(move || match child.wait() {
+                    Ok(status) => {
+                        logging::log_line("INFO", &format!("Terminal shell exited with {}", status.exit_code()));
+                        *exit_code_for_wait.lock().expect("exit_code mutex poisoned") = Some(status.exit_code());
+                        notify_for_wait();
+                    }
+                    Err(err) => {
+                        logging::log_line("ERROR", &format!("Failed to wait for terminal shell: {err}"));
+                    }
+                })?;
+
             let session = TerminalSession {
                 term,
-                pty_master: pair.master,
+                pty_master: Some(pair.master),
                 pty_writer,
+                control_mode,
+                tmux: None,
                 scrollback,
                 alive,
+                exit_code,
                 bytes_read,
                 bytes_written: AtomicU64::new(0),
                 notify,
                 io_thread: Some(io_thread),
+                query_colors,
+                ambiguous_width,
+                images,
+                command_marks,
             };
 
             Ok(Arc::new(session))
         }
 
+        /// Create a session showing pane `pane` of the tmux server behind gateway
+        /// `gateway` (see `services::tmux`). Input and resizes become tmux commands and
+        /// output arrives through the gateway's IO thread; the current screen is
+        /// captured first.
+        pub fn tmux_pane(
+            gateway: usize,
+            pane: u32,
+            notify: Arc<dyn Fn() + Send + Sync>,
+            on_title_change: Arc<dyn Fn(String) + Send + Sync>,
+        ) -> io::Result<Arc<Self>> {
+            const DEFAULT_SCROLLBACK: usize = 2000;
+
+            let (channel, size) = with_tmux_gateway(gateway, |gateway| {
+                let size = gateway
+                    .windows
+                    .iter()
+                    .flat_map(|window| &window.panes)
+                    .find(|layout| layout.pane == pane)
+                    .map(|layout| (layout.cols, layout.rows));
+                (gateway.channel.clone(), size)
+            })
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "tmux is no longer attached"))?;
+            let (cols, rows) = size.unwrap_or((80, 24));
+            logging::log_line(
+                "INFO",
+                &format!("Starting tmux pane session (gateway {gateway}, pane %{pane}, {cols}x{rows})"),
+            );
+
+            let pty_writer: Arc<Mutex<Box<dyn Write + Send>>> = Arc::new(Mutex::new(Box::new(
+                TmuxPaneWriter {
+                    channel: channel.clone(),
+                    pane,
+                },
+            )));
+            let alive = Arc::new(AtomicBool::new(true));
+            let ambiguous_width = Arc::new(Mutex::new(AmbiguousWidth::default()));
+            let query_colors = QueryColors::new([Rgb::default(); COUNT]);
+            let term = Term::new(
+                Config {
+                    scrolling_history: DEFAULT_SCROLLBACK,
+                    ..Config::default()
+                },
+                &TermDimensions::new(cols, rows),
+                TideEventListener::new(
+                    Arc::clone(&pty_writer),
+                    Arc::clone(&alive),
+                    on_title_change,
+                    query_colors.clone(),
+                ),
+            );
+            let term = Arc::new(FairMutex::new(term));
+
+            with_tmux_gateway(gateway, |gateway| {
+                gateway.panes.insert(
+                    pane,
+                    TmuxPaneTarget {
+                        term: Arc::clone(&term),
+                        notify: Arc::clone(&notify),
+                        alive: Arc::clone(&alive),
+                        ambiguous_width: Arc::clone(&ambiguous_width),
+                        color_overrides: Arc::clone(&query_colors.overrides),
+                        ready: false,
+                        size: None,
+                    },
+                );
+            });
+            channel.send(TmuxCommand::Capture(pane), &capture_pane_command(pane))?;
+            channel.send(TmuxCommand::Cursor(pane), &cursor_position_command(pane))?;
+
+            Ok(Arc::new(TerminalSession {
+                term,
+                pty_master: None,
+                pty_writer,
+                control_mode: Arc::new(Mutex::new(None)),
+                tmux: Some(TmuxPaneLink { gateway, pane, channel }),
+                scrollback: DEFAULT_SCROLLBACK,
+                alive,
+                exit_code: Arc::new(Mutex::new(None)),
+                bytes_read: Arc::new(AtomicU64::new(0)),
+                bytes_written: AtomicU64::new(0),
+                notify,
+                io_thread: None,
+                query_colors,
+                ambiguous_width,
+                // tmux only forwards images with `allow-passthrough`; none are decoded
+                images: Arc::new(Mutex::new(ImageStore::new(ImageSettings {
+                    enabled: false,
+                    ..ImageSettings::default()
+                }))),
+                command_marks: Arc::new(Mutex::new(CommandMarks::default())),
+            }))
+        }
+
         /// Check if the terminal session is currently active (PTY running).
         pub fn is_active(&self) -> bool {
             self.alive.load(Ordering::SeqCst)
         }
 
+        /// Exit code of the shell (`None` while it runs, and for tmux panes).
+        pub fn exit_code(&self) -> Option<u32> {
+            *self.exit_code.lock().expect("exit_code mutex poisoned")
+        }
+
         /// Write raw bytes to the PTY.
         pub fn write(&self, bytes: &[u8]) -> io::Result<()> {
+            // In tmux control mode the pane only shows a notice; Esc detaches.
+            let control_mode = *self.control_mode.lock().expect("control_mode mutex poisoned");
+            if let Some(gateway) = control_mode {
+                if bytes == b"\x1b" {
+                    logging::log_line("INFO", &format!("Detaching tmux (gateway {gateway})"));
+                    if let Some(channel) = with_tmux_gateway(gateway, |gateway| gateway.channel.clone()) {
+                        channel.send(TmuxCommand::Other, "detach-client\n")?;
+                    }
+                }
+                return Ok(());
+            }
+
+            // Enter starts a command; `pane.last_output` finds its output from this mark
+            if self.tmux.is_none() && bytes.contains(&b'\r') {
+                let term = self.term.lock();
+                let mut marks = self.command_marks.lock().expect("command_marks mutex poisoned");
+                mark_command_line(&term, &mut marks);
+            }
+
             let mut writer = self
                 .pty_writer
                 .lock()
@@ -318,7 +1008,7 @@ mod platform {
             result
         }
 
-        /// Resize both the PTY and the terminal grid.
+        /// Resize both the PTY (or tmux pane) and the terminal grid.
         pub fn resize(&self, cols: u16, rows: u16) -> io::Result<()> {
             let dims = TermDimensions::new(cols, rows);
 
@@ -326,14 +1016,19 @@ mod platform {
             let resize_start = Instant::now();
 
             let pty_start = Instant::now();
-            self.pty_master
-                .resize(PtySize {
-                    rows,
-                    cols,
-                    pixel_width: 0,
-                    pixel_height: 0,
-                })
-                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+            if let Some(master) = &self.pty_master {
+                master
+                    .resize(PtySize {
+                        rows,
+                        cols,
+                        pixel_width: 0,
+                        pixel_height: 0,
+                    })
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+            }
+            if let Some(link) = &self.tmux {
+                self.resize_tmux_pane(link, cols, rows)?;
+            }
             let pty_elapsed = pty_start.elapsed();
             logging::log_slow_op("pty resize", pty_elapsed, &format!("grid={cols}x{rows}"));
 
@@ -366,12 +1061,106 @@ mod platform {
             Ok(())
         }
 
+        /// Size a tmux pane: its window becomes as wide as the Tide panes of the window
+        /// side by side (plus tmux's one-column borders).
+        fn resize_tmux_pane(&self, link: &TmuxPaneLink, cols: u16, rows: u16) -> io::Result<()> {
+            let commands = with_tmux_gateway(link.gateway, |gateway| {
+                if let Some(target) = gateway.panes.get_mut(&link.pane) {
+                    target.size = Some((cols, rows));
+                }
+                let window = gateway
+                    .windows
+                    .iter()
+                    .find(|window| window.panes.iter().any(|layout| layout.pane == link.pane))?;
+                let window_cols = window
+                    .panes
+                    .iter()
+                    .map(|layout| {
+                        gateway
+                            .panes
+                            .get(&layout.pane)
+                            .and_then(|target| target.size)
+                            .map_or(layout.cols, |(cols, _)| cols)
+                    })
+                    .sum::<u16>()
+                    + window.panes.len().saturating_sub(1) as u16;
+                Some(resize_commands(window.id, link.pane, cols, rows, window_cols))
+            })
+            .flatten();
+            match commands {
+                Some(commands) => link.channel.send(TmuxCommand::Other, &commands),
+                None => Ok(()),
+            }
+        }
+
+        /// Set the palette used to answer color queries (OSC 4/10/11/12 `?`).
+        pub fn set_color_palette(&self, colors: [Rgb; COUNT]) {
+            if let Ok(mut palette) = self.query_colors.palette.lock() {
+                *palette = colors;
+            }
+        }
+
+        /// Set the width given to East Asian ambiguous-width characters from now on.
+        pub fn set_ambiguous_width(&self, ambiguous: AmbiguousWidth) {
+            if let Ok(mut current) = self.ambiguous_width.lock() {
+                *current = ambiguous;
+            }
+        }
+
+        /// Apply the inline image settings; disabling them drops every image.
+        pub fn set_image_settings(&self, settings: ImageSettings) {
+            if self.tmux.is_some() {
+                return;
+            }
+            if let Ok(mut images) = self.images.lock() {
+                images.configure(settings);
+            }
+        }
+
+        /// Set the cell size in pixels, used to size images given in pixels.
+        pub fn set_cell_size(&self, width: f64, height: f64) {
+            if let Ok(mut images) = self.images.lock() {
+                images.set_cell_size(width, height);
+            }
+        }
+
+        /// Inline images with at least one row in the viewport.
+        pub fn visible_images(&self) -> Vec<VisiblePlacement> {
+            let term = self.term.lock();
+            match self.images.lock() {
+                Ok(mut images) => visible_placements(&term, &mut images),
+                Err(_) => Vec::new(),
+            }
+        }
+
+        /// Process group in the foreground of the PTY (`None` for tmux panes).
+        pub fn foreground_pid(&self) -> Option<i32> {
+            self.pty_master.as_ref()?.process_group_leader()
+        }
+
+        /// Set the cursor style used until the program requests one (and after DECSCUSR 0).
+        pub fn set_default_cursor_style(&self, style: CursorStyle) {
+            let mut term = self.term.lock();
+            term.set_options(Config {
+                scrolling_history: self.scrollback,
+                default_cursor_style: style,
+                ..Config::default()
+            });
+        }
+
         /// Scroll the visible terminal content by the given delta.
         pub fn scroll_display(&self, delta: i32) {
             let mut term = self.term.lock();
             term.scroll_display(Scroll::Delta(delta));
         }
 
+        /// Output of the last command entered in the pane (see `pane.last_output`).
+        pub fn last_command_output(&self) -> Option<String> {
+            let term = self.term.lock();
+            let marks = self.command_marks.lock().expect("command_marks mutex poisoned");
+            last_command_output(&term, &marks)
+        }
+
         /// Helper giving read-only access to the underlying `Term`.
         ///
         /// The provided closure is executed while the terminal lock is held.
@@ -408,6 +1197,18 @@ mod platform {
                 ),
             );
 
+            if let Some(link) = &self.tmux {
+                with_tmux_gateway(link.gateway, |gateway| {
+                    let ours = gateway
+                        .panes
+                        .get(&link.pane)
+                        .is_some_and(|target| Arc::ptr_eq(&target.term, &self.term));
+                    if ours {
+                        gateway.panes.remove(&link.pane);
+                    }
+                });
+            }
+
             if let Some(handle) = self.io_thread.take() {
                 logging::breadcrumb("TerminalSession joining IO thread (background)");
                 let joiner = thread::Builder::new()
@@ -449,8 +1250,12 @@ mod platform {
         /// Create a new stub terminal session.
         pub fn new(
             workspace_root: &Path,
+            _profile: &crate::services::ShellProfile,
+            _options: super::SessionOptions<'_>,
             _notify: Arc<dyn Fn() + Send + Sync>,
             _on_title_change: Arc<dyn Fn(String) + Send + Sync>,
+            _on_process_title: Arc<dyn Fn(Option<String>) + Send + Sync>,
+            _on_notification: Arc<dyn Fn(crate::services::TerminalNotification) + Send + Sync>,
         ) -> io::Result<Arc<Self>> {
             logging::log_line(
                 "WARN",
@@ -479,6 +1284,11 @@ mod platform {
         pub fn is_active(&self) -> bool {
             true
         }
+
+        /// Stub exit code; the stub never exits.
+        pub fn exit_code(&self) -> Option<u32> {
+            None
+        }
     }
 }
 
@@ -486,15 +1296,24 @@ pub use platform::TerminalSession;
 
 #[cfg(test)]
 mod tests {
-    use super::TerminalSession;
+    use super::{SessionOptions, TerminalSession};
+    use crate::services::ShellProfile;
     use std::env;
     use std::sync::Arc;
 
     #[test]
     fn terminal_session_new_succeeds() {
         let root = env::current_dir().unwrap();
-        let session = TerminalSession::new(&root, Arc::new(|| {}), Arc::new(|_| {}))
-            .expect("terminal session should construct");
+        let session = TerminalSession::new(
+            &root,
+            &ShellProfile::default(),
+            SessionOptions::default(),
+            Arc::new(|| {}),
+            Arc::new(|_| {}),
+            Arc::new(|_| {}),
+            Arc::new(|_| {}),
+        )
+        .expect("terminal session should construct");
         let _ = session;
     }
 
@@ -502,8 +1321,167 @@ mod tests {
     #[test]
     fn terminal_session_scrollback_at_least_500() {
         let root = env::current_dir().unwrap();
-        let session = TerminalSession::new(&root, Arc::new(|| {}), Arc::new(|_| {}))
-            .expect("terminal session should construct");
+        let session = TerminalSession::new(
+            &root,
+            &ShellProfile::default(),
+            SessionOptions::default(),
+            Arc::new(|| {}),
+            Arc::new(|_| {}),
+            Arc::new(|_| {}),
+            Arc::new(|_| {}),
+        )
+        .expect("terminal session should construct");
         assert!(session.scrollback() >= 500);
     }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn background_run_finishes_with_its_exit_code() {
+        use crate::services::background::{TaskStatus, BACKGROUND_SCROLLBACK};
+        use crate::services::launcher::{Launcher, LauncherRunIn};
+        use std::time::{Duration, Instant};
+
+        let root = env::current_dir().unwrap();
+        let launcher = Launcher::new("Check", "echo checking; exit 3", LauncherRunIn::Background);
+        let options = SessionOptions { scrollback: Some(BACKGROUND_SCROLLBACK), ..SessionOptions::default() };
+        let session = TerminalSession::new(
+            &root,
+            &launcher.background_profile(&launcher.command, &root),
+            options,
+            Arc::new(|| {}),
+            Arc::new(|_| {}),
+            Arc::new(|_| {}),
+            Arc::new(|_| {}),
+        )
+        .expect("terminal session should construct");
+        assert_eq!(session.scrollback(), BACKGROUND_SCROLLBACK);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while session.exit_code().is_none() {
+            assert!(Instant::now() < deadline, "the run did not finish");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(TaskStatus::from_exit_code(session.exit_code()), TaskStatus::Failed(3));
+    }
+
+    #[cfg(target_os = "macos")]
+    mod color_queries {
+        use super::super::platform::{QueryColors, TideEventListener};
+        use crate::services::text_width::{AmbiguousWidth, WidthPolicyHandler};
+        use alacritty_terminal::term::color::COUNT;
+        use alacritty_terminal::term::test::TermSize;
+        use alacritty_terminal::term::{Config, Term};
+        use alacritty_terminal::vte::ansi::{NamedColor, Processor, Rgb, StdSyncHandler};
+        use std::io::{self, Write};
+        use std::sync::atomic::AtomicBool;
+        use std::sync::{Arc, Mutex};
+
+        /// What the terminal writes back to the program.
+        #[derive(Clone, Default)]
+        struct Replies(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Replies {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        struct Harness {
+            term: Term<TideEventListener>,
+            parser: Processor<StdSyncHandler>,
+            colors: QueryColors,
+            replies: Replies,
+        }
+
+        impl Harness {
+            fn new() -> Self {
+                let mut palette = [Rgb::default(); COUNT];
+                palette[NamedColor::Red as usize] = Rgb { r: 0xac, g: 0x42, b: 0x42 };
+                palette[NamedColor::Foreground as usize] = Rgb { r: 0xe6, g: 0xe8, b: 0xf0 };
+                palette[NamedColor::Background as usize] = Rgb { r: 0x17, g: 0x18, b: 0x1e };
+                let colors = QueryColors::new(palette);
+                let replies = Replies::default();
+                let term = Term::new(
+                    Config::default(),
+                    &TermSize::new(80, 24),
+                    TideEventListener::new(
+                        Arc::new(Mutex::new(Box::new(replies.clone()))),
+                        Arc::new(AtomicBool::new(true)),
+                        Arc::new(|_| {}),
+                        colors.clone(),
+                    ),
+                );
+                Self { term, parser: Processor::new(), colors, replies }
+            }
+
+            /// Feed program output and return what the terminal writes back.
+            fn feed(&mut self, bytes: &str) -> String {
+                let mut handler = WidthPolicyHandler::new(&mut self.term, AmbiguousWidth::Narrow)
+                    .with_color_overrides(&self.colors.overrides);
+                self.parser.advance(&mut handler, bytes.as_bytes());
+                let replies = std::mem::take(&mut *self.replies.0.lock().unwrap());
+                String::from_utf8(replies).unwrap()
+            }
+        }
+
+        #[test]
+        fn background_query_answers_palette_color() {
+            let mut harness = Harness::new();
+            assert_eq!(harness.feed("\x1b]11;?\x07"), "\x1b]11;rgb:1717/1818/1e1e\x07");
+        }
+
+        #[test]
+        fn foreground_query_keeps_st_terminator() {
+            let mut harness = Harness::new();
+            assert_eq!(harness.feed("\x1b]10;?\x1b\\"), "\x1b]10;rgb:e6e6/e8e8/f0f0\x1b\\");
+        }
+
+        #[test]
+        fn palette_set_is_reported_and_reset_restores_palette() {
+            let mut harness = Harness::new();
+            assert_eq!(
+                harness.feed("\x1b]4;1;rgb:12/34/56\x07\x1b]4;1;?\x07"),
+                "\x1b]4;1;rgb:1212/3434/5656\x07"
+            );
+            assert_eq!(
+                harness.term.colors()[NamedColor::Red],
+                Some(Rgb { r: 0x12, g: 0x34, b: 0x56 })
+            );
+
+            assert_eq!(
+                harness.feed("\x1b]104;1\x07\x1b]4;1;?\x07"),
+                "\x1b]4;1;rgb:acac/4242/4242\x07"
+            );
+            assert_eq!(harness.term.colors()[NamedColor::Red], None);
+        }
+
+        #[test]
+        fn background_set_is_reported_until_reset() {
+            let mut harness = Harness::new();
+            assert_eq!(
+                harness.feed("\x1b]11;#ffffff\x07\x1b]11;?\x07"),
+                "\x1b]11;rgb:ffff/ffff/ffff\x07"
+            );
+            assert_eq!(
+                harness.feed("\x1b]111\x07\x1b]11;?\x07"),
+                "\x1b]11;rgb:1717/1818/1e1e\x07"
+            );
+        }
+
+        #[test]
+        fn queries_are_answered_in_order_with_other_replies() {
+            // Programs send a device attributes query after the color query and take its
+            // reply as the end of the answers
+            let mut harness = Harness::new();
+            assert_eq!(
+                harness.feed("\x1b]11;?\x07\x1b]11;#ffffff\x07\x1b]11;?\x07\x1b[c"),
+                "\x1b]11;rgb:1717/1818/1e1e\x07\x1b]11;rgb:ffff/ffff/ffff\x07\x1b[?6c"
+            );
+        }
+    }
 }
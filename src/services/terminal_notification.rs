@@ -0,0 +1,222 @@
+//! Desktop-style notifications sent by terminal programs.
+//!
+//! Programs announce events with `OSC 9 ; <message>` (iTerm2) or
+//! `OSC 777 ; notify ; <title> ; <body>` (rxvt/foot). The terminal parser ignores both,
+//! so the IO thread scans the PTY output for them before parsing.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Longest OSC payload kept while scanning; longer sequences are dropped.
+const MAX_OSC_LEN: usize = 4096;
+
+/// A notification requested by a program running in a pane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalNotification {
+    pub title: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    Escape,
+    Osc,
+    /// `ESC` seen inside an OSC (start of the `ESC \` terminator)
+    OscEscape,
+}
+
+/// Incremental scanner for notification sequences; keeps state across PTY reads.
+#[derive(Debug)]
+pub struct NotificationScanner {
+    state: ScanState,
+    payload: Vec<u8>,
+    overflow: bool,
+}
+
+impl Default for NotificationScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotificationScanner {
+    pub fn new() -> Self {
+        Self {
+            state: ScanState::Ground,
+            payload: Vec::new(),
+            overflow: false,
+        }
+    }
+
+    /// Scan a chunk of PTY output and return the notifications completed in it.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<TerminalNotification> {
+        let mut found = Vec::new();
+        for &byte in bytes {
+            match (self.state, byte) {
+                (ScanState::Ground, 0x1b) => self.state = ScanState::Escape,
+                (ScanState::Ground, _) => {}
+                (ScanState::Escape, b']') => {
+                    self.state = ScanState::Osc;
+                    self.payload.clear();
+                    self.overflow = false;
+                }
+                (ScanState::Escape, 0x1b) => {}
+                (ScanState::Escape, _) => self.state = ScanState::Ground,
+                (ScanState::Osc, 0x07) => {
+                    self.finish(&mut found);
+                    self.state = ScanState::Ground;
+                }
+                (ScanState::Osc, 0x1b) => self.state = ScanState::OscEscape,
+                (ScanState::Osc, 0x18 | 0x1a) => self.state = ScanState::Ground,
+                (ScanState::Osc, _) => {
+                    if self.payload.len() < MAX_OSC_LEN {
+                        self.payload.push(byte);
+                    } else {
+                        self.overflow = true;
+                    }
+                }
+                (ScanState::OscEscape, b'\\') => {
+                    self.finish(&mut found);
+                    self.state = ScanState::Ground;
+                }
+                // Any other escape aborts the OSC and may start a new sequence
+                (ScanState::OscEscape, b']') => {
+                    self.state = ScanState::Osc;
+                    self.payload.clear();
+                    self.overflow = false;
+                }
+                (ScanState::OscEscape, 0x1b) => self.state = ScanState::Escape,
+                (ScanState::OscEscape, _) => self.state = ScanState::Ground,
+            }
+        }
+        found
+    }
+
+    fn finish(&mut self, found: &mut Vec<TerminalNotification>) {
+        if !self.overflow {
+            if let Some(notification) = parse_notification(&self.payload) {
+                found.push(notification);
+            }
+        }
+        self.payload.clear();
+    }
+}
+
+/// Notification carried by an OSC payload (the bytes between `ESC ]` and the terminator).
+fn parse_notification(payload: &[u8]) -> Option<TerminalNotification> {
+    let payload = String::from_utf8_lossy(payload);
+    if let Some(message) = payload.strip_prefix("9;") {
+        // ConEmu reuses OSC 9 for numbered commands (`9;4;1;50` is progress)
+        let first = message.split(';').next().unwrap_or_default();
+        if !first.is_empty() && first.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let body = clean(message);
+        return (!body.is_empty()).then_some(TerminalNotification { title: None, body });
+    }
+
+    let rest = payload.strip_prefix("777;notify;")?;
+    let (title, body) = rest.split_once(';').unwrap_or((rest, ""));
+    let title = clean(title);
+    let body = clean(body);
+    if title.is_empty() && body.is_empty() {
+        return None;
+    }
+    Some(TerminalNotification {
+        title: (!title.is_empty()).then_some(title),
+        body,
+    })
+}
+
+fn clean(text: &str) -> String {
+    text.chars().filter(|ch| !ch.is_control()).collect::<String>().trim().to_string()
+}
+
+/// Sliding-window limit on how many notifications a source may post.
+#[derive(Debug, Clone)]
+pub struct NotificationRateLimiter {
+    max: usize,
+    window: Duration,
+    recent: VecDeque<Instant>,
+}
+
+impl NotificationRateLimiter {
+    pub fn new(max: usize, window: Duration) -> Self {
+        Self {
+            max,
+            window,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Record an attempt at `now`; false when the source is over its limit.
+    pub fn allow(&mut self, now: Instant) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|sent| now.duration_since(*sent) >= self.window)
+        {
+            self.recent.pop_front();
+        }
+        if self.recent.len() >= self.max {
+            return false;
+        }
+        self.recent.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(title: Option<&str>, body: &str) -> TerminalNotification {
+        TerminalNotification {
+            title: title.map(String::from),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn scanner_finds_osc_9_and_777() {
+        let mut scanner = NotificationScanner::new();
+        let found = scanner.feed(b"out\x1b]9;Build finished\x07more\x1b]777;notify;cargo;tests passed; 0 failed\x1b\\");
+        assert_eq!(
+            found,
+            vec![
+                notification(None, "Build finished"),
+                notification(Some("cargo"), "tests passed; 0 failed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scanner_handles_sequences_split_across_reads() {
+        let mut scanner = NotificationScanner::new();
+        assert!(scanner.feed(b"\x1b]9;Do").is_empty());
+        assert!(scanner.feed(b"ne\x1b").is_empty());
+        assert_eq!(scanner.feed(b"\\"), vec![notification(None, "Done")]);
+    }
+
+    #[test]
+    fn scanner_ignores_titles_and_progress() {
+        let mut scanner = NotificationScanner::new();
+        assert!(scanner.feed(b"\x1b]0;title\x07\x1b]9;4;1;50\x07\x1b]777;preexec\x07").is_empty());
+        // An aborted OSC does not swallow the next one
+        assert_eq!(
+            scanner.feed(b"\x1b]9;lost\x1b[0m\x1b]9;kept\x07"),
+            vec![notification(None, "kept")]
+        );
+    }
+
+    #[test]
+    fn rate_limiter_allows_a_burst_per_window() {
+        let start = Instant::now();
+        let mut limiter = NotificationRateLimiter::new(2, Duration::from_secs(10));
+        assert!(limiter.allow(start));
+        assert!(limiter.allow(start + Duration::from_secs(1)));
+        assert!(!limiter.allow(start + Duration::from_secs(2)));
+        assert!(limiter.allow(start + Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,341 @@
+//! Character widths shared by the terminal parser and renderer.
+//!
+//! Programs lay out text with wcwidth-style widths, one character at a time. East Asian
+//! ambiguous-width characters (`①`, `°`, `→`, box drawing, …) take one column in most
+//! locales and two in CJK ones, so their width is a setting: the parser places them on
+//! the grid with it and the renderer sizes text with the same rule. Grapheme clusters
+//! that still span several cells (ZWJ emoji sequences, flags) are drawn as one run.
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+/// Width of East Asian ambiguous-width characters, stored in `settings.json` as
+/// `ambiguous_width`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AmbiguousWidth {
+    /// One column (most locales)
+    #[default]
+    Narrow,
+    /// Two columns (CJK locales)
+    Wide,
+}
+
+const ZERO_WIDTH_JOINER: char = '\u{200d}';
+
+/// Whether `c` is one column wide by default and two in CJK contexts.
+pub fn is_ambiguous(c: char) -> bool {
+    c.width() == Some(1) && c.width_cjk() == Some(2)
+}
+
+/// Columns `c` takes on the grid (`None` for control characters).
+pub fn char_width(c: char, ambiguous: AmbiguousWidth) -> Option<usize> {
+    match ambiguous {
+        AmbiguousWidth::Narrow => c.width(),
+        AmbiguousWidth::Wide => c.width_cjk(),
+    }
+}
+
+/// Columns `text` takes on the grid, summed per character like the shell does.
+pub fn str_width(text: &str, ambiguous: AmbiguousWidth) -> usize {
+    text.chars().filter_map(|c| char_width(c, ambiguous)).sum()
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+/// Whether the next cell, starting with `next`, continues the grapheme cluster `run`
+/// (the text of the cells before it): after a zero-width joiner, or the second half
+/// of a flag.
+pub fn joins_next_cell(run: &str, next: char) -> bool {
+    if run.ends_with(ZERO_WIDTH_JOINER) {
+        return true;
+    }
+    is_regional_indicator(next)
+        && run.chars().all(is_regional_indicator)
+        && run.chars().count() % 2 == 1
+}
+
+/// Handler placing characters on the grid with the ambiguous-width setting; everything
+/// else goes straight to the `Term`. Use it in place of the term with a `Processor`.
+#[cfg(target_os = "macos")]
+pub use self::platform::WidthPolicyHandler;
+
+/// Implement `Handler` methods by passing them on to the handler (or `Term`) in `field`.
+#[cfg(target_os = "macos")]
+macro_rules! delegate_handler {
+    ($field:ident: $($name:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $name(&mut self, $($arg: $ty),*) {
+                self.$field.$name($($arg),*)
+            }
+        )*
+    };
+}
+#[cfg(target_os = "macos")]
+pub(crate) use delegate_handler;
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{is_ambiguous, AmbiguousWidth};
+    use alacritty_terminal::event::EventListener;
+    use alacritty_terminal::grid::Dimensions;
+    use alacritty_terminal::index::{Column, Point};
+    use alacritty_terminal::term::cell::Flags;
+    use alacritty_terminal::term::color::COUNT;
+    use alacritty_terminal::term::Term;
+    use alacritty_terminal::vte::ansi::{
+        Attr, CharsetIndex, ClearMode, CursorShape, CursorStyle, Handler, Hyperlink, KeyboardModes,
+        KeyboardModesApplyBehavior, LineClearMode, Mode, ModifyOtherKeys, PrivateMode, Rgb,
+        ScpCharPath, ScpUpdateMode, StandardCharset, TabulationClearMode,
+    };
+    use std::sync::Mutex;
+
+    pub struct WidthPolicyHandler<'a, T: EventListener> {
+        term: &'a mut Term<T>,
+        ambiguous: AmbiguousWidth,
+        color_overrides: Option<&'a Mutex<[Option<Rgb>; COUNT]>>,
+    }
+
+    impl<'a, T: EventListener> WidthPolicyHandler<'a, T> {
+        pub fn new(term: &'a mut Term<T>, ambiguous: AmbiguousWidth) -> Self {
+            Self { term, ambiguous, color_overrides: None }
+        }
+
+        /// Copy the color the program has set for a queried color (OSC 4/10/11/12 `?`)
+        /// to `overrides` before the query reaches the event listener, which answers it
+        /// but cannot lock the `Term`.
+        pub fn with_color_overrides(mut self, overrides: &'a Mutex<[Option<Rgb>; COUNT]>) -> Self {
+            self.color_overrides = Some(overrides);
+            self
+        }
+
+        /// The terminal the output goes to, for handlers wrapping this one.
+        pub fn term(&self) -> &Term<T> {
+            self.term
+        }
+
+        /// Write `c` as a two-column character: the character, then a spacer cell.
+        fn input_wide(&mut self, c: char) {
+            let columns = self.term.columns();
+            let cursor = &self.term.grid().cursor;
+            if columns < 2 {
+                self.term.input(c);
+                return;
+            }
+            if !cursor.input_needs_wrap && cursor.point.column.0 + 1 >= columns {
+                // No room on this line: wrap first, like any wide character
+                self.term.input(' ');
+                self.flag_last_input(Flags::LEADING_WIDE_CHAR_SPACER);
+            }
+            self.term.input(c);
+            self.flag_last_input(Flags::WIDE_CHAR);
+            self.term.input(' ');
+            self.flag_last_input(Flags::WIDE_CHAR_SPACER);
+        }
+
+        /// Add `flags` to the cell written by the last `input`.
+        fn flag_last_input(&mut self, flags: Flags) {
+            let cursor = &self.term.grid().cursor;
+            let point = if cursor.input_needs_wrap {
+                cursor.point
+            } else {
+                Point::new(cursor.point.line, Column(cursor.point.column.0.saturating_sub(1)))
+            };
+            self.term.grid_mut()[point].flags.insert(flags);
+        }
+    }
+
+    impl<T: EventListener> Handler for WidthPolicyHandler<'_, T> {
+        fn input(&mut self, c: char) {
+            if self.ambiguous == AmbiguousWidth::Wide && is_ambiguous(c) {
+                self.input_wide(c);
+            } else {
+                self.term.input(c);
+            }
+        }
+
+        fn dynamic_color_sequence(&mut self, prefix: String, index: usize, terminator: &str) {
+            if let Some(mut overrides) = self.color_overrides.and_then(|overrides| overrides.lock().ok()) {
+                if index < COUNT {
+                    overrides[index] = self.term.colors()[index];
+                }
+            }
+            self.term.dynamic_color_sequence(prefix, index, terminator)
+        }
+
+        fn clipboard_store(&mut self, clipboard: u8, data: &[u8]) {
+            self.term.clipboard_store(clipboard, data)
+        }
+
+        fn clipboard_load(&mut self, clipboard: u8, terminator: &str) {
+            self.term.clipboard_load(clipboard, terminator)
+        }
+
+        delegate_handler! { term:
+            set_title(title: Option<String>);
+            set_cursor_style(style: Option<CursorStyle>);
+            set_cursor_shape(shape: CursorShape);
+            goto(line: i32, col: usize);
+            goto_line(line: i32);
+            goto_col(col: usize);
+            insert_blank(count: usize);
+            move_up(count: usize);
+            move_down(count: usize);
+            identify_terminal(intermediate: Option<char>);
+            device_status(arg: usize);
+            move_forward(col: usize);
+            move_backward(col: usize);
+            move_down_and_cr(row: usize);
+            move_up_and_cr(row: usize);
+            put_tab(count: u16);
+            backspace();
+            carriage_return();
+            linefeed();
+            bell();
+            substitute();
+            newline();
+            set_horizontal_tabstop();
+            scroll_up(count: usize);
+            scroll_down(count: usize);
+            insert_blank_lines(count: usize);
+            delete_lines(count: usize);
+            erase_chars(count: usize);
+            delete_chars(count: usize);
+            move_backward_tabs(count: u16);
+            move_forward_tabs(count: u16);
+            save_cursor_position();
+            restore_cursor_position();
+            clear_line(mode: LineClearMode);
+            clear_screen(mode: ClearMode);
+            clear_tabs(mode: TabulationClearMode);
+            set_tabs(interval: u16);
+            reset_state();
+            reverse_index();
+            terminal_attribute(attr: Attr);
+            set_mode(mode: Mode);
+            unset_mode(mode: Mode);
+            report_mode(mode: Mode);
+            set_private_mode(mode: PrivateMode);
+            unset_private_mode(mode: PrivateMode);
+            report_private_mode(mode: PrivateMode);
+            set_scrolling_region(top: usize, bottom: Option<usize>);
+            set_keypad_application_mode();
+            unset_keypad_application_mode();
+            set_active_charset(index: CharsetIndex);
+            configure_charset(index: CharsetIndex, charset: StandardCharset);
+            set_color(index: usize, color: Rgb);
+            reset_color(index: usize);
+            decaln();
+            push_title();
+            pop_title();
+            text_area_size_pixels();
+            text_area_size_chars();
+            set_hyperlink(hyperlink: Option<Hyperlink>);
+            report_keyboard_mode();
+            push_keyboard_mode(mode: KeyboardModes);
+            pop_keyboard_modes(to_pop: u16);
+            set_keyboard_mode(mode: KeyboardModes, behavior: KeyboardModesApplyBehavior);
+            set_modify_other_keys(mode: ModifyOtherKeys);
+            report_modify_other_keys();
+            set_scp(char_path: ScpCharPath, update_mode: ScpUpdateMode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ambiguous_width_follows_the_setting() {
+        assert!(is_ambiguous('①'));
+        assert!(!is_ambiguous('界'));
+        assert!(!is_ambiguous('a'));
+        assert_eq!(str_width("①→a", AmbiguousWidth::Narrow), 3);
+        assert_eq!(str_width("①→a", AmbiguousWidth::Wide), 5);
+        assert_eq!(str_width("界e\u{301}", AmbiguousWidth::Narrow), 3);
+    }
+
+    #[test]
+    fn clusters_join_across_cells() {
+        assert!(joins_next_cell("👨\u{200d}", '👩'));
+        assert!(!joins_next_cell("👨", '👩'));
+        assert!(joins_next_cell("🇺", '🇸'));
+        assert!(!joins_next_cell("🇺🇸", '🇫'));
+        assert!(!joins_next_cell("a", '🇸'));
+    }
+
+    /// Grid snapshots: each cell as `text|` (two-column cells as `text=|`), spacers omitted.
+    #[cfg(target_os = "macos")]
+    mod snapshots {
+        use super::super::{AmbiguousWidth, WidthPolicyHandler};
+        use alacritty_terminal::event::VoidListener;
+        use alacritty_terminal::grid::Dimensions;
+        use alacritty_terminal::index::{Column, Line};
+        use alacritty_terminal::term::cell::Flags;
+        use alacritty_terminal::term::test::TermSize;
+        use alacritty_terminal::term::{Config, Term};
+        use alacritty_terminal::vte::ansi::{Processor, StdSyncHandler};
+
+        fn snapshot(text: &str, columns: usize, ambiguous: AmbiguousWidth) -> (Vec<String>, (usize, usize)) {
+            let mut term = Term::new(Config::default(), &TermSize::new(columns, 3), VoidListener);
+            let mut parser = Processor::<StdSyncHandler>::new();
+            parser.advance(&mut WidthPolicyHandler::new(&mut term, ambiguous), text.as_bytes());
+
+            let rows = (0..term.screen_lines() as i32)
+                .map(|line| {
+                    let row = &term.grid()[Line(line)];
+                    let mut out = String::new();
+                    for col in 0..term.columns() {
+                        let cell = &row[Column(col)];
+                        if cell.flags.intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER) {
+                            continue;
+                        }
+                        out.push(cell.c);
+                        out.extend(cell.zerowidth().into_iter().flatten());
+                        if cell.flags.contains(Flags::WIDE_CHAR) {
+                            out.push('=');
+                        }
+                        out.push('|');
+                    }
+                    out.trim_end_matches(" |").trim_end_matches('|').to_string()
+                })
+                .collect();
+            let cursor = term.grid().cursor.point;
+            (rows, (cursor.line.0 as usize, cursor.column.0))
+        }
+
+        #[test]
+        fn cjk_and_combining_text() {
+            let (rows, cursor) = snapshot("界a e\u{301}x", 8, AmbiguousWidth::Narrow);
+            assert_eq!(rows[0], "界=|a| |e\u{301}|x");
+            assert_eq!(cursor, (0, 6));
+        }
+
+        #[test]
+        fn emoji_sequences_keep_per_character_widths() {
+            let (rows, cursor) = snapshot("👨\u{200d}👧 ❤\u{fe0f}!", 10, AmbiguousWidth::Narrow);
+            assert_eq!(rows[0], "👨\u{200d}=|👧=| |❤\u{fe0f}|!");
+            assert_eq!(cursor, (0, 7));
+        }
+
+        #[test]
+        fn ambiguous_characters_follow_the_setting() {
+            let (narrow, cursor) = snapshot("①b", 6, AmbiguousWidth::Narrow);
+            assert_eq!(narrow[0], "①|b");
+            assert_eq!(cursor, (0, 2));
+
+            let (wide, cursor) = snapshot("①b", 6, AmbiguousWidth::Wide);
+            assert_eq!(wide[0], "①=|b");
+            assert_eq!(cursor, (0, 3));
+
+            // A wide ambiguous character at the last column wraps like any wide character
+            let (wrapped, cursor) = snapshot("abc→\u{301}", 4, AmbiguousWidth::Wide);
+            assert_eq!(wrapped[0], "a|b|c");
+            assert_eq!(wrapped[1], "→\u{301}=");
+            assert_eq!(cursor, (1, 2));
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! tmux control mode (`tmux -CC`) protocol.
+//!
+//! When tmux is started with `-CC` in a pane, it wraps a line-based protocol in a DCS
+//! sequence: pane output arrives as `%output` notifications, window changes as
+//! `%window-*` / `%layout-change`, and command replies between `%begin` and `%end`.
+//! Tide shows every tmux window as a workspace tab and every tmux pane as a terminal
+//! pane; keystrokes and sizes go back as tmux commands on the same channel.
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// DCS sequence tmux prints when it enters control mode.
+pub const CONTROL_MODE_START: &[u8] = b"\x1bP1000p";
+
+/// Largest key chunk sent in one `send-keys` command.
+const SEND_KEYS_CHUNK: usize = 256;
+
+/// A notification or command reply read from the control channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlEvent {
+    Output { pane: u32, data: Vec<u8> },
+    WindowAdd { window: u32 },
+    WindowClose { window: u32 },
+    WindowRenamed { window: u32, name: String },
+    LayoutChange { window: u32, layout: String },
+    /// Reply to a command sent by this client (`%begin` … `%end` or `%error`)
+    Reply { ok: bool, lines: Vec<String> },
+    Exit { reason: Option<String> },
+}
+
+/// Reply block being collected: its command number, whether this client sent the
+/// command, and the lines so far.
+struct OpenReply {
+    number: String,
+    ours: bool,
+    lines: Vec<String>,
+}
+
+/// Finds `CONTROL_MODE_START` in terminal output read in chunks. Bytes at the end of a
+/// chunk that may begin the introducer are held back until the next one, so an
+/// introducer split between two reads is still found.
+#[derive(Default)]
+pub struct ControlModeScanner {
+    held: Vec<u8>,
+}
+
+impl ControlModeScanner {
+    /// Split the next chunk of output at the introducer: the terminal output before it
+    /// and, once it has been found, the control channel output after it.
+    pub fn feed(&mut self, chunk: &[u8]) -> (Vec<u8>, Option<Vec<u8>>) {
+        let mut output = std::mem::take(&mut self.held);
+        output.extend_from_slice(chunk);
+        let start = output
+            .windows(CONTROL_MODE_START.len())
+            .position(|window| window == CONTROL_MODE_START);
+        if let Some(start) = start {
+            let channel = output.split_off(start + CONTROL_MODE_START.len());
+            output.truncate(start);
+            return (output, Some(channel));
+        }
+        let partial = (1..CONTROL_MODE_START.len())
+            .rev()
+            .find(|&len| output.ends_with(&CONTROL_MODE_START[..len]))
+            .unwrap_or(0);
+        self.held = output.split_off(output.len() - partial);
+        (output, None)
+    }
+}
+
+/// Incremental parser for the control channel; keeps partial lines across reads.
+#[derive(Default)]
+pub struct ControlParser {
+    line: Vec<u8>,
+    reply: Option<OpenReply>,
+}
+
+impl ControlParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a chunk of channel output. Returns the events it completed and, when
+    /// control mode ended (`ESC \`), the offset of the first byte after it: everything
+    /// from there on is ordinary terminal output again.
+    pub fn feed(&mut self, bytes: &[u8]) -> (Vec<ControlEvent>, Option<usize>) {
+        let mut events = Vec::new();
+        for (index, &byte) in bytes.iter().enumerate() {
+            if byte == b'\\' && self.line == b"\x1b" {
+                self.line.clear();
+                return (events, Some(index + 1));
+            }
+            if byte == b'\n' {
+                let mut line = std::mem::take(&mut self.line);
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                if let Some(event) = self.parse_line(&String::from_utf8_lossy(&line)) {
+                    events.push(event);
+                }
+            } else {
+                self.line.push(byte);
+            }
+        }
+        (events, None)
+    }
+
+    fn parse_line(&mut self, line: &str) -> Option<ControlEvent> {
+        if let Some(reply) = &mut self.reply {
+            let mut words = line.split(' ');
+            let keyword = words.next();
+            let number = words.nth(1);
+            if matches!(keyword, Some("%end" | "%error")) && number == Some(reply.number.as_str()) {
+                let reply = self.reply.take()?;
+                return reply.ours.then(|| ControlEvent::Reply {
+                    ok: keyword == Some("%end"),
+                    lines: reply.lines,
+                });
+            }
+            reply.lines.push(line.to_string());
+            return None;
+        }
+
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "%begin" => {
+                let mut fields = rest.split(' ');
+                let number = fields.nth(1).unwrap_or_default().to_string();
+                let flags: u32 = fields.next().and_then(|flags| flags.parse().ok()).unwrap_or(0);
+                self.reply = Some(OpenReply {
+                    number,
+                    ours: flags & 1 != 0,
+                    lines: Vec::new(),
+                });
+                None
+            }
+            "%output" => {
+                let (pane, data) = rest.split_once(' ').unwrap_or((rest, ""));
+                Some(ControlEvent::Output {
+                    pane: parse_id(pane, '%')?,
+                    data: unescape_output(data),
+                })
+            }
+            "%window-add" => Some(ControlEvent::WindowAdd { window: parse_id(rest, '@')? }),
+            "%window-close" | "%unlinked-window-close" => {
+                Some(ControlEvent::WindowClose { window: parse_id(rest, '@')? })
+            }
+            "%window-renamed" => {
+                let (window, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                Some(ControlEvent::WindowRenamed {
+                    window: parse_id(window, '@')?,
+                    name: name.to_string(),
+                })
+            }
+            "%layout-change" => {
+                let mut fields = rest.split(' ');
+                Some(ControlEvent::LayoutChange {
+                    window: parse_id(fields.next()?, '@')?,
+                    layout: fields.next()?.to_string(),
+                })
+            }
+            "%exit" => Some(ControlEvent::Exit {
+                reason: (!rest.is_empty()).then(|| rest.to_string()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Numeric id of a tmux object written with its sigil (`%3`, `@1`).
+fn parse_id(text: &str, sigil: char) -> Option<u32> {
+    text.strip_prefix(sigil)?.parse().ok()
+}
+
+/// Decode `%output` data: tmux escapes control characters and `\` as `\ooo` octal.
+pub fn unescape_output(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let octal = bytes.get(index + 1..index + 4).filter(|digits| {
+            bytes[index] == b'\\' && digits.iter().all(|digit| (b'0'..=b'7').contains(digit))
+        });
+        match octal {
+            Some(digits) => {
+                let value = digits.iter().fold(0u32, |value, digit| value * 8 + (digit - b'0') as u32);
+                out.push(value as u8);
+                index += 4;
+            }
+            None => {
+                out.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    out
+}
+
+/// A pane in a window layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutPane {
+    pub pane: u32,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// A tmux window and its panes, left to right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TmuxWindow {
+    pub id: u32,
+    pub name: String,
+    pub panes: Vec<LayoutPane>,
+}
+
+/// Panes of a window layout such as `b25d,80x24,0,0{40x24,0,0,1,39x24,41,0,2}`, in
+/// layout order. Tide shows panes side by side, so nested splits are flattened.
+pub fn parse_layout(layout: &str) -> Vec<LayoutPane> {
+    // Skip the checksum
+    let body = layout.split_once(',').map_or("", |(_, body)| body);
+    let mut panes = Vec::new();
+    parse_layout_cell(body.as_bytes(), &mut 0, &mut panes);
+    panes
+}
+
+fn parse_layout_cell(text: &[u8], pos: &mut usize, panes: &mut Vec<LayoutPane>) -> Option<()> {
+    let cols = read_number(text, pos)?;
+    expect(text, pos, b'x')?;
+    let rows = read_number(text, pos)?;
+    for _ in 0..2 {
+        expect(text, pos, b',')?;
+        read_number(text, pos)?;
+    }
+
+    match text.get(*pos) {
+        Some(b',') => {
+            *pos += 1;
+            let pane = read_number(text, pos)?;
+            panes.push(LayoutPane {
+                pane,
+                cols: cols as u16,
+                rows: rows as u16,
+            });
+        }
+        Some(&open @ (b'{' | b'[')) => {
+            let close = if open == b'{' { b'}' } else { b']' };
+            *pos += 1;
+            loop {
+                parse_layout_cell(text, pos, panes)?;
+                match text.get(*pos) {
+                    Some(b',') => *pos += 1,
+                    Some(&byte) if byte == close => {
+                        *pos += 1;
+                        break;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+fn read_number(text: &[u8], pos: &mut usize) -> Option<u32> {
+    let start = *pos;
+    while text.get(*pos).is_some_and(u8::is_ascii_digit) {
+        *pos += 1;
+    }
+    std::str::from_utf8(&text[start..*pos]).ok()?.parse().ok()
+}
+
+fn expect(text: &[u8], pos: &mut usize, byte: u8) -> Option<()> {
+    (text.get(*pos) == Some(&byte)).then(|| *pos += 1)
+}
+
+/// Command listing every window with its layout; see `parse_window_line`.
+pub const LIST_WINDOWS_COMMAND: &str = "list-windows -F \"#{window_id} #{window_layout} #{window_name}\"\n";
+
+/// Window from a line of `LIST_WINDOWS_COMMAND` output.
+pub fn parse_window_line(line: &str) -> Option<TmuxWindow> {
+    let (id, rest) = line.split_once(' ')?;
+    let (layout, name) = rest.split_once(' ').unwrap_or((rest, ""));
+    Some(TmuxWindow {
+        id: parse_id(id, '@')?,
+        name: name.to_string(),
+        panes: parse_layout(layout),
+    })
+}
+
+/// Commands typing `bytes` into `pane`, as hex so any byte survives.
+pub fn send_keys_commands(pane: u32, bytes: &[u8]) -> String {
+    let mut commands = String::new();
+    for chunk in bytes.chunks(SEND_KEYS_CHUNK) {
+        commands.push_str(&format!("send-keys -t %{pane} -H"));
+        for byte in chunk {
+            commands.push_str(&format!(" {byte:02x}"));
+        }
+        commands.push('\n');
+    }
+    commands
+}
+
+/// Commands sizing `window` to `window_cols` x `rows` and its pane `pane` to
+/// `cols` x `rows`.
+pub fn resize_commands(window: u32, pane: u32, cols: u16, rows: u16, window_cols: u16) -> String {
+    format!("refresh-client -C @{window}:{window_cols}x{rows}\nresize-pane -t %{pane} -x {cols} -y {rows}\n")
+}
+
+/// Command printing the current contents of `pane` with attributes.
+pub fn capture_pane_command(pane: u32) -> String {
+    format!("capture-pane -p -e -t %{pane}\n")
+}
+
+/// Command printing the cursor position of `pane` as `x y` (zero-based).
+pub fn cursor_position_command(pane: u32) -> String {
+    format!("display-message -p -t %{pane} \"#{{cursor_x}} #{{cursor_y}}\"\n")
+}
+
+/// Change to the set of tmux windows, delivered to the UI.
+#[derive(Debug, Clone)]
+pub enum TmuxEvent {
+    /// Windows of gateway `gateway` changed (added, closed, renamed or re-split).
+    /// `root` is the workspace the `tmux -CC` pane belongs to.
+    Windows {
+        gateway: usize,
+        root: std::path::PathBuf,
+        windows: Vec<TmuxWindow>,
+    },
+    /// Control mode ended (tmux detached or exited).
+    Exited { gateway: usize, reason: Option<String> },
+}
+
+type TmuxEventHandler = Arc<dyn Fn(TmuxEvent) + Send + Sync>;
+
+static TMUX_EVENT_HANDLER: OnceLock<Mutex<Option<TmuxEventHandler>>> = OnceLock::new();
+
+/// Register the handler receiving tmux window changes (called from terminal IO threads).
+pub fn set_tmux_event_handler(handler: TmuxEventHandler) {
+    let mutex = TMUX_EVENT_HANDLER.get_or_init(|| Mutex::new(None));
+    if let Ok(mut guard) = mutex.lock() {
+        *guard = Some(handler);
+    }
+}
+
+/// Deliver a tmux event to the registered handler.
+pub fn dispatch_tmux_event(event: TmuxEvent) {
+    let handler = TMUX_EVENT_HANDLER
+        .get()
+        .and_then(|mutex| mutex.lock().ok().and_then(|guard| guard.clone()));
+    match handler {
+        Some(handler) => handler(event),
+        None => crate::logging::log_line("WARN", "tmux event dropped: no handler registered"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Vec<ControlEvent> {
+        let (events, end) = ControlParser::new().feed(input.as_bytes());
+        assert_eq!(end, None);
+        events
+    }
+
+    #[test]
+    fn output_is_unescaped() {
+        assert_eq!(
+            parse("%output %0 echo hi\\015\\012hi\\134n\n"),
+            vec![ControlEvent::Output { pane: 0, data: b"echo hi\r\nhi\\n".to_vec() }]
+        );
+    }
+
+    #[test]
+    fn replies_are_reported_only_for_own_commands() {
+        let events = parse(
+            "%begin 1 260 0\n%end 1 260 0\n%window-add @0\n%begin 1 266 1\n@0 b25d,80x24,0,0,0 sh\n%end 1 266 1\n",
+        );
+        assert_eq!(
+            events,
+            vec![
+                ControlEvent::WindowAdd { window: 0 },
+                ControlEvent::Reply { ok: true, lines: vec!["@0 b25d,80x24,0,0,0 sh".to_string()] },
+            ]
+        );
+    }
+
+    #[test]
+    fn notifications_and_exit_are_parsed() {
+        let mut parser = ControlParser::new();
+        let (events, end) = parser.feed(b"%window-renamed @2 my shell\r\n%layout-change @2 8205,80x24,0,0{40x24,0,0,0,39x24,41,0,1} 8205,80x24,0,0{40x24,0,0,0,39x24,41,0,1} *\n%exi");
+        assert_eq!(end, None);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], ControlEvent::WindowRenamed { window: 2, name: "my shell".to_string() });
+
+        let (events, end) = parser.feed(b"t\n\x1b\\$ ");
+        assert_eq!(events, vec![ControlEvent::Exit { reason: None }]);
+        assert_eq!(end, Some(4));
+    }
+
+    #[test]
+    fn control_mode_start_is_found_across_reads() {
+        let mut scanner = ControlModeScanner::default();
+        assert_eq!(scanner.feed(b"$ tmux -CC\r\n\x1bP10"), (b"$ tmux -CC\r\n".to_vec(), None));
+        assert_eq!(scanner.feed(b"00p%begin"), (Vec::new(), Some(b"%begin".to_vec())));
+
+        // Held bytes that turn out not to start control mode are output after all
+        let mut scanner = ControlModeScanner::default();
+        assert_eq!(scanner.feed(b"a\x1bP"), (b"a".to_vec(), None));
+        assert_eq!(scanner.feed(b"q\x1b\\"), (b"\x1bPq\x1b\\".to_vec(), None));
+        assert_eq!(scanner.feed(b"b\x1bP1000p"), (b"b".to_vec(), Some(Vec::new())));
+    }
+
+    #[test]
+    fn layouts_are_flattened_in_order() {
+        assert_eq!(
+            parse_layout("b25d,80x24,0,0,3"),
+            vec![LayoutPane { pane: 3, cols: 80, rows: 24 }]
+        );
+        let panes = parse_layout("1234,120x30,0,0{60x30,0,0,0,59x30,61,0[59x15,61,0,1,59x14,61,16,2]}");
+        let ids: Vec<u32> = panes.iter().map(|pane| pane.pane).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(panes[2], LayoutPane { pane: 2, cols: 59, rows: 14 });
+        assert!(parse_layout("garbage").is_empty());
+    }
+
+    #[test]
+    fn commands_are_formatted() {
+        assert_eq!(send_keys_commands(4, b"ls\r"), "send-keys -t %4 -H 6c 73 0d\n");
+        assert_eq!(send_keys_commands(1, &[b'a'; 300]).lines().count(), 2);
+        assert_eq!(
+            parse_window_line("@1 b25d,80x24,0,0,5 vim main.rs"),
+            Some(TmuxWindow {
+                id: 1,
+                name: "vim main.rs".to_string(),
+                panes: vec![LayoutPane { pane: 5, cols: 80, rows: 24 }],
+            })
+        );
+    }
+
+    /// Drive a real tmux server in control mode (`-C`, without the DCS wrapper). Needs
+    /// tmux, so it only runs with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "starts a local tmux server"]
+    fn local_tmux_round_trip() {
+        use std::io::{Read, Write};
+        use std::process::{Command, Stdio};
+        use std::sync::mpsc;
+        use std::time::{Duration, Instant};
+
+        let socket = format!("tide-test-{}", std::process::id());
+        let mut child = Command::new("tmux")
+            .args(["-L", &socket, "-f", "/dev/null", "-C", "new-session", "-x", "80", "-y", "24", "sh"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("tmux should be installed");
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = child.stdout.take().unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n @ 1..) = stdout.read(&mut buf) {
+                if sender.send(buf[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Parse output until `found` accepts an event, failing after a deadline
+        let mut parser = ControlParser::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut wait_for = |found: &mut dyn FnMut(&ControlEvent) -> bool| loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            let chunk = receiver.recv_timeout(left).expect("tmux should answer in time");
+            if parser.feed(&chunk).0.iter().any(&mut *found) {
+                break;
+            }
+        };
+
+        stdin.write_all(LIST_WINDOWS_COMMAND.as_bytes()).unwrap();
+        let mut window = None;
+        wait_for(&mut |event| {
+            if let ControlEvent::Reply { ok: true, lines } = event {
+                window = lines.first().and_then(|line| parse_window_line(line));
+            }
+            window.is_some()
+        });
+        let window = window.unwrap();
+        assert_eq!(window.panes.len(), 1);
+        assert_eq!((window.panes[0].cols, window.panes[0].rows), (80, 24));
+
+        let pane = window.panes[0].pane;
+        stdin.write_all(send_keys_commands(pane, b"echo tide-$((40+2))\r").as_bytes()).unwrap();
+        let mut output = Vec::new();
+        wait_for(&mut |event| {
+            if let ControlEvent::Output { pane: from, data } = event {
+                if *from == pane {
+                    output.extend_from_slice(data);
+                }
+            }
+            String::from_utf8_lossy(&output).contains("tide-42")
+        });
+
+        stdin.write_all(b"kill-server\n").unwrap();
+        let _ = child.wait();
+    }
+}
@@ -59,6 +59,14 @@ pub struct TerminalPalette {
     pub normal: [AnsiRgb; 8],
     pub bright: [AnsiRgb; 8],
     pub dim: [AnsiRgb; 8],
+    /// Cursor color (`None` = use the UI accent)
+    pub cursor: Option<AnsiRgb>,
+    /// Color of the character under a block cursor (`None` = keep cell color)
+    pub cursor_text: Option<AnsiRgb>,
+    /// Selection background (`None` = built-in selection colors)
+    pub selection_background: Option<AnsiRgb>,
+    /// Selection foreground (`None` = built-in selection colors)
+    pub selection_foreground: Option<AnsiRgb>,
 }
 
 impl TerminalPalette {
@@ -110,6 +118,10 @@ impl TerminalPalette {
             normal,
             bright,
             dim,
+            cursor: None,
+            cursor_text: None,
+            selection_background: None,
+            selection_foreground: None,
         }
     }
 }